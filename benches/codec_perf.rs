@@ -30,6 +30,7 @@ fn make_send_message() -> SendMessageRequest {
                   that mirrors a realistic IM payload across the wire."
             .as_bytes()
             .to_vec(),
+        expire_mode: ExpireMode::AfterSend,
     }
 }
 
@@ -58,6 +59,7 @@ fn make_push_message() -> PushMessageRequest {
             .as_bytes()
             .to_vec(),
         deleted: false,
+        expire_mode: ExpireMode::AfterSend,
     }
 }
 
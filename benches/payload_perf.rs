@@ -19,8 +19,8 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
 use privchat_protocol::message::{ContentMessageType, LocalMessagePayloadEnvelope};
 use privchat_protocol::{
-    decode_message, encode_message, ImageMetadata, MessageMetadata, MessagePayloadEnvelope,
-    MessageSetting, MessageSource, SendMessageRequest,
+    decode_message, encode_message, ExpireMode, ImageMetadata, MessageMetadata,
+    MessagePayloadEnvelope, MessageSetting, MessageSource, SendMessageRequest,
 };
 
 // ---------------------------------------------------------------------------
@@ -219,6 +219,7 @@ fn make_send_request_with_payload(payload_bytes: Vec<u8>) -> SendMessageRequest
         from_uid: 20002,
         topic: "general".to_string(),
         payload: payload_bytes,
+        expire_mode: ExpireMode::AfterSend,
     }
 }
 
@@ -23,6 +23,7 @@ const SCHEMAS: &[&str] = &[
     "content.fbs",
     "timeline.fbs",
     "entity_sync.fbs",
+    "presence.fbs",
 ];
 
 const SCHEMA_MODULES: &[&str] = &[
@@ -39,6 +40,7 @@ const SCHEMA_MODULES: &[&str] = &[
     "content_generated",
     "timeline_generated",
     "entity_sync_generated",
+    "presence_generated",
 ];
 
 fn main() {
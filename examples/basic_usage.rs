@@ -86,6 +86,7 @@ fn main() {
         from_uid: 123,
         topic: "chat".to_string(),
         payload: "Hello, World!".as_bytes().to_vec(),
+        expire_mode: ExpireMode::AfterSend,
     };
 
     let send_resp = SendMessageResponse {
@@ -121,6 +122,7 @@ fn main() {
         from_uid: 456,
         payload: "Hello back!".as_bytes().to_vec(),
         deleted: false,
+        expire_mode: ExpireMode::AfterSend,
     };
 
     let recv_resp = PushMessageResponse {
@@ -79,6 +79,7 @@ fn send_request_fixture() -> SendMessageRequest {
         from_uid: 999,
         topic: String::new(),
         payload: br#"{"content":"hi"}"#.to_vec(),
+        expire_mode: ExpireMode::AfterSend,
     }
 }
 
@@ -113,6 +114,7 @@ fn push_message_fixture() -> PushMessageRequest {
         from_uid: 999,
         payload: br#"{"content":"hi"}"#.to_vec(),
         deleted: false,
+        expire_mode: ExpireMode::AfterSend,
     }
 }
 
@@ -336,6 +338,7 @@ fn payload_forward_fixture() -> MessagePayloadEnvelope {
                     extra: vec![],
                 },
             ],
+            items: vec![],
         })),
         reply_to_message_id: None,
         mentioned_user_ids: vec![],
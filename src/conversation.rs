@@ -0,0 +1,575 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! 会话列表模型与统一排序。
+//!
+//! 会话列表由 `ChannelSyncPayload`、`ChannelExtraSyncPayload`、
+//! `ChannelUnreadSyncPayload`、`channel_draft` 实体、置顶 / 隐藏 / 静音状态和
+//! 最新一条消息拼出来，以前各端各拼各的、排序也不一样。
+//! [`ConversationSummary`] 把这些来源合并成一行，[`compare_conversations`]
+//! 是唯一的排序规则：
+//!
+//! 1. 置顶会话在前，置顶之间按置顶时间倒序；
+//! 2. 其余按最后活跃 `(timestamp, pts)` 倒序；
+//! 3. 仍相同时按 `channel_id` 倒序，保证全序、各端结果一致。
+//!
+//! 各 `apply_*` 只覆盖负载里出现的字段；最后活跃只前进不后退，迟到的旧
+//! 同步负载不会把会话往下拽。
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use crate::rpc::sync::{
+    ChannelDraftSyncPayload, ChannelExtraSyncPayload, ChannelSyncPayload, ChannelUnreadSyncPayload,
+};
+use crate::unread::ChannelUnread;
+use crate::PushMessageRequest;
+
+/// 会话最后一条消息的摘要。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LastMessagePreview {
+    pub server_message_id: Option<u64>,
+    pub from_uid: u64,
+    /// 列表上展示的文本（已按消息类型处理好，如「[图片]」）
+    pub text: String,
+    /// 发送时间（毫秒）
+    pub timestamp: i64,
+    /// 频道 pts；同步负载只有时间没有 pts 时为 0
+    pub pts: u64,
+}
+
+impl LastMessagePreview {
+    /// 由推送构造。`PushMessageRequest.timestamp` 为秒，这里换算成毫秒。
+    pub fn from_push(push: &PushMessageRequest, pts: u64, text: impl Into<String>) -> Self {
+        Self {
+            server_message_id: Some(push.server_message_id).filter(|id| *id != 0),
+            from_uid: push.from_uid,
+            text: text.into(),
+            timestamp: i64::from(push.timestamp) * 1000,
+            pts,
+        }
+    }
+}
+
+/// 会话列表中的一行。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConversationSummary {
+    pub channel_id: u64,
+    pub channel_type: u8,
+    pub name: String,
+    pub avatar: Option<String>,
+    /// 私聊对端用户 ID
+    pub peer_user_id: Option<u64>,
+    /// 置顶时间（毫秒）；`None` = 未置顶
+    pub pinned_at: Option<i64>,
+    pub muted: bool,
+    pub hidden: bool,
+    pub unread_count: u32,
+    pub mention_count: u32,
+    pub last_message: Option<LastMessagePreview>,
+    /// 最后活跃时间（毫秒），排序依据之一
+    pub last_activity_at: i64,
+    /// 最后活跃 pts，同一毫秒内的次序
+    pub last_activity_pts: u64,
+    pub draft: Option<String>,
+    pub draft_updated_at: i64,
+    /// 最近一次采用的 `channel_draft` 负载，同一毫秒的并发草稿按它决胜负
+    pub draft_payload: Option<ChannelDraftSyncPayload>,
+}
+
+fn channel_type_of(channel_type: Option<i64>, type_field: Option<i64>) -> Option<u8> {
+    channel_type
+        .or(type_field)
+        .and_then(|t| u8::try_from(t).ok())
+}
+
+impl ConversationSummary {
+    pub fn new(channel_id: u64, channel_type: u8) -> Self {
+        Self {
+            channel_id,
+            channel_type,
+            ..Default::default()
+        }
+    }
+
+    /// 由频道同步负载新建；负载缺 `channel_id` 时返回 `None`。
+    pub fn from_channel(payload: &ChannelSyncPayload) -> Option<Self> {
+        let channel_id = payload.channel_id?;
+        let channel_type = channel_type_of(payload.channel_type, payload.type_field).unwrap_or(0);
+        let mut summary = Self::new(channel_id, channel_type);
+        summary.apply_channel(payload);
+        Some(summary)
+    }
+
+    pub fn is_pinned(&self) -> bool {
+        self.pinned_at.is_some()
+    }
+
+    /// 排序用的最后活跃键。
+    pub fn activity_key(&self) -> (i64, u64) {
+        (self.last_activity_at, self.last_activity_pts)
+    }
+
+    fn touch(&mut self, timestamp: i64, pts: u64) -> bool {
+        if (timestamp, pts) > self.activity_key() {
+            self.last_activity_at = timestamp;
+            self.last_activity_pts = pts;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn apply_channel(&mut self, payload: &ChannelSyncPayload) {
+        if let Some(channel_type) = channel_type_of(payload.channel_type, payload.type_field) {
+            self.channel_type = channel_type;
+        }
+        if let Some(name) = payload.channel_name.as_ref().or(payload.name.as_ref()) {
+            self.name = name.clone();
+        }
+        if payload.avatar.is_some() {
+            self.avatar = payload.avatar.clone();
+        }
+        if payload.peer_user_id.is_some() {
+            self.peer_user_id = payload.peer_user_id;
+        }
+        if let Some(unread) = payload.unread_count {
+            self.unread_count = unread.max(0) as u32;
+        }
+        if let Some(top) = payload.top {
+            self.pinned_at =
+                (top > 0).then(|| payload.pinned_at.or(self.pinned_at).unwrap_or_default());
+        }
+        if let Some(mute) = payload.mute {
+            self.muted = mute > 0;
+        }
+        if let Some(hidden) = payload.hidden {
+            self.hidden = hidden > 0;
+        }
+        if let Some(timestamp) = payload.last_msg_timestamp {
+            if self.touch(timestamp, 0) {
+                self.last_message =
+                    payload
+                        .last_msg_content
+                        .as_ref()
+                        .map(|text| LastMessagePreview {
+                            text: text.clone(),
+                            timestamp,
+                            ..Default::default()
+                        });
+            }
+        }
+    }
+
+    /// 旧版纯文本草稿；`channel_draft` 实体见 [`Self::apply_draft`]。
+    pub fn apply_extra(&mut self, payload: &ChannelExtraSyncPayload) {
+        let updated_at = payload
+            .draft_updated_at
+            .and_then(|t| i64::try_from(t).ok())
+            .unwrap_or_default();
+        if payload.draft.is_some() && updated_at >= self.draft_updated_at {
+            self.draft = payload.draft.clone().filter(|d| !d.is_empty());
+            self.draft_updated_at = updated_at;
+        }
+    }
+
+    /// `channel_draft` 实体（last-writer-wins，平局按
+    /// [`ChannelDraftSyncPayload::should_replace`] 决定，与到达顺序无关）。
+    pub fn apply_draft(&mut self, draft: &ChannelDraftSyncPayload) {
+        if draft.updated_at < self.draft_updated_at
+            || self
+                .draft_payload
+                .as_ref()
+                .is_some_and(|current| !current.should_replace(draft))
+        {
+            return;
+        }
+        self.draft = (!draft.is_cleared()).then(|| draft.text.clone());
+        self.draft_updated_at = draft.updated_at;
+        self.draft_payload = Some(draft.clone());
+    }
+
+    pub fn apply_unread(&mut self, payload: &ChannelUnreadSyncPayload) {
+        if let Some(unread) = payload.unread_count {
+            self.unread_count = unread.max(0) as u32;
+        }
+    }
+
+    /// 以 [`crate::unread::UnreadCalculator`] 的结果为准（含 @ 未读）。
+    pub fn apply_unread_view(&mut self, view: &ChannelUnread) {
+        self.unread_count = view.unread_count;
+        self.mention_count = view.mention_count;
+        self.muted = view.muted;
+    }
+
+    /// 新消息：更新摘要与最后活跃，并取消隐藏。比当前更旧的消息被忽略。
+    pub fn apply_message(&mut self, message: LastMessagePreview) {
+        if self.touch(message.timestamp, message.pts) {
+            self.last_message = Some(message);
+            self.hidden = false;
+        }
+    }
+
+    /// `channel/pin` 的结果。
+    pub fn apply_pin(&mut self, pinned: bool, at_ms: i64) {
+        self.pinned_at = pinned.then_some(at_ms);
+    }
+
+    pub fn apply_mute(&mut self, muted: bool) {
+        self.muted = muted;
+    }
+
+    pub fn apply_hide(&mut self, hidden: bool) {
+        self.hidden = hidden;
+    }
+}
+
+/// 会话列表统一排序：列表从上到下即 `Ordering::Less` 到 `Greater`。
+pub fn compare_conversations(a: &ConversationSummary, b: &ConversationSummary) -> Ordering {
+    match (a.pinned_at, b.pinned_at) {
+        (Some(x), Some(y)) => y.cmp(&x),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+    .then_with(|| b.activity_key().cmp(&a.activity_key()))
+    .then_with(|| b.channel_id.cmp(&a.channel_id))
+}
+
+/// 始终保持有序的会话列表。
+#[derive(Debug, Clone, Default)]
+pub struct ConversationList {
+    items: Vec<ConversationSummary>,
+}
+
+impl ConversationList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 同一频道出现多次时保留活跃度（`(timestamp, pts)`）最新的一条，
+    /// 相同时保留靠后的一条。
+    pub fn from_summaries(items: Vec<ConversationSummary>) -> Self {
+        let mut latest: HashMap<u64, ConversationSummary> = HashMap::with_capacity(items.len());
+        for summary in items {
+            match latest.get(&summary.channel_id) {
+                Some(existing) if existing.activity_key() > summary.activity_key() => {}
+                _ => {
+                    latest.insert(summary.channel_id, summary);
+                }
+            }
+        }
+        let mut items: Vec<_> = latest.into_values().collect();
+        items.sort_by(compare_conversations);
+        Self { items }
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// 全部会话（含隐藏），已排序。
+    pub fn all(&self) -> &[ConversationSummary] {
+        &self.items
+    }
+
+    /// 列表上应展示的会话（排除隐藏）。
+    pub fn visible(&self) -> impl Iterator<Item = &ConversationSummary> {
+        self.items.iter().filter(|s| !s.hidden)
+    }
+
+    pub fn get(&self, channel_id: u64) -> Option<&ConversationSummary> {
+        self.items.iter().find(|s| s.channel_id == channel_id)
+    }
+
+    /// 修改一行并移动到新位置；不存在时以 `new(channel_id, channel_type)`
+    /// 新建。返回修改后的下标。
+    pub fn update(
+        &mut self,
+        channel_id: u64,
+        channel_type: u8,
+        f: impl FnOnce(&mut ConversationSummary),
+    ) -> usize {
+        let mut summary = match self.items.iter().position(|s| s.channel_id == channel_id) {
+            Some(index) => self.items.remove(index),
+            None => ConversationSummary::new(channel_id, channel_type),
+        };
+        f(&mut summary);
+        self.insert_sorted(summary)
+    }
+
+    /// 整行替换（或插入），返回下标。
+    pub fn upsert(&mut self, summary: ConversationSummary) -> usize {
+        self.remove(summary.channel_id);
+        self.insert_sorted(summary)
+    }
+
+    pub fn remove(&mut self, channel_id: u64) -> Option<ConversationSummary> {
+        let index = self.items.iter().position(|s| s.channel_id == channel_id)?;
+        Some(self.items.remove(index))
+    }
+
+    pub fn apply_channel(&mut self, payload: &ChannelSyncPayload) -> Option<usize> {
+        let channel_id = payload.channel_id?;
+        let channel_type = channel_type_of(payload.channel_type, payload.type_field).unwrap_or(0);
+        Some(self.update(channel_id, channel_type, |s| s.apply_channel(payload)))
+    }
+
+    pub fn apply_extra(&mut self, payload: &ChannelExtraSyncPayload) -> Option<usize> {
+        let channel_id = payload.channel_id?;
+        let channel_type = channel_type_of(payload.channel_type, payload.type_field).unwrap_or(0);
+        Some(self.update(channel_id, channel_type, |s| s.apply_extra(payload)))
+    }
+
+    pub fn apply_unread(&mut self, payload: &ChannelUnreadSyncPayload) -> Option<usize> {
+        let channel_id = payload.channel_id?;
+        let channel_type = channel_type_of(payload.channel_type, payload.type_field).unwrap_or(0);
+        Some(self.update(channel_id, channel_type, |s| s.apply_unread(payload)))
+    }
+
+    pub fn apply_draft(&mut self, draft: &ChannelDraftSyncPayload) -> usize {
+        self.update(draft.channel_id, draft.channel_type, |s| {
+            s.apply_draft(draft)
+        })
+    }
+
+    pub fn apply_message(
+        &mut self,
+        channel_id: u64,
+        channel_type: u8,
+        message: LastMessagePreview,
+    ) -> usize {
+        self.update(channel_id, channel_type, |s| s.apply_message(message))
+    }
+
+    fn insert_sorted(&mut self, summary: ConversationSummary) -> usize {
+        let index = self
+            .items
+            .partition_point(|s| compare_conversations(s, &summary) == Ordering::Less);
+        self.items.insert(index, summary);
+        index
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(channel_id: u64, pinned_at: Option<i64>, at: i64, pts: u64) -> ConversationSummary {
+        ConversationSummary {
+            channel_id,
+            channel_type: 2,
+            pinned_at,
+            last_activity_at: at,
+            last_activity_pts: pts,
+            ..Default::default()
+        }
+    }
+
+    fn ids(list: &ConversationList) -> Vec<u64> {
+        list.all().iter().map(|s| s.channel_id).collect()
+    }
+
+    #[test]
+    fn pinned_first_then_activity_then_channel_id() {
+        let list = ConversationList::from_summaries(vec![
+            row(1, None, 100, 1),
+            row(2, Some(10), 1, 1),
+            row(3, None, 100, 5),
+            row(4, Some(20), 0, 0),
+            row(5, None, 200, 0),
+            row(6, None, 100, 5),
+        ]);
+        assert_eq!(ids(&list), vec![4, 2, 5, 6, 3, 1]);
+    }
+
+    #[test]
+    fn from_summaries_dedupes_non_adjacent_rows() {
+        // 同一频道的两条记录排序后不相邻：一条被置顶、一条很旧
+        let list = ConversationList::from_summaries(vec![
+            row(1, Some(10), 50, 1),
+            row(2, None, 100, 1),
+            row(3, None, 80, 1),
+            row(1, None, 10, 1),
+        ]);
+        assert_eq!(ids(&list), vec![1, 2, 3]);
+        assert_eq!(list.get(1).unwrap().pinned_at, Some(10));
+
+        let list = ConversationList::from_summaries(vec![
+            row(7, None, 10, 1),
+            row(8, None, 50, 1),
+            row(7, None, 90, 2),
+        ]);
+        assert_eq!(ids(&list), vec![7, 8]);
+        assert_eq!(list.get(7).unwrap().last_activity_at, 90);
+    }
+
+    #[test]
+    fn incremental_updates_move_rows() {
+        let mut list =
+            ConversationList::from_summaries(vec![row(1, None, 100, 1), row(2, None, 200, 1)]);
+        let message = LastMessagePreview {
+            text: "hi".to_string(),
+            timestamp: 300,
+            pts: 2,
+            ..Default::default()
+        };
+        assert_eq!(list.apply_message(1, 2, message), 0);
+        assert_eq!(ids(&list), vec![1, 2]);
+
+        // 旧消息不会改变摘要或位置
+        let stale = LastMessagePreview {
+            text: "old".to_string(),
+            timestamp: 50,
+            ..Default::default()
+        };
+        list.apply_message(1, 2, stale);
+        assert_eq!(
+            list.get(1).unwrap().last_message.as_ref().unwrap().text,
+            "hi"
+        );
+
+        list.update(2, 2, |s| s.apply_pin(true, 1_000));
+        assert_eq!(ids(&list), vec![2, 1]);
+        list.update(2, 2, |s| s.apply_pin(false, 0));
+        assert_eq!(ids(&list), vec![1, 2]);
+
+        // 新会话
+        assert_eq!(list.apply_message(9, 1, LastMessagePreview::default()), 2);
+        assert_eq!(list.len(), 3);
+    }
+
+    #[test]
+    fn channel_payload_merges_only_present_fields() {
+        let mut list = ConversationList::new();
+        list.apply_channel(&ChannelSyncPayload {
+            channel_id: Some(7),
+            type_field: Some(2),
+            name: Some("team".to_string()),
+            last_msg_content: Some("hello".to_string()),
+            last_msg_timestamp: Some(500),
+            top: Some(1),
+            pinned_at: Some(42),
+            ..Default::default()
+        });
+        let s = list.get(7).unwrap();
+        assert_eq!((s.channel_type, s.name.as_str()), (2, "team"));
+        assert_eq!(s.pinned_at, Some(42));
+        assert_eq!(s.activity_key(), (500, 0));
+
+        // 部分负载：不带 pinned_at 的 top 保留原置顶时间，旧时间戳不回退
+        list.apply_channel(&ChannelSyncPayload {
+            channel_id: Some(7),
+            top: Some(1),
+            mute: Some(1),
+            last_msg_timestamp: Some(100),
+            last_msg_content: Some("older".to_string()),
+            ..Default::default()
+        });
+        let s = list.get(7).unwrap();
+        assert_eq!(s.name, "team");
+        assert_eq!(s.pinned_at, Some(42));
+        assert!(s.muted);
+        assert_eq!(s.last_message.as_ref().unwrap().text, "hello");
+
+        list.apply_unread(&ChannelUnreadSyncPayload {
+            channel_id: Some(7),
+            unread_count: Some(3),
+            ..Default::default()
+        });
+        assert_eq!(list.get(7).unwrap().unread_count, 3);
+        assert!(list.apply_channel(&ChannelSyncPayload::default()).is_none());
+    }
+
+    #[test]
+    fn hidden_rows_reappear_on_new_message() {
+        let mut list = ConversationList::new();
+        list.update(1, 1, |s| s.apply_hide(true));
+        assert_eq!(list.visible().count(), 0);
+        list.apply_message(
+            1,
+            1,
+            LastMessagePreview {
+                timestamp: 1,
+                ..Default::default()
+            },
+        );
+        assert_eq!(list.visible().count(), 1);
+    }
+
+    #[test]
+    fn drafts_are_last_writer_wins() {
+        let mut summary = ConversationSummary::new(1, 1);
+        summary.apply_draft(&ChannelDraftSyncPayload {
+            channel_id: 1,
+            channel_type: 1,
+            text: "new".to_string(),
+            updated_at: 20,
+            ..Default::default()
+        });
+        summary.apply_extra(&ChannelExtraSyncPayload {
+            draft: Some("legacy".to_string()),
+            draft_updated_at: Some(10),
+            ..Default::default()
+        });
+        assert_eq!(summary.draft.as_deref(), Some("new"));
+        summary.apply_draft(&ChannelDraftSyncPayload {
+            channel_id: 1,
+            updated_at: 30,
+            ..Default::default()
+        });
+        assert_eq!(summary.draft, None);
+    }
+
+    #[test]
+    fn concurrent_drafts_converge_regardless_of_arrival_order() {
+        let draft = |text: &str| ChannelDraftSyncPayload {
+            channel_id: 1,
+            channel_type: 1,
+            text: text.to_string(),
+            updated_at: 40,
+            ..Default::default()
+        };
+        let (a, b) = (draft("from phone"), draft("from desktop"));
+        let mut left = ConversationSummary::new(1, 1);
+        left.apply_draft(&a);
+        left.apply_draft(&b);
+        let mut right = ConversationSummary::new(1, 1);
+        right.apply_draft(&b);
+        right.apply_draft(&a);
+        assert_eq!(left.draft, right.draft);
+        assert_eq!(left.draft, Some(a.clone().merge(b).text));
+    }
+
+    #[test]
+    fn push_preview_uses_milliseconds() {
+        let push = PushMessageRequest {
+            server_message_id: 9,
+            timestamp: 1_700_000_000,
+            from_uid: 3,
+            ..Default::default()
+        };
+        let preview = LastMessagePreview::from_push(&push, 12, "[图片]");
+        assert_eq!(preview.timestamp, 1_700_000_000_000);
+        assert_eq!(preview.server_message_id, Some(9));
+        assert_eq!(preview.pts, 12);
+    }
+}
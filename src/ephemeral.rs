@@ -0,0 +1,289 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! 阅后即焚 —— 消息过期截止时间的统一计算。
+//!
+//! `SendMessageRequest.expire` / `PushMessageRequest.expire` 是 TTL（秒），
+//! 0 = 永不过期；计时起点由同包的 `expire_mode` 决定：
+//!
+//! - [`ExpireMode::AfterSend`]：从 server 发送时间开始计时；
+//! - [`ExpireMode::AfterRead`]：从**发送者以外**任一成员第一次读到该消息开始
+//!   计时（即第一条覆盖该消息 pts 的已读游标推进）。
+//!
+//! server 与客户端都用 [`ExpiringMessage::deadline`] 从同一组输入（消息 pts、
+//! 发送时间、已读游标）推导出同一个毫秒截止时间，到点同时删除。截止时间一经
+//! 确定，server 再往 canonical timeline 写一条 [`ExpireEvent`] 把它固化下来
+//! —— 离线期间错过游标中间推进的客户端只能看到更晚的游标快照，推导出的
+//! 截止时间只会偏晚不会偏早，收到事件后以事件为准。
+
+use crate::rpc::sync::{ChannelReadCursorSyncPayload, ChannelSyncPayload};
+use crate::{ExpireEvent, ExpireMode, PushMessageRequest, SendMessageRequest};
+
+/// 频道级阅后即焚设置（`channel` 实体的 `ephemeral_ttl` / `ephemeral_mode`）。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ChannelEphemeralSetting {
+    /// 消息存活时长（秒），0 = 未开启
+    pub ttl_seconds: u32,
+    /// 计时起点
+    pub mode: ExpireMode,
+}
+
+impl ChannelEphemeralSetting {
+    /// 从 `channel` 实体同步负载读取；字段缺省视为未开启。
+    pub fn from_sync_payload(payload: &ChannelSyncPayload) -> Self {
+        Self {
+            ttl_seconds: payload.ephemeral_ttl.unwrap_or(0),
+            mode: payload.ephemeral_mode.unwrap_or_default(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.ttl_seconds > 0
+    }
+
+    /// server 受理 `SendMessageRequest` 时按频道设置给消息盖章。
+    ///
+    /// 频道设置是 TTL 上限：计时起点一律用频道的；TTL 取客户端与频道中较短
+    /// 的一个，客户端未指定 `expire` 时用频道的。
+    ///
+    /// 起点不能听客户端的：频道 `AfterSend` 60s 时，客户端若能改成
+    /// `AfterRead` 5s，没人读的消息就永远不会过期，突破了频道上限。
+    pub fn stamp(&self, request: &mut SendMessageRequest) {
+        if !self.is_enabled() {
+            return;
+        }
+        if request.expire == 0 || request.expire > self.ttl_seconds {
+            request.expire = self.ttl_seconds;
+        }
+        request.expire_mode = self.mode;
+    }
+}
+
+/// 一次已读游标推进：`reader_id` 在 `read_at`（毫秒）时刻读到了 `last_read_pts`。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReadMark {
+    pub reader_id: u64,
+    pub last_read_pts: u64,
+    pub read_at: i64,
+}
+
+impl ReadMark {
+    /// 从 `channel_read_cursor` 实体同步负载读取；缺字段的负载不参与计算。
+    pub fn from_sync_payload(payload: &ChannelReadCursorSyncPayload) -> Option<Self> {
+        Some(Self {
+            reader_id: payload.reader_id?,
+            last_read_pts: payload.last_read_pts?,
+            read_at: payload.updated_at?,
+        })
+    }
+}
+
+/// 参与过期计算的消息视图。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExpiringMessage {
+    pub server_message_id: u64,
+    pub sender_id: u64,
+    /// 消息在频道内的 pts，与已读游标同一坐标系
+    pub pts: u64,
+    /// server 发送时间（毫秒）
+    pub sent_at: i64,
+    /// TTL（秒），0 = 永不过期
+    pub expire: u32,
+    pub expire_mode: ExpireMode,
+}
+
+impl ExpiringMessage {
+    /// 从推送构造。`PushMessageRequest.timestamp` 为秒级，这里换算成毫秒；
+    /// pts 不在推送包里，由调用方从同步上下文传入。
+    pub fn from_push(push: &PushMessageRequest, pts: u64) -> Self {
+        Self {
+            server_message_id: push.server_message_id,
+            sender_id: push.from_uid,
+            pts,
+            sent_at: i64::from(push.timestamp) * 1000,
+            expire: push.expire,
+            expire_mode: push.expire_mode,
+        }
+    }
+
+    /// 第一次被发送者以外的成员读到的时刻。
+    ///
+    /// 取所有覆盖本消息 pts 的游标推进中最早的一次；发送者自己的游标不算
+    /// —— 否则"发出即已读"会让 `AfterRead` 退化成 `AfterSend`。
+    pub fn first_read_at(&self, marks: &[ReadMark]) -> Option<i64> {
+        marks
+            .iter()
+            .filter(|mark| mark.reader_id != self.sender_id && mark.last_read_pts >= self.pts)
+            .map(|mark| mark.read_at)
+            .min()
+    }
+
+    /// 删除截止时间（毫秒）。`None` = 永不过期，或 `AfterRead` 尚未被读到。
+    pub fn deadline(&self, marks: &[ReadMark]) -> Option<i64> {
+        if self.expire == 0 {
+            return None;
+        }
+        let anchor = match self.expire_mode {
+            ExpireMode::AfterSend => self.sent_at,
+            ExpireMode::AfterRead => self.first_read_at(marks)?,
+        };
+        Some(anchor.saturating_add(i64::from(self.expire) * 1000))
+    }
+
+    pub fn is_expired(&self, marks: &[ReadMark], now_ms: i64) -> bool {
+        self.deadline(marks)
+            .is_some_and(|deadline| now_ms >= deadline)
+    }
+
+    /// 截止时间已确定时，生成写入 canonical timeline 的 [`ExpireEvent`]。
+    pub fn expire_event(&self, marks: &[ReadMark]) -> Option<ExpireEvent> {
+        Some(ExpireEvent {
+            target_server_message_id: self.server_message_id,
+            expire_at: self.deadline(marks)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SENDER: u64 = 10001;
+    const PEER: u64 = 10002;
+
+    fn message(expire: u32, expire_mode: ExpireMode) -> ExpiringMessage {
+        ExpiringMessage {
+            server_message_id: 9_007_199_254_740_993,
+            sender_id: SENDER,
+            pts: 50,
+            sent_at: 1_700_000_000_000,
+            expire,
+            expire_mode,
+        }
+    }
+
+    fn mark(reader_id: u64, last_read_pts: u64, read_at: i64) -> ReadMark {
+        ReadMark {
+            reader_id,
+            last_read_pts,
+            read_at,
+        }
+    }
+
+    #[test]
+    fn zero_expire_never_expires() {
+        let msg = message(0, ExpireMode::AfterSend);
+        assert_eq!(msg.deadline(&[mark(PEER, 60, 1)]), None);
+        assert!(!msg.is_expired(&[], i64::MAX));
+    }
+
+    #[test]
+    fn after_send_counts_from_sent_at() {
+        let msg = message(30, ExpireMode::AfterSend);
+        assert_eq!(msg.deadline(&[]), Some(1_700_000_030_000));
+        assert!(!msg.is_expired(&[], 1_700_000_029_999));
+        assert!(msg.is_expired(&[], 1_700_000_030_000));
+    }
+
+    #[test]
+    fn after_read_waits_for_first_non_sender_read() {
+        let msg = message(10, ExpireMode::AfterRead);
+        // 未读 / 游标没覆盖到本消息 / 发送者自己的游标，都不启动计时
+        assert_eq!(msg.deadline(&[]), None);
+        assert_eq!(msg.deadline(&[mark(PEER, 49, 1_700_000_001_000)]), None);
+        assert_eq!(msg.deadline(&[mark(SENDER, 80, 1_700_000_001_000)]), None);
+
+        // 多个读者、乱序到达的游标：取覆盖本消息的最早一次
+        let marks = [
+            mark(PEER, 70, 1_700_000_009_000),
+            mark(10003, 50, 1_700_000_005_000),
+            mark(PEER, 40, 1_700_000_002_000),
+        ];
+        assert_eq!(msg.first_read_at(&marks), Some(1_700_000_005_000));
+        assert_eq!(msg.deadline(&marks), Some(1_700_000_015_000));
+        let event = msg.expire_event(&marks).expect("deadline known");
+        assert_eq!(event.target_server_message_id, msg.server_message_id);
+        assert_eq!(event.expire_at, 1_700_000_015_000);
+    }
+
+    #[test]
+    fn channel_setting_caps_message_ttl() {
+        let setting = ChannelEphemeralSetting {
+            ttl_seconds: 60,
+            mode: ExpireMode::AfterRead,
+        };
+
+        let mut unset = SendMessageRequest::new();
+        setting.stamp(&mut unset);
+        assert_eq!(unset.expire, 60);
+        assert_eq!(unset.expire_mode, ExpireMode::AfterRead);
+
+        let mut longer = SendMessageRequest::new();
+        longer.expire = 3600;
+        setting.stamp(&mut longer);
+        assert_eq!(longer.expire, 60);
+
+        let mut shorter = SendMessageRequest::new();
+        shorter.expire = 5;
+        shorter.expire_mode = ExpireMode::AfterRead;
+        setting.stamp(&mut shorter);
+        assert_eq!(shorter.expire, 5);
+        assert_eq!(shorter.expire_mode, ExpireMode::AfterRead);
+
+        let mut off = SendMessageRequest::new();
+        ChannelEphemeralSetting::default().stamp(&mut off);
+        assert_eq!(off.expire, 0);
+    }
+
+    #[test]
+    fn channel_mode_overrides_client_mode() {
+        let setting = ChannelEphemeralSetting {
+            ttl_seconds: 60,
+            mode: ExpireMode::AfterSend,
+        };
+
+        // 更短的 TTL 保留，但 AfterRead 会让没人读的消息永不过期，必须改回频道起点
+        let mut after_read = SendMessageRequest::new();
+        after_read.expire = 5;
+        after_read.expire_mode = ExpireMode::AfterRead;
+        setting.stamp(&mut after_read);
+        assert_eq!(after_read.expire, 5);
+        assert_eq!(after_read.expire_mode, ExpireMode::AfterSend);
+
+        let mut longer = SendMessageRequest::new();
+        longer.expire = 600;
+        longer.expire_mode = ExpireMode::AfterRead;
+        setting.stamp(&mut longer);
+        assert_eq!(longer.expire, 60);
+        assert_eq!(longer.expire_mode, ExpireMode::AfterSend);
+    }
+
+    #[test]
+    fn setting_round_trips_through_channel_entity() {
+        let payload: ChannelSyncPayload = serde_json::from_str(
+            r#"{"channel_id":1,"ephemeral_ttl":30,"ephemeral_mode":"after_read"}"#,
+        )
+        .unwrap();
+        let setting = ChannelEphemeralSetting::from_sync_payload(&payload);
+        assert_eq!(setting.ttl_seconds, 30);
+        assert_eq!(setting.mode, ExpireMode::AfterRead);
+
+        // 老 server 不下发字段 → 未开启
+        let legacy: ChannelSyncPayload = serde_json::from_str(r#"{"channel_id":1}"#).unwrap();
+        assert!(!ChannelEphemeralSetting::from_sync_payload(&legacy).is_enabled());
+    }
+}
@@ -232,6 +232,16 @@ pub enum ErrorCode {
     GroupAddFriendPersonalDisabled = 20312,
     /// Profile view grant expired or invalid; re-fetch user detail
     ProfileViewGrantExpired = 20313,
+    /// Group forbids forwarding its messages
+    GroupForwardForbidden = 20314,
+    /// Group slow mode: wait before posting again
+    GroupSlowMode = 20315,
+    /// Member may not send media in this group
+    MemberMediaRestricted = 20316,
+    /// Member may not send links in this group
+    MemberLinksRestricted = 20317,
+    /// Join questions must be answered
+    GroupJoinAnswerRequired = 20318,
 
     // Friend Basics (20400-20499)
     /// Friend not found
@@ -437,6 +447,11 @@ impl ErrorCode {
                 "User does not allow being added via group chat"
             }
             Self::ProfileViewGrantExpired => "Profile view grant expired",
+            Self::GroupForwardForbidden => "Group forbids forwarding messages",
+            Self::GroupSlowMode => "Group slow mode, retry later",
+            Self::MemberMediaRestricted => "Member restricted from sending media",
+            Self::MemberLinksRestricted => "Member restricted from sending links",
+            Self::GroupJoinAnswerRequired => "Join questions must be answered",
             Self::FriendNotFound => "Friend not found",
             Self::AlreadyFriends => "Already friends",
             Self::BlockedByUser => "Blocked by user",
@@ -573,6 +588,11 @@ impl ErrorCode {
             20311 => Some(Self::GroupAddFriendDisabled),
             20312 => Some(Self::GroupAddFriendPersonalDisabled),
             20313 => Some(Self::ProfileViewGrantExpired),
+            20314 => Some(Self::GroupForwardForbidden),
+            20315 => Some(Self::GroupSlowMode),
+            20316 => Some(Self::MemberMediaRestricted),
+            20317 => Some(Self::MemberLinksRestricted),
+            20318 => Some(Self::GroupJoinAnswerRequired),
             20400 => Some(Self::FriendNotFound),
             20401 => Some(Self::AlreadyFriends),
             20402 => Some(Self::BlockedByUser),
@@ -634,6 +654,331 @@ impl ErrorCode {
         let code = self.code();
         code >= 20000
     }
+
+    /// Category, derived from the segment the code is allocated in.
+    pub fn category(&self) -> ErrorCategory {
+        match self.code() {
+            0 => ErrorCategory::Success,
+            1..=99 => ErrorCategory::System,
+            100..=299 => ErrorCategory::Protocol,
+            10000..=10099 => ErrorCategory::Auth,
+            10100..=10199 => ErrorCategory::Param,
+            10200..=10299 => ErrorCategory::Business,
+            10300..=10399 => ErrorCategory::Rate,
+            20000..=20199 => ErrorCategory::Message,
+            20200..=20299 => ErrorCategory::User,
+            20300..=20399 => ErrorCategory::Group,
+            20400..=20499 => ErrorCategory::Friend,
+            20500..=20599 => ErrorCategory::Channel,
+            20600..=20699 => ErrorCategory::File,
+            20700..=20799 => ErrorCategory::QrCode,
+            20800..=20899 => ErrorCategory::Device,
+            20900..=20919 => ErrorCategory::Sync,
+            20920..=20999 => ErrorCategory::Bot,
+            21000..=21099 => ErrorCategory::SystemUser,
+            _ => ErrorCategory::Unknown,
+        }
+    }
+
+    /// How a client may retry the failed request.
+    ///
+    /// This is the default for the code; a
+    /// [`ErrorDetail::RetryAfter`](crate::ErrorDetail::RetryAfter) on the
+    /// response gives the exact delay and takes precedence (e.g. a timed
+    /// `MemberMuted`).
+    ///
+    /// Every variant is listed explicitly so a new code cannot be added
+    /// without deciding its retry behaviour.
+    pub fn retry_class(&self) -> RetryClass {
+        use ErrorCode::*;
+        match self {
+            Timeout | NetworkError | UploadChunkChecksumMismatch => RetryClass::Immediately,
+
+            SystemBusy
+            | ServiceUnavailable
+            | Maintenance
+            | DatabaseError
+            | CacheError
+            | RateLimitExceeded
+            | ConcurrentLimitExceeded
+            | SendMessageTooFast
+            | GroupSlowMode
+            | UploadSessionBusy
+            | BotFollowRateLimited => RetryClass::Backoff,
+
+            AuthRequired | InvalidToken | TokenExpired | SessionExpired | SessionNotFound
+            | SessionNotReady | UploadTokenInvalid | UploadTokenExpired | UploadSessionGone => {
+                RetryClass::AfterReauth
+            }
+
+            OperationConflict
+            | UploadRangeOverlap
+            | UploadMissingRanges
+            | SyncChannelResyncRequired
+            | SyncEntityResyncRequired
+            | SyncFullRebuildRequired => RetryClass::AfterResync,
+
+            Ok
+            | SystemError
+            | InternalError
+            | ProtocolError
+            | UnsupportedProtocol
+            | InvalidPacket
+            | PacketTooLarge
+            | EncodingError
+            | DecodingError
+            | VersionError
+            | ClientVersionTooOld
+            | ClientVersionTooNew
+            | IncompatibleVersion
+            | DeprecatedApi
+            | TokenRevoked
+            | PermissionDenied
+            | UserBanned
+            | IpNotAllowed
+            | RefreshTokenExpired
+            | RefreshTokenRevoked
+            | InvalidParams
+            | MissingRequiredParam
+            | InvalidParamType
+            | ParamOutOfRange
+            | InvalidFormat
+            | InvalidJson
+            | PayloadTooLarge
+            | OperationNotAllowed
+            | ResourceNotFound
+            | ResourceAlreadyExists
+            | ResourceDeleted
+            | DuplicateOperation
+            | AttachmentSourceMissing
+            | DailyQuotaExceeded
+            | MonthlyQuotaExceeded
+            | MessageNotFound
+            | MessageDeleted
+            | MessageRevoked
+            | MessageSendFailed
+            | MessageTooLarge
+            | MessageTypeInvalid
+            | MessageContentInvalid
+            | MessageCannotRevoke
+            | MessageAlreadyRead
+            | OfflineMessageFull
+            | OfflineMessageExpired
+            | UserNotFound
+            | UserAlreadyExists
+            | UserDeleted
+            | UserBannedAlt
+            | UserNotActive
+            | NicknameInvalid
+            | AvatarInvalid
+            | GroupNotFound
+            | GroupDeleted
+            | GroupFull
+            | NotGroupMember
+            | NotGroupAdmin
+            | NotGroupOwner
+            | GroupMuted
+            | MemberMuted
+            | MemberAlreadyInGroup
+            | CannotRemoveOwner
+            | JoinApprovalRequired
+            | GroupAddFriendDisabled
+            | GroupAddFriendPersonalDisabled
+            | ProfileViewGrantExpired
+            | GroupForwardForbidden
+            | MemberMediaRestricted
+            | MemberLinksRestricted
+            | GroupJoinAnswerRequired
+            | FriendNotFound
+            | AlreadyFriends
+            | BlockedByUser
+            | UserInBlacklist
+            | FriendRequestExpired
+            | ChannelNotFound
+            | ChannelDeleted
+            | ChannelMuted
+            | FileNotFound
+            | FileUploadFailed
+            | FileTooLarge
+            | FileTypeNotAllowed
+            | UploadSessionCompleted
+            | UploadModeConflict
+            | UploadChunkNotAligned
+            | StorageQuotaExceeded
+            | QRCodeNotFound
+            | QRCodeExpired
+            | QRCodeUsed
+            | QRCodeRevoked
+            | QRCodeLimitExceeded
+            | DeviceNotFound
+            | DeviceLimitExceeded
+            | DeviceNotVerified
+            | BotNotFound
+            | NotABot
+            | BotDisabled
+            | SystemUserNotGroupInvitable => RetryClass::Never,
+        }
+    }
+
+    /// Whether the client should drop local credentials and return to login.
+    pub fn requires_logout(&self) -> bool {
+        matches!(
+            self,
+            Self::TokenRevoked
+                | Self::RefreshTokenExpired
+                | Self::RefreshTokenRevoked
+                | Self::UserBanned
+                | Self::UserDeleted
+        )
+    }
+
+    /// HTTP status for gateways and HTTP APIs that surface this code.
+    pub fn http_status(&self) -> u16 {
+        use ErrorCode::*;
+        match self {
+            Ok => 200,
+            SystemBusy | ServiceUnavailable | Maintenance => 503,
+            Timeout => 504,
+            PacketTooLarge | PayloadTooLarge | MessageTooLarge | FileTooLarge => 413,
+            ClientVersionTooOld => 426,
+            DeprecatedApi => 410,
+            PermissionDenied | UserBanned | IpNotAllowed => 403,
+            ResourceAlreadyExists | DuplicateOperation | OperationConflict | SessionNotReady => 409,
+            ResourceDeleted | MessageDeleted | GroupDeleted | ChannelDeleted | UserDeleted => 410,
+            SendMessageTooFast | GroupSlowMode | BotFollowRateLimited => 429,
+            ResourceNotFound
+            | AttachmentSourceMissing
+            | MessageNotFound
+            | UserNotFound
+            | GroupNotFound
+            | FriendNotFound
+            | ChannelNotFound
+            | FileNotFound
+            | QRCodeNotFound
+            | DeviceNotFound
+            | BotNotFound => 404,
+            NotGroupMember
+            | NotGroupAdmin
+            | NotGroupOwner
+            | GroupMuted
+            | MemberMuted
+            | GroupAddFriendDisabled
+            | GroupAddFriendPersonalDisabled
+            | GroupForwardForbidden
+            | MemberMediaRestricted
+            | MemberLinksRestricted
+            | BlockedByUser
+            | UserInBlacklist
+            | ChannelMuted
+            | FileTypeNotAllowed
+            | SystemUserNotGroupInvitable => 403,
+            UploadSessionBusy
+            | UploadSessionCompleted
+            | UploadRangeOverlap
+            | UploadModeConflict => 409,
+            _ => match self.category() {
+                ErrorCategory::Success => 200,
+                ErrorCategory::System | ErrorCategory::Unknown => 500,
+                ErrorCategory::Protocol | ErrorCategory::Param => 400,
+                ErrorCategory::Auth => 401,
+                ErrorCategory::Rate => 429,
+                _ => 422,
+            },
+        }
+    }
+
+    /// Whether the message is meant for the end user. `false` means the
+    /// client handles it silently (refresh a token, resync, resend a chunk)
+    /// or it only signals a bug / outage and should be logged.
+    pub fn is_user_visible(&self) -> bool {
+        use ErrorCode::*;
+        match self {
+            Ok | SystemError | InternalError | DatabaseError | CacheError => false,
+            ClientVersionTooOld | ClientVersionTooNew | IncompatibleVersion => true,
+            AuthRequired | InvalidToken | TokenExpired | SessionExpired | SessionNotReady => false,
+            UploadTokenInvalid
+            | UploadTokenExpired
+            | UploadRangeOverlap
+            | UploadChunkChecksumMismatch
+            | UploadSessionBusy
+            | UploadSessionGone
+            | UploadSessionCompleted
+            | UploadMissingRanges
+            | UploadModeConflict
+            | UploadChunkNotAligned => false,
+            _ => !matches!(
+                self.category(),
+                ErrorCategory::Protocol | ErrorCategory::Sync | ErrorCategory::Unknown
+            ),
+        }
+    }
+}
+
+/// Which part of the system a code belongs to. Mirrors the segment table in
+/// the [`ErrorCode`] docs; business codes are split by domain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCategory {
+    Success,
+    /// Runtime failures (1-99)
+    System,
+    /// Protocol and version compatibility (100-299)
+    Protocol,
+    /// Authentication / authorization (10000-10099)
+    Auth,
+    /// Request parameters (10100-10199)
+    Param,
+    /// Generic business rules (10200-10299)
+    Business,
+    /// Rate limiting and quotas (10300-10399)
+    Rate,
+    Message,
+    User,
+    Group,
+    Friend,
+    Channel,
+    File,
+    QrCode,
+    Device,
+    Sync,
+    Bot,
+    SystemUser,
+    /// Not in an allocated segment
+    Unknown,
+}
+
+impl ErrorCategory {
+    /// Whether this is one of the business domains (20000+).
+    pub fn is_business_domain(self) -> bool {
+        !matches!(
+            self,
+            Self::Success
+                | Self::System
+                | Self::Protocol
+                | Self::Auth
+                | Self::Param
+                | Self::Business
+                | Self::Rate
+                | Self::Unknown
+        )
+    }
+}
+
+/// Client retry policy for a failed request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetryClass {
+    /// Retrying the same request cannot succeed
+    Never,
+    /// Safe to resend right away (transient transport failure, bad chunk)
+    Immediately,
+    /// Retry with exponential backoff, honouring `retry_after` if present
+    Backoff,
+    /// Retry after obtaining fresh credentials (session or upload token)
+    AfterReauth,
+    /// Retry after refreshing local state (get_difference, entity sync,
+    /// upload status)
+    AfterResync,
 }
 
 impl Default for ErrorCode {
@@ -733,6 +1078,78 @@ mod tests {
         assert_eq!(ErrorCode::from_code(99999), None);
     }
 
+    /// Every code `from_code` knows sits in an allocated segment and has a
+    /// coherent retry / HTTP / visibility classification.
+    #[test]
+    fn test_every_variant_is_classified() {
+        let all: Vec<ErrorCode> = (0..=u32::from(u16::MAX))
+            .filter_map(ErrorCode::from_code)
+            .collect();
+        assert!(all.len() > 100);
+
+        for code in all {
+            assert_eq!(ErrorCode::from_code(code.code()), Some(code));
+            let category = code.category();
+            assert_ne!(category, ErrorCategory::Unknown, "{code:?} is unallocated");
+            assert_eq!(category == ErrorCategory::Success, code == ErrorCode::Ok);
+            assert_eq!(category.is_business_domain(), code.is_business_error());
+
+            let status = code.http_status();
+            assert!((200..600).contains(&status), "{code:?} -> {status}");
+            assert_eq!(status == 200, code == ErrorCode::Ok, "{code:?}");
+
+            if code.requires_logout() {
+                assert_eq!(code.retry_class(), RetryClass::Never, "{code:?}");
+            }
+            if category == ErrorCategory::Param {
+                assert_eq!(code.retry_class(), RetryClass::Never, "{code:?}");
+                assert!((400..500).contains(&status), "{code:?}");
+            }
+            if category == ErrorCategory::Sync {
+                assert_eq!(code.retry_class(), RetryClass::AfterResync, "{code:?}");
+                assert!(!code.is_user_visible(), "{code:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_error_code_taxonomy_samples() {
+        assert_eq!(ErrorCode::GroupSlowMode.category(), ErrorCategory::Group);
+        assert_eq!(ErrorCode::BotNotFound.category(), ErrorCategory::Bot);
+        assert_eq!(
+            ErrorCode::RateLimitExceeded.retry_class(),
+            RetryClass::Backoff
+        );
+        assert_eq!(
+            ErrorCode::TokenExpired.retry_class(),
+            RetryClass::AfterReauth
+        );
+        assert_eq!(ErrorCode::Timeout.retry_class(), RetryClass::Immediately);
+        assert!(ErrorCode::RefreshTokenRevoked.requires_logout());
+        assert!(!ErrorCode::TokenExpired.requires_logout());
+
+        assert_eq!(ErrorCode::InvalidToken.http_status(), 401);
+        assert_eq!(ErrorCode::GroupNotFound.http_status(), 404);
+        assert_eq!(ErrorCode::RateLimitExceeded.http_status(), 429);
+        assert_eq!(ErrorCode::ClientVersionTooOld.http_status(), 426);
+        assert_eq!(ErrorCode::MessageCannotRevoke.http_status(), 422);
+
+        assert!(ErrorCode::GroupMuted.is_user_visible());
+        assert!(ErrorCode::ClientVersionTooOld.is_user_visible());
+        assert!(!ErrorCode::DecodingError.is_user_visible());
+        assert!(!ErrorCode::UploadMissingRanges.is_user_visible());
+    }
+
+    #[test]
+    fn test_send_message_reason_code() {
+        let mut resp = crate::SendMessageResponse::default();
+        assert_eq!(resp.error_code(), None);
+        resp.reason_code = 20306;
+        assert_eq!(resp.error_code(), Some(ErrorCode::GroupMuted));
+        resp.reason_code = 64999;
+        assert_eq!(resp.error_code(), Some(ErrorCode::MessageSendFailed));
+    }
+
     #[test]
     fn test_error_code_display() {
         let err = ErrorCode::AuthRequired;
@@ -0,0 +1,158 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Machine-readable error details carried next to an [`ErrorCode`].
+//!
+//! `RpcResponse.detail` holds one [`ErrorDetail`] as JSON. The code says
+//! *what* went wrong; the detail says what the client can do about it
+//! (when to retry, which byte ranges to resend, which version to upgrade to).
+//! Clients that don't understand a detail kind still get the code.
+
+use serde::{Deserialize, Serialize};
+
+use crate::ErrorCode;
+
+/// A half-open byte range `[offset, offset + length)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ByteRange {
+    pub offset: u64,
+    pub length: u64,
+}
+
+/// Structured detail for an error response. JSON shape:
+/// `{"type":"retry_after","retry_after_ms":1500}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ErrorDetail {
+    /// Retry the same request after this many milliseconds
+    /// (`RateLimitExceeded`, `GroupSlowMode`, timed member restrictions).
+    RetryAfter { retry_after_ms: u64 },
+    /// Ranges the server has not received; resend them, then complete again
+    /// (`UploadMissingRanges`).
+    MissingRanges { ranges: Vec<ByteRange> },
+    /// Minimum client version the server accepts (`ClientVersionTooOld`).
+    MinVersion {
+        min_version: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        upgrade_url: Option<String>,
+    },
+    /// The operation was allowed until this time, ms (`MessageCannotRevoke`).
+    Deadline { deadline_ms: i64 },
+    /// Local state is too old for incremental sync; rebuild from scratch.
+    /// `min_version` is the oldest entity version the server still serves
+    /// (`SyncFullRebuildRequired`).
+    FullRebuild {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        min_version: Option<u64>,
+    },
+    /// Detail kind introduced by a newer server.
+    #[serde(other)]
+    Unknown,
+}
+
+impl ErrorDetail {
+    pub fn retry_after(retry_after_ms: u64) -> Self {
+        Self::RetryAfter { retry_after_ms }
+    }
+
+    /// Suggested delay before retrying, if the detail carries one.
+    pub fn retry_after_ms(&self) -> Option<u64> {
+        match self {
+            Self::RetryAfter { retry_after_ms } => Some(*retry_after_ms),
+            _ => None,
+        }
+    }
+}
+
+/// A failed RPC as seen by the caller: the wire code, message and detail.
+///
+/// `code` stays an `i32` so codes from a newer server survive; use
+/// [`RpcError::error_code`] to match on known ones.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("[{code}] {message}")]
+pub struct RpcError {
+    pub code: i32,
+    pub message: String,
+    pub detail: Option<ErrorDetail>,
+}
+
+impl RpcError {
+    pub fn new(code: ErrorCode, detail: Option<ErrorDetail>) -> Self {
+        Self {
+            code: code.code() as i32,
+            message: code.message().to_string(),
+            detail,
+        }
+    }
+
+    /// The known [`ErrorCode`], or `None` for codes this build doesn't know.
+    pub fn error_code(&self) -> Option<ErrorCode> {
+        u32::try_from(self.code).ok().and_then(ErrorCode::from_code)
+    }
+
+    pub fn retry_after_ms(&self) -> Option<u64> {
+        self.detail.as_ref().and_then(ErrorDetail::retry_after_ms)
+    }
+}
+
+impl From<ErrorCode> for RpcError {
+    fn from(code: ErrorCode) -> Self {
+        Self::new(code, None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detail_json_shape() {
+        let detail = ErrorDetail::MissingRanges {
+            ranges: vec![ByteRange {
+                offset: 0,
+                length: 4096,
+            }],
+        };
+        let json = serde_json::to_value(&detail).unwrap();
+        assert_eq!(json["type"], "missing_ranges");
+        assert_eq!(json["ranges"][0]["length"], 4096);
+        assert_eq!(serde_json::from_value::<ErrorDetail>(json).unwrap(), detail);
+
+        let unknown: ErrorDetail =
+            serde_json::from_value(serde_json::json!({"type": "captcha", "url": "x"})).unwrap();
+        assert_eq!(unknown, ErrorDetail::Unknown);
+    }
+
+    #[test]
+    fn rpc_error_keeps_unknown_codes() {
+        let err = RpcError::new(
+            ErrorCode::RateLimitExceeded,
+            Some(ErrorDetail::retry_after(1500)),
+        );
+        assert_eq!(err.error_code(), Some(ErrorCode::RateLimitExceeded));
+        assert_eq!(err.retry_after_ms(), Some(1500));
+        assert_eq!(err.to_string(), "[10300] Rate limit exceeded");
+
+        let future = RpcError {
+            code: 64999,
+            message: "new".into(),
+            detail: None,
+        };
+        assert_eq!(future.error_code(), None);
+        assert_eq!(RpcError { code: -1, ..future }.error_code(), None);
+    }
+}
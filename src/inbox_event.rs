@@ -29,18 +29,40 @@
 //!    类型 RPC。
 //! 2. **`event_id` 仅供 dedupe，不可用于 diff**：v1 没有 cursor，server 不
 //!    保证 event_id 在用户维度单调；客户端只能拿 event_id 去重，不能拿它
-//!    驱动增量拉取。v2 才会引入 `cursor` 字段（user-scoped monotonic），
-//!    `event_id` 的语义跨版本保持稳定。
+//!    驱动增量拉取。`event_id` 的语义跨版本保持稳定。
 //! 3. **不冗余 type**：事件类型来自外层 `PushMessageRequest.topic`，envelope
 //!    自身**不**重复写 `type` —— 避免一致性纪律负担。
 //! 4. **`aggregate_id` 必须是单个稳定标识**：禁止 `"a:b"` 这种复合编码；
 //!    复合语义放进 [`payload`]。
+//!
+//! ## v2
+//!
+//! v2 envelope 由 server 持久化，并带上 `cursor`：**用户维度**单调递增，
+//! 同一用户的所有事件共用一条序列。在线推送与 `inbox/get_difference` 补差
+//! 返回同一个 envelope；客户端用 [`InboxCursor`] 记录进度、按 `event_id`
+//! 去重、丢弃过期事件，发现 cursor 跳号时再去补差。v1 的 1、3、4 条不变式
+//! 中，只有「online-push-only」被 v2 取消。
+//!
+//! 消费方不要自己按 topic 字符串挑 payload 结构体，统一走
+//! [`InboxEvent::decode`]。
+
+use std::collections::{HashSet, VecDeque};
 
 use serde::{Deserialize, Serialize};
 
+use crate::rpc::inbox::{InboxEventItem, InboxGetDifferenceRequest, InboxGetDifferenceResponse};
+use crate::ProtocolError;
+
 /// 当前 envelope schema 版本。v1 仅支持在线推送语义。
 pub const USER_INBOX_EVENT_SCHEMA_VERSION_V1: u32 = 1;
 
+/// v2：持久化 + 用户维度 `cursor`，支持 `inbox/get_difference` 补差。
+pub const USER_INBOX_EVENT_SCHEMA_VERSION_V2: u32 = 2;
+
+/// 本端能解析的最高 envelope schema 版本；更高版本的 envelope 由
+/// [`InboxEvent::decode`] 拒绝，而不是按旧语义误读。
+pub const USER_INBOX_EVENT_SCHEMA_VERSION_MAX: u32 = USER_INBOX_EVENT_SCHEMA_VERSION_V2;
+
 /// 用户收件箱事件信封 v1。
 ///
 /// 作为 `PushMessageRequest.payload` 的 JSON 主体；外层 `topic` 即事件类型
@@ -76,6 +98,14 @@ pub struct UserInboxEventEnvelope {
     /// 业务自定义负载。每类事件对应一个具体的 payload 结构体（见
     /// `payloads` 子模块），客户端按外层 `topic` 决定如何反序列化。
     pub payload: serde_json::Value,
+
+    /// 用户维度单调递增的收件箱序号（v2）。v1 envelope 没有此字段。
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "crate::serde_u64::option"
+    )]
+    pub cursor: Option<u64>,
 }
 
 /// v1 事件类型常量（== outer `PushMessageRequest.topic`）。
@@ -100,6 +130,38 @@ pub mod topics {
     /// aggregate_id = peer_user_id（接收人视角下的"对端"，requester 视角下的
     /// 被申请人）。payload = [`super::payloads::FriendRequestStatusChangedPayload`]。
     pub const FRIEND_REQUEST_STATUS_CHANGED: &str = "friend.request.status_changed";
+
+    /// 用户点击了 Bot 消息上的回调按钮。target = Bot；aggregate_type =
+    /// `callback_query`；aggregate_id = query_id 字符串。payload =
+    /// [`super::payloads::CallbackQueryPayload`]。Bot 须在超时前调用
+    /// `bot/callback/answer` 应答。
+    pub const BOT_CALLBACK_QUERY: &str = "bot.callback_query";
+
+    /// 有人申请入群，等待审批。target = 持有审批权限的群主 / 管理员；
+    /// aggregate_type = `group_join_request`；aggregate_id = 申请的 request_id。
+    /// payload = [`super::payloads::GroupJoinRequestReceivedPayload`]。
+    pub const GROUP_JOIN_REQUEST_RECEIVED: &str = "group.join_request.received";
+
+    /// 入群申请状态变化（approved / rejected / expired）。target = 申请人
+    /// 所有设备，以及其他审批人（用于收起待审条目）；aggregate_type =
+    /// `group_join_request`；aggregate_id = request_id。`ignore` 不通知申请人。
+    /// payload = [`super::payloads::GroupJoinRequestStatusChangedPayload`]。
+    pub const GROUP_JOIN_REQUEST_STATUS_CHANGED: &str = "group.join_request.status_changed";
+
+    /// 收到入群邀请（对方开启了入群审批或被邀请人需要确认时）。target =
+    /// 被邀请人；aggregate_type = `group_invite`；aggregate_id = group_id 字符串。
+    /// payload = [`super::payloads::GroupInvitationReceivedPayload`]。
+    pub const GROUP_INVITATION_RECEIVED: &str = "group.invitation.received";
+
+    /// 我被移出群（被踢或群解散）。target = 被移出的用户所有设备；
+    /// aggregate_type = `group`；aggregate_id = group_id 字符串。payload =
+    /// [`super::payloads::GroupMemberRemovedPayload`]。
+    pub const GROUP_MEMBER_REMOVED: &str = "group.member.removed";
+
+    /// 账号在新设备上登录。target = 该用户其他所有设备；aggregate_type =
+    /// `device`；aggregate_id = 新设备的 device_id。payload =
+    /// [`super::payloads::DeviceLoginAlertPayload`]。
+    pub const DEVICE_LOGIN_ALERT: &str = "device.login_alert";
 }
 
 /// 各事件类型对应的 typed payload 结构体。
@@ -107,7 +169,7 @@ pub mod topics {
 /// **不允许在业务代码里直接 `json!({...})` 构造 envelope.payload**——必须用
 /// 这里的具体结构体走 [`serde_json::to_value`]。
 pub mod payloads {
-    use serde::{Deserialize, Serialize};
+    use serde::{Deserialize, Deserializer, Serialize};
 
     /// `friend.request.received` 事件 payload。
     ///
@@ -146,6 +208,223 @@ pub mod payloads {
         pub target_user_id: u64,
         pub new_status: i16,
     }
+
+    /// `bot.callback_query` 事件 payload——用户点击了 Bot 消息上的回调按钮。
+    ///
+    /// `server_message_id` 指向携带键盘的那条消息；`data` 原样回传按钮的
+    /// callback data（server 已校验它确实在该消息的键盘上）。
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct CallbackQueryPayload {
+        /// 本次点击 ID，Bot 应答时回传
+        pub query_id: u64,
+        /// 点击者
+        pub from_user_id: u64,
+        pub channel_id: u64,
+        pub channel_type: u8,
+        /// 被点击的消息
+        pub server_message_id: u64,
+        pub data: String,
+    }
+
+    /// `group.join_request.received` 事件 payload。回答的问题文本已与答案
+    /// 配对，审批人无需再拉一次问题列表。
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct GroupJoinRequestReceivedPayload {
+        pub request_id: String,
+        pub group_id: u64,
+        pub applicant_id: u64,
+        pub method: crate::rpc::group::ApprovalMethod,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub message: Option<String>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        pub answers: Vec<crate::rpc::group::GroupJoinAnswer>,
+    }
+
+    /// `group.join_request.status_changed` 事件 payload。
+    ///
+    /// 仍然是 hint：申请人据此刷新「我的申请」，被同意后群资料走 entity sync。
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct GroupJoinRequestStatusChangedPayload {
+        pub request_id: String,
+        pub group_id: u64,
+        pub applicant_id: u64,
+        pub new_status: crate::rpc::group::GroupJoinRequestStatus,
+        /// 处理人；过期时为 `None`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub handled_by: Option<u64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub reject_reason: Option<String>,
+    }
+
+    /// `group.invitation.received` 事件 payload。
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct GroupInvitationReceivedPayload {
+        pub group_id: u64,
+        /// 群名快照（邀请时）
+        pub group_name: String,
+        pub inviter_id: u64,
+        pub invitee_id: u64,
+        /// 邀请附言
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub message: Option<String>,
+    }
+
+    /// 被移出群的原因。
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+    #[serde(rename_all = "snake_case")]
+    pub enum GroupRemovalReason {
+        /// 被管理员移出
+        Kicked,
+        /// 群被解散
+        GroupDismissed,
+        /// 新版本服务端引入、本端不认识的原因
+        #[serde(other)]
+        Unknown,
+    }
+
+    /// `group.member.removed` 事件 payload。
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct GroupMemberRemovedPayload {
+        pub group_id: u64,
+        pub user_id: u64,
+        pub reason: GroupRemovalReason,
+        /// 操作者；群解散时为群主
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub operator_id: Option<u64>,
+    }
+
+    /// `device.login_alert` 事件 payload。`ip` / `location` 由服务端在登录时
+    /// 记录，不保证存在。
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct DeviceLoginAlertPayload {
+        pub user_id: u64,
+        pub device_id: String,
+        #[serde(default)]
+        pub device_name: String,
+        /// 新版本服务端下发的未知设备类型（如 `"watch"`）降级为 `Unknown`
+        #[serde(default, deserialize_with = "lenient_device_type")]
+        pub device_type: crate::DeviceType,
+        /// 登录时间，毫秒时间戳
+        pub login_at: i64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub ip: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub location: Option<String>,
+    }
+
+    fn lenient_device_type<'de, D>(deserializer: D) -> Result<crate::DeviceType, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw = Option::<String>::deserialize(deserializer)?;
+        Ok(raw
+            .map(|s| crate::DeviceType::from_str(&s))
+            .unwrap_or_default())
+    }
+}
+
+/// 按 topic 解出的 typed 收件箱事件。
+#[derive(Debug, Clone)]
+pub enum InboxEvent {
+    FriendRequestReceived(payloads::FriendRequestReceivedPayload),
+    FriendRequestSent(payloads::FriendRequestSentPayload),
+    FriendRequestStatusChanged(payloads::FriendRequestStatusChangedPayload),
+    BotCallbackQuery(payloads::CallbackQueryPayload),
+    GroupInvitationReceived(payloads::GroupInvitationReceivedPayload),
+    GroupJoinRequestReceived(payloads::GroupJoinRequestReceivedPayload),
+    GroupJoinRequestStatusChanged(payloads::GroupJoinRequestStatusChangedPayload),
+    GroupMemberRemoved(payloads::GroupMemberRemovedPayload),
+    DeviceLoginAlert(payloads::DeviceLoginAlertPayload),
+    /// 本端不认识的 topic（新版本服务端引入）。原样保留，消费方可忽略。
+    Unknown {
+        topic: String,
+        payload: serde_json::Value,
+    },
+}
+
+impl InboxEvent {
+    /// 按外层 `PushMessageRequest.topic` 解析 envelope 的 payload。
+    ///
+    /// `schema_version` 为 0 或高于 [`USER_INBOX_EVENT_SCHEMA_VERSION_MAX`] 时
+    /// 返回错误；已知 topic 的 payload 与结构体对不上也返回错误；未知 topic
+    /// 得到 [`InboxEvent::Unknown`]。
+    pub fn decode(topic: &str, envelope: &UserInboxEventEnvelope) -> Result<Self, ProtocolError> {
+        if envelope.schema_version == 0
+            || envelope.schema_version > USER_INBOX_EVENT_SCHEMA_VERSION_MAX
+        {
+            return Err(ProtocolError::InvalidValue(format!(
+                "unsupported inbox event schema_version {} (max {})",
+                envelope.schema_version, USER_INBOX_EVENT_SCHEMA_VERSION_MAX
+            )));
+        }
+
+        fn parse<T: serde::de::DeserializeOwned>(
+            topic: &str,
+            payload: &serde_json::Value,
+        ) -> Result<T, ProtocolError> {
+            T::deserialize(payload).map_err(|e| {
+                ProtocolError::InvalidValue(format!("inbox event `{topic}` payload: {e}"))
+            })
+        }
+
+        let payload = &envelope.payload;
+        Ok(match topic {
+            topics::FRIEND_REQUEST_RECEIVED => Self::FriendRequestReceived(parse(topic, payload)?),
+            topics::FRIEND_REQUEST_SENT => Self::FriendRequestSent(parse(topic, payload)?),
+            topics::FRIEND_REQUEST_STATUS_CHANGED => {
+                Self::FriendRequestStatusChanged(parse(topic, payload)?)
+            }
+            topics::BOT_CALLBACK_QUERY => Self::BotCallbackQuery(parse(topic, payload)?),
+            topics::GROUP_INVITATION_RECEIVED => {
+                Self::GroupInvitationReceived(parse(topic, payload)?)
+            }
+            topics::GROUP_JOIN_REQUEST_RECEIVED => {
+                Self::GroupJoinRequestReceived(parse(topic, payload)?)
+            }
+            topics::GROUP_JOIN_REQUEST_STATUS_CHANGED => {
+                Self::GroupJoinRequestStatusChanged(parse(topic, payload)?)
+            }
+            topics::GROUP_MEMBER_REMOVED => Self::GroupMemberRemoved(parse(topic, payload)?),
+            topics::DEVICE_LOGIN_ALERT => Self::DeviceLoginAlert(parse(topic, payload)?),
+            _ => Self::Unknown {
+                topic: topic.to_string(),
+                payload: payload.clone(),
+            },
+        })
+    }
+
+    /// 对应的外层 topic。
+    pub fn topic(&self) -> &str {
+        match self {
+            Self::FriendRequestReceived(_) => topics::FRIEND_REQUEST_RECEIVED,
+            Self::FriendRequestSent(_) => topics::FRIEND_REQUEST_SENT,
+            Self::FriendRequestStatusChanged(_) => topics::FRIEND_REQUEST_STATUS_CHANGED,
+            Self::BotCallbackQuery(_) => topics::BOT_CALLBACK_QUERY,
+            Self::GroupInvitationReceived(_) => topics::GROUP_INVITATION_RECEIVED,
+            Self::GroupJoinRequestReceived(_) => topics::GROUP_JOIN_REQUEST_RECEIVED,
+            Self::GroupJoinRequestStatusChanged(_) => topics::GROUP_JOIN_REQUEST_STATUS_CHANGED,
+            Self::GroupMemberRemoved(_) => topics::GROUP_MEMBER_REMOVED,
+            Self::DeviceLoginAlert(_) => topics::DEVICE_LOGIN_ALERT,
+            Self::Unknown { topic, .. } => topic,
+        }
+    }
+
+    /// envelope 的 `aggregate_type`（见 [`topics`] 中各 topic 的约定）。
+    pub fn aggregate_type(&self) -> &'static str {
+        match self {
+            Self::FriendRequestReceived(_)
+            | Self::FriendRequestSent(_)
+            | Self::FriendRequestStatusChanged(_) => "friend_request",
+            Self::BotCallbackQuery(_) => "callback_query",
+            Self::GroupInvitationReceived(_) => "group_invite",
+            Self::GroupJoinRequestReceived(_) | Self::GroupJoinRequestStatusChanged(_) => {
+                "group_join_request"
+            }
+            Self::GroupMemberRemoved(_) => "group",
+            Self::DeviceLoginAlert(_) => "device",
+            Self::Unknown { .. } => "unknown",
+        }
+    }
 }
 
 impl UserInboxEventEnvelope {
@@ -169,15 +448,148 @@ impl UserInboxEventEnvelope {
             created_at,
             expires_at,
             payload,
+            cursor: None,
+        }
+    }
+
+    /// 构造 v2 envelope：在 v1 基础上带上用户维度的 `cursor`。
+    pub fn new_v2(
+        event_id: u64,
+        cursor: u64,
+        aggregate_type: impl Into<String>,
+        aggregate_id: impl Into<String>,
+        created_at: i64,
+        expires_at: Option<i64>,
+        payload: serde_json::Value,
+    ) -> Self {
+        Self {
+            schema_version: USER_INBOX_EVENT_SCHEMA_VERSION_V2,
+            cursor: Some(cursor),
+            ..Self::new_v1(
+                event_id,
+                aggregate_type,
+                aggregate_id,
+                created_at,
+                expires_at,
+                payload,
+            )
         }
     }
 
+    /// 在 `now_ms` 时是否已过期。
+    pub fn is_expired(&self, now_ms: i64) -> bool {
+        self.expires_at.is_some_and(|at| at <= now_ms)
+    }
+
     /// 序列化成 JSON 字节，供放进 `PushMessageRequest.payload`。
     pub fn to_json_bytes(&self) -> Result<Vec<u8>, serde_json::Error> {
         serde_json::to_vec(self)
     }
 }
 
+/// 去重窗口：记住最近这么多个 `event_id`
+pub const INBOX_DEDUPE_CAPACITY: usize = 1024;
+
+/// 客户端收件箱进度。
+///
+/// - 在线推送走 [`InboxCursor::accept`]，补差结果走
+///   [`InboxCursor::apply_difference`]，两条路径共用一套去重；
+/// - 只有 cursor 连续（恰好是下一个）时才前进；跳号说明中间有事件没收到，
+///   此时事件照常交付，但 [`InboxCursor::needs_difference`] 置位，cursor 不动，
+///   补差时会把它重新拉回来并被去重掉；
+/// - 补差进行中收到的跳号推送不会丢：记住见过的最大 cursor，补差结果没追上它
+///   时继续置位；
+/// - v1 envelope 没有 cursor，只做去重。
+///
+/// 调用方负责把 [`InboxCursor::cursor`] 持久化。
+#[derive(Debug, Clone)]
+pub struct InboxCursor {
+    cursor: u64,
+    /// 推送或补差中见过的最大 cursor
+    highest_seen: u64,
+    needs_difference: bool,
+    seen: HashSet<String>,
+    order: VecDeque<String>,
+}
+
+impl InboxCursor {
+    /// 从持久化的 cursor 恢复；首次使用传 0。恢复后应先补差一次。
+    pub fn new(cursor: u64) -> Self {
+        Self {
+            cursor,
+            highest_seen: cursor,
+            needs_difference: true,
+            seen: HashSet::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    /// 已连续处理到的 cursor。
+    pub fn cursor(&self) -> u64 {
+        self.cursor
+    }
+
+    /// 是否需要调用 `inbox/get_difference`。
+    pub fn needs_difference(&self) -> bool {
+        self.needs_difference
+    }
+
+    /// 下一次补差请求。
+    pub fn difference_request(&self, limit: Option<u32>) -> InboxGetDifferenceRequest {
+        InboxGetDifferenceRequest {
+            cursor: self.cursor,
+            limit,
+            user_id: 0,
+        }
+    }
+
+    /// 处理一条在线推送的 envelope，返回是否应交给业务处理（非重复、未过期）。
+    pub fn accept(&mut self, envelope: &UserInboxEventEnvelope, now_ms: i64) -> bool {
+        if let Some(cursor) = envelope.cursor {
+            self.highest_seen = self.highest_seen.max(cursor);
+            if self.cursor.checked_add(1) == Some(cursor) {
+                self.cursor = cursor;
+            } else if cursor > self.cursor {
+                self.needs_difference = true;
+            }
+        }
+        !envelope.is_expired(now_ms) && self.remember(&envelope.event_id)
+    }
+
+    /// 应用一批补差结果，返回需要交给业务处理的事件（已去重、已过滤过期）。
+    /// 还有更多时 [`InboxCursor::needs_difference`] 保持置位。
+    pub fn apply_difference(
+        &mut self,
+        response: InboxGetDifferenceResponse,
+        now_ms: i64,
+    ) -> Vec<InboxEventItem> {
+        let fresh = response
+            .events
+            .into_iter()
+            .filter(|item| {
+                !item.envelope.is_expired(now_ms) && self.remember(&item.envelope.event_id)
+            })
+            .collect();
+        self.cursor = self.cursor.max(response.current_cursor);
+        self.highest_seen = self.highest_seen.max(self.cursor);
+        self.needs_difference = response.has_more || self.highest_seen > self.cursor;
+        fresh
+    }
+
+    fn remember(&mut self, event_id: &str) -> bool {
+        if !self.seen.insert(event_id.to_string()) {
+            return false;
+        }
+        self.order.push_back(event_id.to_string());
+        if self.order.len() > INBOX_DEDUPE_CAPACITY {
+            if let Some(oldest) = self.order.pop_front() {
+                self.seen.remove(&oldest);
+            }
+        }
+        true
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::payloads::FriendRequestReceivedPayload;
@@ -216,6 +628,240 @@ mod tests {
         assert_eq!(p.message, "我是 Alice");
     }
 
+    #[test]
+    fn group_join_status_payload_roundtrip() {
+        use super::payloads::GroupJoinRequestStatusChangedPayload;
+        use crate::rpc::group::GroupJoinRequestStatus;
+
+        let payload = GroupJoinRequestStatusChangedPayload {
+            request_id: "r1".into(),
+            group_id: 7,
+            applicant_id: 9,
+            new_status: GroupJoinRequestStatus::Rejected,
+            handled_by: Some(1),
+            reject_reason: Some("名额已满".into()),
+        };
+        let env = UserInboxEventEnvelope::new_v1(
+            2,
+            "group_join_request",
+            "r1",
+            0,
+            None,
+            serde_json::to_value(&payload).unwrap(),
+        );
+        assert_eq!(env.payload["new_status"], "rejected");
+
+        let parsed: GroupJoinRequestStatusChangedPayload =
+            serde_json::from_value(env.payload).unwrap();
+        assert_eq!(parsed.new_status, GroupJoinRequestStatus::Rejected);
+        assert_eq!(parsed.handled_by, Some(1));
+    }
+
+    fn envelope(payload: serde_json::Value) -> UserInboxEventEnvelope {
+        UserInboxEventEnvelope::new_v1(1, "group", "7", 0, None, payload)
+    }
+
+    #[test]
+    fn decode_dispatches_by_topic() {
+        let removed = envelope(serde_json::json!({
+            "group_id": 7,
+            "user_id": 9,
+            "reason": "kicked",
+            "operator_id": 1
+        }));
+        match InboxEvent::decode(topics::GROUP_MEMBER_REMOVED, &removed).unwrap() {
+            InboxEvent::GroupMemberRemoved(p) => {
+                assert_eq!(p.group_id, 7);
+                assert_eq!(p.reason, payloads::GroupRemovalReason::Kicked);
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+
+        let login = envelope(serde_json::json!({
+            "user_id": 9,
+            "device_id": "d-2",
+            "device_type": "ios",
+            "login_at": 1
+        }));
+        let event = InboxEvent::decode(topics::DEVICE_LOGIN_ALERT, &login).unwrap();
+        assert_eq!(event.topic(), topics::DEVICE_LOGIN_ALERT);
+        assert_eq!(event.aggregate_type(), "device");
+        assert!(matches!(
+            event,
+            InboxEvent::DeviceLoginAlert(p) if p.device_type == crate::DeviceType::iOS
+        ));
+
+        let future = InboxEvent::decode("wallet.topped_up", &login).unwrap();
+        assert_eq!(future.topic(), "wallet.topped_up");
+        assert!(matches!(future, InboxEvent::Unknown { .. }));
+    }
+
+    #[test]
+    fn decode_rejects_bad_schema_version_and_payload() {
+        let mut env = envelope(serde_json::json!({"group_id": 7}));
+        assert!(InboxEvent::decode(topics::GROUP_MEMBER_REMOVED, &env).is_err());
+
+        env.payload = serde_json::json!({"group_id": 7, "user_id": 9, "reason": "banned"});
+        assert!(InboxEvent::decode(topics::GROUP_MEMBER_REMOVED, &env).is_ok());
+
+        for version in [0, USER_INBOX_EVENT_SCHEMA_VERSION_MAX + 1] {
+            env.schema_version = version;
+            assert!(InboxEvent::decode(topics::GROUP_MEMBER_REMOVED, &env).is_err());
+        }
+    }
+
+    fn v2(event_id: u64, cursor: u64, expires_at: Option<i64>) -> UserInboxEventEnvelope {
+        UserInboxEventEnvelope::new_v2(
+            event_id,
+            cursor,
+            "friend_request",
+            "1",
+            0,
+            expires_at,
+            serde_json::json!({}),
+        )
+    }
+
+    #[test]
+    fn v2_envelope_carries_string_cursor() {
+        let env = v2(5, 42, None);
+        let json = serde_json::to_value(&env).unwrap();
+        assert_eq!(json["schema_version"], 2);
+        assert_eq!(json["cursor"], "42");
+
+        let v1 = serde_json::to_value(UserInboxEventEnvelope::new_v1(
+            1,
+            "friend_request",
+            "1",
+            0,
+            None,
+            serde_json::json!({}),
+        ))
+        .unwrap();
+        assert!(v1.get("cursor").is_none());
+        let parsed: UserInboxEventEnvelope = serde_json::from_value(v1).unwrap();
+        assert_eq!(parsed.cursor, None);
+    }
+
+    #[test]
+    fn inbox_cursor_advances_dedupes_and_detects_gaps() {
+        let mut inbox = InboxCursor::new(10);
+        assert!(inbox.needs_difference());
+        let resp = InboxGetDifferenceResponse {
+            events: vec![InboxEventItem {
+                topic: topics::FRIEND_REQUEST_RECEIVED.into(),
+                envelope: v2(100, 11, None),
+            }],
+            current_cursor: 11,
+            has_more: false,
+        };
+        assert_eq!(inbox.apply_difference(resp, 0).len(), 1);
+        assert_eq!(inbox.cursor(), 11);
+        assert!(!inbox.needs_difference());
+
+        // 连续推送：前进；重复：丢弃
+        assert!(inbox.accept(&v2(101, 12, None), 0));
+        assert!(!inbox.accept(&v2(101, 12, None), 0));
+        assert_eq!(inbox.cursor(), 12);
+
+        // 跳号：照常交付，但 cursor 不动并要求补差
+        assert!(inbox.accept(&v2(103, 14, None), 0));
+        assert_eq!(inbox.cursor(), 12);
+        assert!(inbox.needs_difference());
+        assert_eq!(inbox.difference_request(None).cursor, 12);
+
+        // 补差拉回 13、14：14 已交付过，被去重；13 已过期，被过滤
+        let resp = InboxGetDifferenceResponse {
+            events: vec![
+                InboxEventItem {
+                    topic: topics::FRIEND_REQUEST_RECEIVED.into(),
+                    envelope: v2(102, 13, Some(50)),
+                },
+                InboxEventItem {
+                    topic: topics::FRIEND_REQUEST_RECEIVED.into(),
+                    envelope: v2(103, 14, None),
+                },
+            ],
+            current_cursor: 14,
+            has_more: false,
+        };
+        assert!(inbox.apply_difference(resp, 100).is_empty());
+        assert_eq!(inbox.cursor(), 14);
+        assert!(!inbox.needs_difference());
+
+        // v1 envelope 只去重
+        let v1 = UserInboxEventEnvelope::new_v1(
+            7,
+            "friend_request",
+            "1",
+            0,
+            None,
+            serde_json::json!({}),
+        );
+        assert!(inbox.accept(&v1, 0));
+        assert!(!inbox.accept(&v1, 0));
+        assert_eq!(inbox.cursor(), 14);
+    }
+
+    #[test]
+    fn unknown_device_type_degrades() {
+        let env = envelope(serde_json::json!({
+            "user_id": 1,
+            "device_id": "d1",
+            "device_type": "watch",
+            "login_at": 1_770_000_000_000_i64
+        }));
+        match InboxEvent::decode(topics::DEVICE_LOGIN_ALERT, &env).unwrap() {
+            InboxEvent::DeviceLoginAlert(p) => {
+                assert_eq!(p.device_type, crate::DeviceType::Unknown);
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+    }
+
+    #[test]
+    fn gap_seen_during_difference_is_not_forgotten() {
+        let mut inbox = InboxCursor::new(10);
+        // 补差请求在途时推送了 13（跳号）
+        assert!(inbox.accept(&v2(103, 13, None), 0));
+        let resp = InboxGetDifferenceResponse {
+            events: vec![InboxEventItem {
+                topic: topics::FRIEND_REQUEST_RECEIVED.into(),
+                envelope: v2(101, 11, None),
+            }],
+            current_cursor: 11,
+            has_more: false,
+        };
+        assert_eq!(inbox.apply_difference(resp, 0).len(), 1);
+        assert_eq!(inbox.cursor(), 11);
+        assert!(inbox.needs_difference());
+
+        let resp = InboxGetDifferenceResponse {
+            events: vec![],
+            current_cursor: 13,
+            has_more: false,
+        };
+        inbox.apply_difference(resp, 0);
+        assert!(!inbox.needs_difference());
+    }
+
+    #[test]
+    fn cursor_at_max_does_not_overflow() {
+        let mut inbox = InboxCursor::new(u64::MAX);
+        assert!(inbox.accept(&v2(1, u64::MAX, None), 0));
+        assert_eq!(inbox.cursor(), u64::MAX);
+    }
+
+    #[test]
+    fn dedupe_window_is_bounded() {
+        let mut inbox = InboxCursor::new(0);
+        for id in 0..(INBOX_DEDUPE_CAPACITY as u64 + 1) {
+            assert!(inbox.remember(&id.to_string()));
+        }
+        assert_eq!(inbox.seen.len(), INBOX_DEDUPE_CAPACITY);
+        assert!(inbox.remember("0"));
+    }
+
     #[test]
     fn expires_at_none_is_omitted() {
         let env = UserInboxEventEnvelope::new_v1(
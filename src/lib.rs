@@ -77,6 +77,10 @@ pub mod timeline_generated {
 pub mod entity_sync_generated {
     include!(concat!(env!("OUT_DIR"), "/entity_sync_generated.rs"));
 }
+#[allow(unused_imports, dead_code, clippy::all, mismatched_lifetime_syntaxes)]
+pub mod presence_generated {
+    include!(concat!(env!("OUT_DIR"), "/presence_generated.rs"));
+}
 
 /// Aggregated re-export of all generated FlatBuffers view types.
 /// Internal use; application layer should prefer owned types in `protocol`.
@@ -87,6 +91,7 @@ pub mod fb {
     pub use crate::disconnect_generated::privchat::protocol::*;
     pub use crate::entity_sync_generated::privchat::protocol::*;
     pub use crate::ping_generated::privchat::protocol::*;
+    pub use crate::presence_generated::privchat::protocol::*;
     pub use crate::publish_generated::privchat::protocol::*;
     pub use crate::push_generated::privchat::protocol::*;
     pub use crate::rpc_generated::privchat::protocol::*;
@@ -97,23 +102,34 @@ pub mod fb {
 }
 
 pub mod codec;
+pub mod conversation;
+pub mod ephemeral;
 pub mod error;
 pub mod error_code;
+pub mod error_detail;
 pub mod inbox_event;
 pub mod message;
 pub mod notification;
+pub mod notification_policy;
 pub mod serde_u64;
+pub mod stream;
+pub mod typing;
+pub mod unread;
 pub mod presence;
 pub mod protocol;
+pub mod push_payload;
 pub mod rpc;
 pub mod version;
+pub mod webhook;
 
 pub use codec::{decode_message, encode_message, FlatBufferMessage};
 pub use error::ProtocolError;
-pub use error_code::ErrorCode;
+pub use error_code::{ErrorCategory, ErrorCode, RetryClass};
+pub use error_detail::{ByteRange, ErrorDetail, RpcError};
 pub use inbox_event::{
-    payloads as inbox_event_payloads, topics as inbox_event_topics, UserInboxEventEnvelope,
-    USER_INBOX_EVENT_SCHEMA_VERSION_V1,
+    payloads as inbox_event_payloads, topics as inbox_event_topics, InboxCursor, InboxEvent,
+    UserInboxEventEnvelope, USER_INBOX_EVENT_SCHEMA_VERSION_MAX,
+    USER_INBOX_EVENT_SCHEMA_VERSION_V1, USER_INBOX_EVENT_SCHEMA_VERSION_V2,
 };
 pub use message::*;
 pub use notification::*;
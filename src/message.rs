@@ -100,6 +100,8 @@ pub enum ContentMessageType {
     RedPacket = 11,
     /// 转账（PrivChat Money Message，PLATFORM-only）。payload 只带 transferId + 展示快照。
     MoneyTransfer = 12,
+    /// Bot 交互消息：文本 + 内联键盘（`InteractiveMetadata`）。
+    Interactive = 13,
 }
 
 impl ContentMessageType {
@@ -118,6 +120,7 @@ impl ContentMessageType {
             "forward" => Some(ContentMessageType::Forward),
             "red_packet" => Some(ContentMessageType::RedPacket),
             "money_transfer" => Some(ContentMessageType::MoneyTransfer),
+            "interactive" => Some(ContentMessageType::Interactive),
             _ => None,
         }
     }
@@ -137,6 +140,7 @@ impl ContentMessageType {
             10 => Some(ContentMessageType::Forward),
             11 => Some(ContentMessageType::RedPacket),
             12 => Some(ContentMessageType::MoneyTransfer),
+            13 => Some(ContentMessageType::Interactive),
             _ => None,
         }
     }
@@ -160,6 +164,7 @@ impl ContentMessageType {
             ContentMessageType::Forward => "forward",
             ContentMessageType::RedPacket => "red_packet",
             ContentMessageType::MoneyTransfer => "money_transfer",
+            ContentMessageType::Interactive => "interactive",
         }
     }
 }
@@ -15,6 +15,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::rpc::group::GroupSettingChange;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -206,6 +207,14 @@ pub enum NotificationType {
         changer_name: String,
     },
 
+    /// 群设置变更（公告变更另见 `GroupAnnouncementChanged`）
+    GroupSettingsChanged {
+        group_id: u64,
+        changed_by: u64,
+        changer_name: String,
+        changes: Vec<GroupSettingChange>,
+    },
+
     /// 群主转让
     GroupOwnerTransferred {
         group_id: u64,
@@ -494,6 +503,7 @@ impl NotificationMessage {
             NotificationType::GroupNameChanged { .. } => "group_name_changed",
             NotificationType::GroupAvatarChanged { .. } => "group_avatar_changed",
             NotificationType::GroupAnnouncementChanged { .. } => "group_announcement_changed",
+            NotificationType::GroupSettingsChanged { .. } => "group_settings_changed",
             NotificationType::GroupOwnerTransferred { .. } => "group_owner_transferred",
             NotificationType::GroupAdminAdded { .. } => "group_admin_added",
             NotificationType::GroupAdminRemoved { .. } => "group_admin_removed",
@@ -564,6 +574,17 @@ impl NotificationMessage {
                     changer_name, old_name, new_name
                 )
             }
+            NotificationType::GroupSettingsChanged {
+                changer_name,
+                changes,
+                ..
+            } => {
+                let described: Vec<String> = changes
+                    .iter()
+                    .map(GroupSettingChange::describe_cn)
+                    .collect();
+                format!("{} {}", changer_name, described.join("，"))
+            }
             NotificationType::GroupOwnerTransferred {
                 old_owner_name,
                 new_owner_name,
@@ -620,6 +641,48 @@ impl NotificationMessage {
     }
 }
 
+impl NotificationType {
+    /// 由 [`GroupSettingsData::apply`](crate::rpc::group::GroupSettingsData::apply)
+    /// 的结果生成要广播给群成员的通知。
+    ///
+    /// 公告变更单独成一条 `GroupAnnouncementChanged`（客户端对公告有专门的
+    /// 展示），其余变更合并为一条 `GroupSettingsChanged`；没有变更时返回空。
+    pub fn from_group_setting_changes(
+        group_id: u64,
+        group_name: &str,
+        changed_by: u64,
+        changer_name: &str,
+        changes: Vec<GroupSettingChange>,
+    ) -> Vec<NotificationType> {
+        let mut notifications = Vec::new();
+        let mut rest = Vec::new();
+        for change in changes {
+            match change {
+                GroupSettingChange::Announcement {
+                    new: Some(announcement),
+                    ..
+                } => notifications.push(NotificationType::GroupAnnouncementChanged {
+                    group_id,
+                    group_name: group_name.to_string(),
+                    announcement,
+                    changed_by,
+                    changer_name: changer_name.to_string(),
+                }),
+                other => rest.push(other),
+            }
+        }
+        if !rest.is_empty() {
+            notifications.push(NotificationType::GroupSettingsChanged {
+                group_id,
+                changed_by,
+                changer_name: changer_name.to_string(),
+                changes: rest,
+            });
+        }
+        notifications
+    }
+}
+
 /// 格式化时长
 fn format_duration(seconds: u64) -> String {
     if seconds < 60 {
@@ -710,6 +773,40 @@ mod tests {
         assert!(msg.should_persist);
     }
 
+    #[test]
+    fn group_setting_changes_become_notifications() {
+        let changes = vec![
+            GroupSettingChange::AllMuted {
+                old: false,
+                new: true,
+            },
+            GroupSettingChange::Announcement {
+                old: None,
+                new: Some("明天团建".to_string()),
+            },
+            GroupSettingChange::ForbidForward {
+                old: false,
+                new: true,
+            },
+        ];
+        let notifications =
+            NotificationType::from_group_setting_changes(1, "测试群", 2, "李四", changes);
+        assert_eq!(notifications.len(), 2);
+        assert!(matches!(
+            &notifications[0],
+            NotificationType::GroupAnnouncementChanged { announcement, .. } if announcement == "明天团建"
+        ));
+        assert_eq!(
+            NotificationMessage::generate_display_text_cn(&notifications[1]),
+            "李四 开启了全员禁言，禁止转发群消息"
+        );
+        let json = serde_json::to_value(&notifications[1]).unwrap();
+        assert_eq!(json["type"], "GroupSettingsChanged");
+        assert_eq!(json["data"]["changes"][0]["field"], "all_muted");
+
+        assert!(NotificationType::from_group_setting_changes(1, "g", 2, "n", vec![]).is_empty());
+    }
+
     #[test]
     fn test_format_duration() {
         assert_eq!(format_duration(30), "30秒");
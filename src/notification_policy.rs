@@ -0,0 +1,445 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! 通知策略 —— 一条消息该响铃、静默还是不推。
+//!
+//! `channel/mute` 只有一个布尔，`DevicePushStatusResponse.user_push_enabled`
+//! 只管全局开关；[`NotificationPolicy`] 把静音到期时间、仅 @ 提醒、关键词、
+//! 免打扰时段和按频道覆盖收拢成一个模型，以 `notification_policy` 实体多端
+//! 同步（entity_id = 用户 ID）。server 决定离线推送、客户端决定前台提醒时
+//! 都调用同一个 [`decide`]，两边结论一致。
+//!
+//! 判定顺序：
+//!
+//! 1. 撤回通知、自己发的消息 → [`NotificationDecision::Suppress`]；
+//! 2. 取频道规则（频道覆盖优先，否则按私聊 / 群聊默认）：
+//!    - 静音中：@ 我或命中关键词 → `Alert`，否则 `Suppress`；
+//!    - 仅 @ 提醒：@ 我或命中关键词 → `Alert`，否则 `Silent`；
+//!    - 其余 → `Alert`；
+//! 3. 处于免打扰时段时，`Alert` 降为 `Silent`（若时段允许 @ 穿透，则 @ 我
+//!    或命中关键词的消息保持 `Alert`）。
+
+use serde::{Deserialize, Serialize};
+
+use crate::rpc::sync::NOTIFICATION_POLICY_ENTITY_TYPE;
+use crate::{EntityInvalidation, EntityMutationHint, MessagePayloadEnvelope, PushMessageRequest};
+
+/// `mute_until` 取此值表示永久静音。取 JS 安全整数上限（2^53 − 1，约公元
+/// 287396 年），Web 端读回写回不失真；更大的值解码时收敛到它。
+pub const MUTE_FOREVER: i64 = 9_007_199_254_740_991;
+
+const MINUTES_PER_DAY: i64 = 24 * 60;
+
+/// 判定结果。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationDecision {
+    /// 正常提醒（声音 / 横幅）
+    Alert,
+    /// 静默投递：更新角标与通知列表，不响铃不横幅
+    Silent,
+    /// 不推送
+    Suppress,
+}
+
+/// 一类频道（或单个频道）的提醒规则。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NotificationRule {
+    /// 静音截止时间（毫秒）；`None` 或已过期 = 未静音，[`MUTE_FOREVER`] = 永久
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "clamp_mute_until"
+    )]
+    pub mute_until: Option<i64>,
+    /// 仅 @ 我（或命中关键词）时提醒，其余静默
+    #[serde(default)]
+    pub mention_only: bool,
+}
+
+impl NotificationRule {
+    pub fn is_muted(&self, now_ms: i64) -> bool {
+        self.mute_until.is_some_and(|until| until > now_ms)
+    }
+}
+
+/// 老版本写入的 `i64::MAX` 经 JS 往返后变成 `9223372036854776000`（超出 i64）
+/// 或浮点数；一律收敛到 [`MUTE_FOREVER`]，不让整份策略解析失败。
+fn clamp_mute_until<'de, D>(deserializer: D) -> Result<Option<i64>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Wire {
+        Int(i64),
+        Uint(u64),
+        Float(f64),
+    }
+    Ok(
+        Option::<Wire>::deserialize(deserializer)?.map(|wire| match wire {
+            Wire::Int(v) => v.min(MUTE_FOREVER),
+            Wire::Uint(v) => i64::try_from(v).map_or(MUTE_FOREVER, |v| v.min(MUTE_FOREVER)),
+            Wire::Float(v) if v >= MUTE_FOREVER as f64 => MUTE_FOREVER,
+            Wire::Float(v) => v as i64,
+        }),
+    )
+}
+
+/// 单个频道的规则覆盖。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChannelNotificationOverride {
+    #[serde(with = "crate::serde_u64")]
+    pub channel_id: u64,
+    pub channel_type: u8,
+    #[serde(flatten)]
+    pub rule: NotificationRule,
+}
+
+/// 免打扰时段，按用户本地时间的「一天中的分钟数」表示。
+///
+/// `start_minute == end_minute` 表示空时段；`start_minute > end_minute` 表示
+/// 跨午夜（如 22:00 ~ 07:00）。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuietHours {
+    /// 开始（含），0..1440
+    pub start_minute: u16,
+    /// 结束（不含），0..1440
+    pub end_minute: u16,
+    /// 用户本地时间相对 UTC 的偏移（分钟）。判定只用这个值；夏令时切换时
+    /// 由客户端按 `timezone` 重新计算后写回。
+    pub utc_offset_minutes: i32,
+    /// IANA 时区名（如 `Asia/Shanghai`），仅供展示与重新计算偏移
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+    /// @ 我 / 命中关键词的消息在时段内照常提醒
+    #[serde(default)]
+    pub allow_mentions: bool,
+}
+
+impl QuietHours {
+    /// `now_ms` 是否落在时段内。
+    pub fn contains(&self, now_ms: i64) -> bool {
+        let minute = (now_ms.div_euclid(60_000) + i64::from(self.utc_offset_minutes))
+            .rem_euclid(MINUTES_PER_DAY);
+        let (start, end) = (i64::from(self.start_minute), i64::from(self.end_minute));
+        if start <= end {
+            (start..end).contains(&minute)
+        } else {
+            minute >= start || minute < end
+        }
+    }
+}
+
+/// 用户的通知策略（`notification_policy` 实体负载）。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NotificationPolicy {
+    /// 私聊默认规则
+    #[serde(default)]
+    pub direct: NotificationRule,
+    /// 群聊默认规则
+    #[serde(default)]
+    pub group: NotificationRule,
+    /// 按频道覆盖，优先于默认规则
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub overrides: Vec<ChannelNotificationOverride>,
+    /// 关键词：正文包含任一关键词（不区分大小写）视同 @ 我
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keywords: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quiet_hours: Option<QuietHours>,
+    /// 最后修改时间（毫秒），多端 LWW 依据
+    #[serde(default)]
+    pub updated_at: i64,
+}
+
+impl NotificationPolicy {
+    /// 频道生效的规则：有覆盖用覆盖，否则按频道类型（1 = 私聊）取默认。
+    pub fn rule_for(&self, channel_id: u64, channel_type: u8) -> &NotificationRule {
+        self.overrides
+            .iter()
+            .find(|o| o.channel_id == channel_id && o.channel_type == channel_type)
+            .map(|o| &o.rule)
+            .unwrap_or(if channel_type == 1 {
+                &self.direct
+            } else {
+                &self.group
+            })
+    }
+
+    /// 把旧的 `channel/mute` 布尔映射成频道覆盖：静音 = 永久静音，取消静音
+    /// = 清除静音（保留该频道的其他设置）。
+    pub fn set_channel_muted(&mut self, channel_id: u64, channel_type: u8, muted: bool) {
+        let mute_until = muted.then_some(MUTE_FOREVER);
+        match self
+            .overrides
+            .iter_mut()
+            .find(|o| o.channel_id == channel_id && o.channel_type == channel_type)
+        {
+            Some(o) => o.rule.mute_until = mute_until,
+            None if muted => self.overrides.push(ChannelNotificationOverride {
+                channel_id,
+                channel_type,
+                rule: NotificationRule {
+                    mute_until,
+                    mention_only: false,
+                },
+            }),
+            None => {}
+        }
+        self.overrides
+            .retain(|o| o.rule != NotificationRule::default());
+    }
+
+    /// 正文是否命中任一关键词。
+    pub fn matches_keyword(&self, content: &str) -> bool {
+        if self.keywords.is_empty() {
+            return false;
+        }
+        let content = content.to_lowercase();
+        self.keywords
+            .iter()
+            .filter(|k| !k.trim().is_empty())
+            .any(|k| content.contains(&k.to_lowercase()))
+    }
+
+    /// 策略更新后由 server 推送的失效通知条目。
+    pub fn invalidation(user_id: u64, target_version: u64) -> EntityInvalidation {
+        EntityInvalidation {
+            entity_type: NOTIFICATION_POLICY_ENTITY_TYPE.to_string(),
+            entity_id: Some(user_id.to_string()),
+            scope: None,
+            target_version,
+            mutation_hint: EntityMutationHint::Upsert,
+        }
+    }
+}
+
+/// 判定 `me` 收到的这条推送该如何提醒。`envelope` 为 `push.payload` 解码结果。
+pub fn decide(
+    policy: &NotificationPolicy,
+    push: &PushMessageRequest,
+    envelope: &MessagePayloadEnvelope,
+    me: u64,
+    now_ms: i64,
+) -> NotificationDecision {
+    if push.deleted || push.from_uid == me {
+        return NotificationDecision::Suppress;
+    }
+    let addressed =
+        envelope.mentioned_user_ids.contains(&me) || policy.matches_keyword(&envelope.content);
+    let rule = policy.rule_for(push.channel_id, push.channel_type);
+
+    let decision = if rule.is_muted(now_ms) {
+        if addressed {
+            NotificationDecision::Alert
+        } else {
+            NotificationDecision::Suppress
+        }
+    } else if rule.mention_only && !addressed {
+        NotificationDecision::Silent
+    } else {
+        NotificationDecision::Alert
+    };
+
+    match &policy.quiet_hours {
+        Some(quiet)
+            if decision == NotificationDecision::Alert
+                && quiet.contains(now_ms)
+                && !(quiet.allow_mentions && addressed) =>
+        {
+            NotificationDecision::Silent
+        }
+        _ => decision,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use NotificationDecision::*;
+
+    const ME: u64 = 10001;
+    const PEER: u64 = 10002;
+    /// 2026-01-01 00:00:00 UTC
+    const MIDNIGHT_UTC: i64 = 1_767_225_600_000;
+    const HOUR: i64 = 3_600_000;
+
+    fn push(channel_id: u64, channel_type: u8) -> PushMessageRequest {
+        PushMessageRequest {
+            channel_id,
+            channel_type,
+            from_uid: PEER,
+            ..Default::default()
+        }
+    }
+
+    fn text(content: &str, mentions: &[u64]) -> MessagePayloadEnvelope {
+        MessagePayloadEnvelope {
+            content: content.to_string(),
+            mentioned_user_ids: mentions.to_vec(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn own_and_recalled_messages_are_suppressed() {
+        let policy = NotificationPolicy::default();
+        let mut own = push(1, 1);
+        own.from_uid = ME;
+        assert_eq!(decide(&policy, &own, &text("hi", &[]), ME, 0), Suppress);
+        let mut recalled = push(1, 1);
+        recalled.deleted = true;
+        assert_eq!(decide(&policy, &recalled, &text("", &[]), ME, 0), Suppress);
+        assert_eq!(decide(&policy, &push(1, 1), &text("hi", &[]), ME, 0), Alert);
+    }
+
+    #[test]
+    fn mute_until_expires_and_mentions_break_through() {
+        let mut policy = NotificationPolicy::default();
+        policy.group.mute_until = Some(MIDNIGHT_UTC + HOUR);
+        let group = push(7, 2);
+
+        assert_eq!(
+            decide(&policy, &group, &text("hi", &[]), ME, MIDNIGHT_UTC),
+            Suppress
+        );
+        assert_eq!(
+            decide(&policy, &group, &text("hi", &[ME]), ME, MIDNIGHT_UTC),
+            Alert
+        );
+        assert_eq!(
+            decide(&policy, &group, &text("hi", &[]), ME, MIDNIGHT_UTC + HOUR),
+            Alert
+        );
+        // 私聊不受群聊默认规则影响
+        assert_eq!(
+            decide(&policy, &push(8, 1), &text("hi", &[]), ME, MIDNIGHT_UTC),
+            Alert
+        );
+    }
+
+    #[test]
+    fn mention_only_and_keywords() {
+        let mut policy = NotificationPolicy {
+            keywords: vec!["Release".to_string(), "  ".to_string()],
+            ..Default::default()
+        };
+        policy.set_channel_muted(7, 2, false);
+        assert!(policy.overrides.is_empty());
+        policy.overrides.push(ChannelNotificationOverride {
+            channel_id: 7,
+            channel_type: 2,
+            rule: NotificationRule {
+                mute_until: None,
+                mention_only: true,
+            },
+        });
+        let group = push(7, 2);
+        assert_eq!(decide(&policy, &group, &text("lunch?", &[]), ME, 0), Silent);
+        assert_eq!(
+            decide(&policy, &group, &text("the RELEASE is out", &[]), ME, 0),
+            Alert
+        );
+        assert_eq!(decide(&policy, &group, &text("ping", &[ME]), ME, 0), Alert);
+        // 空白关键词不会匹配一切
+        assert_eq!(decide(&policy, &group, &text("a b", &[]), ME, 0), Silent);
+    }
+
+    #[test]
+    fn legacy_channel_mute_maps_to_override() {
+        let mut policy = NotificationPolicy::default();
+        policy.set_channel_muted(7, 2, true);
+        assert_eq!(policy.rule_for(7, 2).mute_until, Some(MUTE_FOREVER));
+        assert_eq!(
+            decide(&policy, &push(7, 2), &text("hi", &[]), ME, MUTE_FOREVER - 1),
+            Suppress
+        );
+        policy.set_channel_muted(7, 2, false);
+        assert!(policy.overrides.is_empty());
+    }
+
+    #[test]
+    fn quiet_hours_wrap_midnight_in_local_time() {
+        // 北京时间 22:00 ~ 07:00
+        let quiet = QuietHours {
+            start_minute: 22 * 60,
+            end_minute: 7 * 60,
+            utc_offset_minutes: 8 * 60,
+            timezone: Some("Asia/Shanghai".to_string()),
+            allow_mentions: true,
+        };
+        // UTC 00:00 = 北京 08:00（时段外）；UTC 15:00 = 北京 23:00（时段内）
+        assert!(!quiet.contains(MIDNIGHT_UTC));
+        assert!(quiet.contains(MIDNIGHT_UTC + 15 * HOUR));
+        // 北京 06:59 在时段内，07:00 为结束边界（不含）
+        assert!(quiet.contains(MIDNIGHT_UTC - HOUR - 60_000));
+        assert!(!quiet.contains(MIDNIGHT_UTC - HOUR));
+
+        let policy = NotificationPolicy {
+            quiet_hours: Some(quiet),
+            ..Default::default()
+        };
+        let night = MIDNIGHT_UTC + 15 * HOUR;
+        assert_eq!(
+            decide(&policy, &push(1, 1), &text("hi", &[]), ME, night),
+            Silent
+        );
+        assert_eq!(
+            decide(&policy, &push(1, 2), &text("hi", &[ME]), ME, night),
+            Alert
+        );
+        assert_eq!(
+            decide(&policy, &push(1, 1), &text("hi", &[]), ME, MIDNIGHT_UTC),
+            Alert
+        );
+
+        let empty = QuietHours::default();
+        assert!(!empty.contains(MIDNIGHT_UTC));
+    }
+
+    #[test]
+    fn policy_json_shape() {
+        let mut policy = NotificationPolicy::default();
+        policy.set_channel_muted(608_993_815_990_284_288, 2, true);
+        let json = serde_json::to_value(&policy).unwrap();
+        assert_eq!(json["overrides"][0]["channel_id"], "608993815990284288");
+        assert_eq!(json["overrides"][0]["mute_until"], MUTE_FOREVER);
+        assert_eq!(MUTE_FOREVER as f64 as i64, MUTE_FOREVER);
+        let back: NotificationPolicy = serde_json::from_value(json).unwrap();
+        assert_eq!(back, policy);
+
+        // 老数据经 JS 往返后的超大值收敛为永久静音
+        for legacy in [
+            r#"{"group":{"mute_until":9223372036854776000}}"#,
+            r#"{"group":{"mute_until":9223372036854775807}}"#,
+            r#"{"group":{"mute_until":9.223372036854776e18}}"#,
+        ] {
+            let policy: NotificationPolicy = serde_json::from_str(legacy).unwrap();
+            assert_eq!(policy.group.mute_until, Some(MUTE_FOREVER), "{legacy}");
+        }
+        let policy: NotificationPolicy =
+            serde_json::from_str(r#"{"group":{"mute_until":1767225600000}}"#).unwrap();
+        assert_eq!(policy.group.mute_until, Some(1_767_225_600_000));
+        let empty: NotificationPolicy = serde_json::from_str("{}").unwrap();
+        assert_eq!(empty, NotificationPolicy::default());
+
+        let invalidation = NotificationPolicy::invalidation(ME, 3);
+        assert_eq!(invalidation.entity_type, NOTIFICATION_POLICY_ENTITY_TYPE);
+        assert_eq!(invalidation.entity_id.as_deref(), Some("10001"));
+    }
+}
@@ -16,6 +16,7 @@
 // limitations under the License.
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// 在线状态枚举
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -89,7 +90,7 @@ pub struct PresenceBatchStatusResponse {
 }
 
 /// Presence 聚合快照
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PresenceSnapshot {
     /// 用户ID
     pub user_id: u64,
@@ -104,7 +105,7 @@ pub struct PresenceSnapshot {
 }
 
 /// Presence 状态变化通知（服务端主动推送）
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PresenceChangedNotification {
     /// 用户ID
     pub user_id: u64,
@@ -114,8 +115,62 @@ pub struct PresenceChangedNotification {
     pub snapshot: PresenceSnapshot,
 }
 
+/// 按 per-user `version` 去重的 presence 缓存。
+///
+/// presence 推送可能乱序、重复（多条推送通道、重连后补发），只接受比已知
+/// 版本更新的快照，旧的直接丢弃。
+#[derive(Debug, Clone, Default)]
+pub struct PresenceCache {
+    snapshots: HashMap<u64, PresenceSnapshot>,
+}
+
+impl PresenceCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 应用一条快照；返回 `true` 表示被接受（比已有版本新）。
+    pub fn apply_snapshot(&mut self, snapshot: PresenceSnapshot) -> bool {
+        match self.snapshots.get(&snapshot.user_id) {
+            Some(current) if current.version >= snapshot.version => false,
+            _ => {
+                self.snapshots.insert(snapshot.user_id, snapshot);
+                true
+            }
+        }
+    }
+
+    pub fn apply_changed(&mut self, notification: PresenceChangedNotification) -> bool {
+        self.apply_snapshot(notification.snapshot)
+    }
+
+    /// 批量应用，返回被接受的用户 ID。
+    pub fn apply_batch(&mut self, items: impl IntoIterator<Item = PresenceSnapshot>) -> Vec<u64> {
+        items
+            .into_iter()
+            .filter_map(|snapshot| {
+                let user_id = snapshot.user_id;
+                self.apply_snapshot(snapshot).then_some(user_id)
+            })
+            .collect()
+    }
+
+    pub fn get(&self, user_id: u64) -> Option<&PresenceSnapshot> {
+        self.snapshots.get(&user_id)
+    }
+
+    pub fn version(&self, user_id: u64) -> u64 {
+        self.snapshots.get(&user_id).map(|s| s.version).unwrap_or(0)
+    }
+
+    /// 取消订阅 / 用户不再可见时移除；之后任意版本都会被重新接受。
+    pub fn remove(&mut self, user_id: u64) -> Option<PresenceSnapshot> {
+        self.snapshots.remove(&user_id)
+    }
+}
+
 /// 输入状态动作类型
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum TypingActionType {
     /// 正在输入文字
     Typing,
@@ -167,7 +222,7 @@ pub struct TypingIndicatorResponse {
 }
 
 /// 输入状态变化通知（服务端推送给会话中的其他成员）
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct TypingStatusNotification {
     /// 用户ID
     pub user_id: u64,
@@ -186,7 +241,7 @@ pub struct TypingStatusNotification {
 }
 
 /// 在线状态隐私设置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PresencePrivacySettings {
     /// 谁能看到我的在线状态
     pub show_online_to: PrivacyRule,
@@ -195,7 +250,7 @@ pub struct PresencePrivacySettings {
 }
 
 /// 隐私规则
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum PrivacyRule {
     /// 所有人
     Everyone,
@@ -221,6 +276,93 @@ impl Default for PresencePrivacySettings {
     }
 }
 
+impl PrivacyRule {
+    /// `viewer` 是否被此规则允许查看。
+    ///
+    /// `Custom`：拒绝列表优先；允许列表为空时表示「除拒绝列表外所有人」，
+    /// 否则仅允许列表中的用户。
+    pub fn permits(&self, viewer: u64, is_contact: bool) -> bool {
+        match self {
+            PrivacyRule::Everyone => true,
+            PrivacyRule::Contacts => is_contact,
+            PrivacyRule::Nobody => false,
+            PrivacyRule::Custom {
+                allow_users,
+                deny_users,
+            } => {
+                !deny_users.contains(&viewer)
+                    && (allow_users.is_empty() || allow_users.contains(&viewer))
+            }
+        }
+    }
+}
+
+/// 某个查看者眼中的在线状态（[`PresenceSnapshot`] 按隐私设置投影后的结果）。
+///
+/// 无权查看的字段为 `None`，`status` 始终存在：无权查看精确时间时只给出
+/// 粗粒度分档，且不会是 `Online`（在线本身也是隐私）。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PresenceView {
+    pub user_id: u64,
+    pub status: OnlineStatus,
+    /// 是否在线；无权查看时为 `None`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_online: Option<bool>,
+    /// 最后活跃时间（Unix 秒）；无权查看时为 `None`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_seen_at: Option<i64>,
+    /// 在线设备数；与 `is_online` 同权限
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device_count: Option<u32>,
+    pub version: u64,
+}
+
+impl PresencePrivacySettings {
+    /// 把 `owner` 的快照投影成 `viewer` 可见的视图。
+    ///
+    /// 互惠规则：某一项（在线 / 最后上线时间）需要双方都对彼此可见——
+    /// 自己对 `owner` 隐藏了该项，也就看不到 `owner` 的该项。
+    /// `is_contact` 为双方是否互为联系人，`now_secs` 为当前 Unix 秒。
+    /// 查看自己时总是返回完整视图。
+    pub fn project(
+        &self,
+        snapshot: &PresenceSnapshot,
+        viewer: u64,
+        viewer_settings: &PresencePrivacySettings,
+        is_contact: bool,
+        now_secs: i64,
+    ) -> PresenceView {
+        let owner = snapshot.user_id;
+        let own = viewer == owner;
+        let can_see_online = own
+            || (self.show_online_to.permits(viewer, is_contact)
+                && viewer_settings.show_online_to.permits(owner, is_contact));
+        let can_see_last_seen = own
+            || (self.show_last_seen_to.permits(viewer, is_contact)
+                && viewer_settings.show_last_seen_to.permits(owner, is_contact));
+
+        let bucket = OnlineStatus::from_elapsed_seconds((now_secs - snapshot.last_seen_at).max(0));
+        let status = if can_see_online && snapshot.is_online {
+            OnlineStatus::Online
+        } else if bucket == OnlineStatus::Online {
+            // 只有可见的 `is_online` 能报 Online：公开了离线就不能自相矛盾；
+            // 隐藏时刚离线与「在线但隐藏」不可区分
+            OnlineStatus::Recently
+        } else {
+            bucket
+        };
+
+        PresenceView {
+            user_id: owner,
+            status,
+            is_online: can_see_online.then_some(snapshot.is_online),
+            last_seen_at: can_see_last_seen.then_some(snapshot.last_seen_at),
+            device_count: can_see_online.then_some(snapshot.device_count),
+            version: snapshot.version,
+        }
+    }
+}
+
 /// 获取群组在线统计请求
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GetGroupOnlineStatsRequest {
@@ -272,6 +414,194 @@ mod tests {
         );
     }
 
+    const OWNER: u64 = 1;
+    const VIEWER: u64 = 2;
+    const NOW: i64 = 1_700_000_000;
+
+    fn settings(online: PrivacyRule, last_seen: PrivacyRule) -> PresencePrivacySettings {
+        PresencePrivacySettings {
+            show_online_to: online,
+            show_last_seen_to: last_seen,
+        }
+    }
+
+    fn snapshot(is_online: bool, last_seen_at: i64) -> PresenceSnapshot {
+        PresenceSnapshot {
+            user_id: OWNER,
+            is_online,
+            last_seen_at,
+            device_count: 2,
+            version: 7,
+        }
+    }
+
+    #[test]
+    fn privacy_rule_permits_matrix() {
+        let custom_allow = PrivacyRule::Custom {
+            allow_users: vec![VIEWER],
+            deny_users: vec![],
+        };
+        let custom_deny = PrivacyRule::Custom {
+            allow_users: vec![],
+            deny_users: vec![VIEWER],
+        };
+        let custom_both = PrivacyRule::Custom {
+            allow_users: vec![VIEWER],
+            deny_users: vec![VIEWER],
+        };
+        // (rule, viewer, is_contact, expected)
+        let cases = [
+            (PrivacyRule::Everyone, VIEWER, false, true),
+            (PrivacyRule::Everyone, VIEWER, true, true),
+            (PrivacyRule::Contacts, VIEWER, false, false),
+            (PrivacyRule::Contacts, VIEWER, true, true),
+            (PrivacyRule::Nobody, VIEWER, false, false),
+            (PrivacyRule::Nobody, VIEWER, true, false),
+            (custom_allow.clone(), VIEWER, false, true),
+            (custom_allow, 3, true, false),
+            (custom_deny.clone(), VIEWER, true, false),
+            (custom_deny, 3, false, true),
+            (custom_both, VIEWER, true, false),
+        ];
+        for (rule, viewer, is_contact, expected) in cases {
+            assert_eq!(
+                rule.permits(viewer, is_contact),
+                expected,
+                "{rule:?} viewer={viewer} contact={is_contact}"
+            );
+        }
+    }
+
+    #[test]
+    fn everyone_sees_everything() {
+        let open = PresencePrivacySettings::default();
+        let view = open.project(&snapshot(true, NOW), VIEWER, &open, false, NOW);
+        assert_eq!(view.status, OnlineStatus::Online);
+        assert_eq!(view.is_online, Some(true));
+        assert_eq!(view.last_seen_at, Some(NOW));
+        assert_eq!(view.device_count, Some(2));
+        assert_eq!(view.version, 7);
+
+        let view = open.project(&snapshot(false, NOW - 7_200), VIEWER, &open, false, NOW);
+        assert_eq!(view.status, OnlineStatus::LastWeek);
+        assert_eq!(view.is_online, Some(false));
+    }
+
+    #[test]
+    fn hidden_last_seen_is_coarsened() {
+        let open = PresencePrivacySettings::default();
+        let owner = settings(PrivacyRule::Everyone, PrivacyRule::Nobody);
+        let view = owner.project(&snapshot(false, NOW - 600), VIEWER, &open, true, NOW);
+        assert_eq!(view.status, OnlineStatus::Recently);
+        assert_eq!(view.last_seen_at, None);
+        assert_eq!(view.is_online, Some(false));
+
+        // 刚离线不能被识别为在线
+        let view = owner.project(&snapshot(false, NOW - 30), VIEWER, &open, true, NOW);
+        assert_eq!(view.status, OnlineStatus::Recently);
+    }
+
+    #[test]
+    fn exposed_offline_is_never_online() {
+        let open = PresencePrivacySettings::default();
+        let view = open.project(&snapshot(false, NOW - 30), VIEWER, &open, false, NOW);
+        assert_eq!(view.is_online, Some(false));
+        assert_eq!(view.status, OnlineStatus::Recently);
+        assert_eq!(view.last_seen_at, Some(NOW - 30));
+    }
+
+    #[test]
+    fn hidden_online_never_reports_online() {
+        let open = PresencePrivacySettings::default();
+        let owner = settings(PrivacyRule::Nobody, PrivacyRule::Nobody);
+        let view = owner.project(&snapshot(true, NOW), VIEWER, &open, true, NOW);
+        assert_eq!(view.status, OnlineStatus::Recently);
+        assert_eq!(view.is_online, None);
+        assert_eq!(view.device_count, None);
+        assert_eq!(view.last_seen_at, None);
+
+        let view = owner.project(
+            &snapshot(false, NOW - 40 * 86_400),
+            VIEWER,
+            &open,
+            true,
+            NOW,
+        );
+        assert_eq!(view.status, OnlineStatus::LongTimeAgo);
+    }
+
+    #[test]
+    fn contacts_only_depends_on_relationship() {
+        let open = PresencePrivacySettings::default();
+        let owner = settings(PrivacyRule::Contacts, PrivacyRule::Contacts);
+        let contact = owner.project(&snapshot(true, NOW), VIEWER, &open, true, NOW);
+        assert_eq!(contact.last_seen_at, Some(NOW));
+        let stranger = owner.project(&snapshot(true, NOW), VIEWER, &open, false, NOW);
+        assert_eq!(stranger.last_seen_at, None);
+        assert_eq!(stranger.is_online, None);
+    }
+
+    #[test]
+    fn reciprocity_hides_others_from_hiders() {
+        let open = PresencePrivacySettings::default();
+        let hider = settings(PrivacyRule::Everyone, PrivacyRule::Nobody);
+        let view = open.project(&snapshot(false, NOW - 600), VIEWER, &hider, true, NOW);
+        assert_eq!(view.last_seen_at, None);
+        assert_eq!(view.is_online, Some(false));
+        assert_eq!(view.status, OnlineStatus::Recently);
+
+        // 只对 owner 以外的人隐藏，不影响查看 owner
+        let selective = settings(
+            PrivacyRule::Everyone,
+            PrivacyRule::Custom {
+                allow_users: vec![],
+                deny_users: vec![99],
+            },
+        );
+        let view = open.project(&snapshot(false, NOW - 600), VIEWER, &selective, false, NOW);
+        assert_eq!(view.last_seen_at, Some(NOW - 600));
+    }
+
+    #[test]
+    fn owner_always_sees_self() {
+        let hidden = settings(PrivacyRule::Nobody, PrivacyRule::Nobody);
+        let view = hidden.project(&snapshot(true, NOW), OWNER, &hidden, false, NOW);
+        assert_eq!(view.status, OnlineStatus::Online);
+        assert_eq!(view.last_seen_at, Some(NOW));
+        assert_eq!(view.device_count, Some(2));
+    }
+
+    #[test]
+    fn presence_cache_drops_stale_versions() {
+        let mut cache = PresenceCache::new();
+        assert!(cache.apply_snapshot(snapshot(true, NOW)));
+        let mut stale = snapshot(false, NOW - 60);
+        stale.version = 6;
+        assert!(!cache.apply_snapshot(stale));
+        assert!(!cache.apply_snapshot(snapshot(false, NOW)));
+        assert!(cache.get(OWNER).unwrap().is_online);
+
+        let mut newer = snapshot(false, NOW + 10);
+        newer.version = 8;
+        let mut other = snapshot(true, NOW);
+        other.user_id = VIEWER;
+        assert_eq!(
+            cache.apply_batch(vec![stale_copy(), newer, other]),
+            vec![OWNER, VIEWER]
+        );
+        assert_eq!(cache.version(OWNER), 8);
+        assert!(!cache.get(OWNER).unwrap().is_online);
+
+        cache.remove(OWNER);
+        assert!(cache.apply_snapshot(snapshot(true, NOW)));
+    }
+
+    fn stale_copy() -> PresenceSnapshot {
+        let mut stale = snapshot(true, NOW);
+        stale.version = 1;
+        stale
+    }
+
     #[test]
     fn test_privacy_rule_default() {
         let settings = PresencePrivacySettings::default();
@@ -8,6 +8,7 @@
 use crate::codec::FlatBufferMessage;
 use crate::error::ProtocolError;
 use crate::fb;
+use crate::message::ContentMessageType;
 use flatbuffers::FlatBufferBuilder;
 use serde::{Deserialize, Serialize};
 
@@ -160,9 +161,60 @@ pub struct ForwardMessageRef {
     pub extra: Vec<u8>,
 }
 
+/// Maximum nesting depth of merged-forward bundles. A top-level envelope is
+/// depth 0; each bundle item's payload is one level deeper. Encoding a deeper
+/// envelope is rejected; decoding drops bundle items below this level.
+pub const FORWARD_BUNDLE_MAX_DEPTH: usize = 4;
+
+/// One message inside a merged-forward ("chat history") bundle. Carries the
+/// full nested envelope, so media and nested bundles survive forwarding.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ForwardBundleItem {
+    /// Original server_message_id; `None` when the source is unknown.
+    #[serde(default, with = "crate::serde_u64::option")]
+    pub original_message_id: Option<u64>,
+    #[serde(with = "crate::serde_u64")]
+    pub sender_id: u64,
+    /// Display-name snapshot taken at forward time.
+    #[serde(default)]
+    pub sender_name: String,
+    /// Original send time, ms.
+    pub timestamp: i64,
+    /// Unknown (newer) types degrade to Text, matching the FlatBuffers path.
+    #[serde(deserialize_with = "lenient_content_type")]
+    pub message_type: ContentMessageType,
+    pub payload: MessagePayloadEnvelope,
+}
+
+/// Accepts the derived variant name, the snake_case name or the u32 wire
+/// value; anything unrecognised becomes `Text` so one newer item can't fail
+/// the whole bundle.
+fn lenient_content_type<'de, D>(deserializer: D) -> Result<ContentMessageType, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = serde_json::Value::deserialize(deserializer)?;
+    let parsed = match &value {
+        serde_json::Value::Number(n) => n
+            .as_u64()
+            .and_then(|n| u32::try_from(n).ok())
+            .and_then(ContentMessageType::from_u32),
+        serde_json::Value::String(s) => ContentMessageType::deserialize(&value)
+            .ok()
+            .or_else(|| ContentMessageType::from_str(s)),
+        _ => None,
+    };
+    Ok(parsed.unwrap_or(ContentMessageType::Text))
+}
+
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct ForwardMetadata {
+    /// Legacy reference list (id + display text + opaque extras).
+    #[serde(default)]
     pub messages: Vec<ForwardMessageRef>,
+    /// Typed merged-forward bundle; empty for legacy single forwards.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub items: Vec<ForwardBundleItem>,
 }
 
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
@@ -174,6 +226,68 @@ pub struct LinkMetadata {
     pub thumbnail_file_id: Option<u64>,
 }
 
+/// Maximum size of an inline button's callback data, in bytes. Enforced by
+/// the encoder; the server echoes the data back to the bot verbatim.
+pub const INLINE_CALLBACK_DATA_MAX_BYTES: usize = 64;
+
+/// What pressing an inline button does.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum InlineButtonAction {
+    /// Delivered to the bot as a `bot.callback_query` push; `data` is opaque
+    /// to everyone but the bot (1..=[`INLINE_CALLBACK_DATA_MAX_BYTES`] bytes).
+    Callback {
+        data: String,
+    },
+    Url {
+        url: String,
+    },
+    /// Pick a chat (or stay in this one) and prefill `query` for the bot.
+    SwitchToChat {
+        query: String,
+        #[serde(default)]
+        same_chat: bool,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InlineButton {
+    pub text: String,
+    #[serde(flatten)]
+    pub action: InlineButtonAction,
+}
+
+/// Bot inline keyboard attached under the message text (`content`).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct InteractiveMetadata {
+    pub rows: Vec<Vec<InlineButton>>,
+}
+
+impl InteractiveMetadata {
+    /// Reject callback data outside 1..=[`INLINE_CALLBACK_DATA_MAX_BYTES`] bytes.
+    pub fn validate(&self) -> Result<(), ProtocolError> {
+        for button in self.rows.iter().flatten() {
+            if let InlineButtonAction::Callback { data } = &button.action {
+                if data.is_empty() || data.len() > INLINE_CALLBACK_DATA_MAX_BYTES {
+                    return Err(ProtocolError::InvalidValue(format!(
+                        "callback data is {} bytes (allowed 1..={INLINE_CALLBACK_DATA_MAX_BYTES})",
+                        data.len()
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The callback button carrying `data`, if any. The server uses this to
+    /// reject presses whose data was never on the message.
+    pub fn find_callback(&self, data: &str) -> Option<&InlineButton> {
+        self.rows.iter().flatten().find(|button| {
+            matches!(&button.action, InlineButtonAction::Callback { data: d } if d == data)
+        })
+    }
+}
+
 // ------------------------------------------------------------------
 // Metadata enum (mirrors fbs union — NONE = no metadata, i.e. text/system)
 // ------------------------------------------------------------------
@@ -193,6 +307,7 @@ pub enum MessageMetadata {
     Sticker(StickerMetadata),
     Forward(ForwardMetadata),
     Link(LinkMetadata),
+    Interactive(InteractiveMetadata),
 }
 
 // ------------------------------------------------------------------
@@ -207,6 +322,43 @@ pub struct MessageSource {
     pub source_id: String,
 }
 
+// ------------------------------------------------------------------
+// Text entities (formatting / mention spans over a text body)
+// ------------------------------------------------------------------
+
+/// A styled or semantic span over a text body. `offset` / `length` count
+/// UTF-16 code units so JS / Swift / Kotlin editors can apply them directly.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct TextEntity {
+    pub offset: u32,
+    pub length: u32,
+    #[serde(flatten)]
+    pub kind: TextEntityKind,
+}
+
+/// Span kind. Serialized as `{"type":"mention","user_id":"…"}` flattened
+/// into the owning [`TextEntity`]; kinds from newer peers read as `Unknown`
+/// and are rendered as plain text.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TextEntityKind {
+    Mention {
+        #[serde(with = "crate::serde_u64")]
+        user_id: u64,
+    },
+    Bold,
+    Italic,
+    Strikethrough,
+    Code,
+    Pre,
+    TextLink {
+        url: String,
+    },
+    BotCommand,
+    #[serde(other)]
+    Unknown,
+}
+
 // ------------------------------------------------------------------
 // Top-level envelope
 // ------------------------------------------------------------------
@@ -228,6 +380,51 @@ pub struct MessagePayloadEnvelope {
 }
 
 
+impl MessagePayloadEnvelope {
+    /// Nesting depth of merged-forward bundles below this envelope: 0 when
+    /// it carries no bundle items, otherwise 1 + the deepest item payload.
+    pub fn forward_depth(&self) -> usize {
+        match &self.metadata {
+            Some(MessageMetadata::Forward(m)) => m
+                .items
+                .iter()
+                .map(|item| 1 + item.payload.forward_depth())
+                .max()
+                .unwrap_or(0),
+            _ => 0,
+        }
+    }
+
+    /// Encoder-side checks: bundle nesting depth and, for every envelope in
+    /// the bundle tree, inline-keyboard callback data size.
+    pub fn validate(&self) -> Result<(), ProtocolError> {
+        self.validate_forward_depth()?;
+        self.validate_metadata()
+    }
+
+    fn validate_metadata(&self) -> Result<(), ProtocolError> {
+        match &self.metadata {
+            Some(MessageMetadata::Interactive(m)) => m.validate(),
+            Some(MessageMetadata::Forward(m)) => m
+                .items
+                .iter()
+                .try_for_each(|item| item.payload.validate_metadata()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Reject envelopes whose bundles nest deeper than [`FORWARD_BUNDLE_MAX_DEPTH`].
+    pub fn validate_forward_depth(&self) -> Result<(), ProtocolError> {
+        let depth = self.forward_depth();
+        if depth > FORWARD_BUNDLE_MAX_DEPTH {
+            return Err(ProtocolError::InvalidValue(format!(
+                "forward bundle nested {depth} levels deep (max {FORWARD_BUNDLE_MAX_DEPTH})"
+            )));
+        }
+        Ok(())
+    }
+}
+
 // ------------------------------------------------------------------
 // Bridge to/from the legacy Value-based JSON envelope
 // ------------------------------------------------------------------
@@ -259,7 +456,8 @@ impl MediaRole {
 pub struct MediaRef {
     pub file_id: u64,
     pub role: MediaRole,
-    /// 同一 role 下的序号。当前每种 role 至多一个，恒为 0；多图消息落地时才会 > 0。
+    /// 同一 role 下的序号，按出现顺序递增。单条消息每种 role 至多一个，恒为 0；
+    /// 合并转发包里的附件依次 > 0（先外层、再按 bundle 顺序深度优先）。
     pub ordinal: i32,
 }
 
@@ -272,14 +470,23 @@ impl MessageMetadata {
     ///
     /// 🔴 **保留同一 file_id 的不同 role**，不去重。要去重的 id 列表见
     /// [`Self::unique_file_ids`]。
+    ///
+    /// 合并转发会递归进每个 bundle item 的 payload——被转发的文件同样要被
+    /// 新消息认领，否则接收端拿不到授权。递归深度受 [`FORWARD_BUNDLE_MAX_DEPTH`] 限制。
     pub fn attachment_refs(&self) -> Vec<MediaRef> {
         let mut refs = Vec::new();
+        self.collect_attachment_refs(&mut refs, 0);
+        refs
+    }
+
+    fn collect_attachment_refs(&self, refs: &mut Vec<MediaRef>, depth: usize) {
         let mut push = |file_id: u64, role: MediaRole| {
             if file_id != 0 {
+                let ordinal = refs.iter().filter(|r| r.role == role).count() as i32;
                 refs.push(MediaRef {
                     file_id,
                     role,
-                    ordinal: 0,
+                    ordinal,
                 });
             }
         };
@@ -309,11 +516,19 @@ impl MessageMetadata {
                     push(id, MediaRole::Thumbnail);
                 }
             }
+            MessageMetadata::Forward(value) => {
+                if depth < FORWARD_BUNDLE_MAX_DEPTH {
+                    for item in &value.items {
+                        if let Some(metadata) = &item.payload.metadata {
+                            metadata.collect_attachment_refs(refs, depth + 1);
+                        }
+                    }
+                }
+            }
             MessageMetadata::ContactCard(_)
             | MessageMetadata::Sticker(_)
-            | MessageMetadata::Forward(_) => {}
+            | MessageMetadata::Interactive(_) => {}
         }
-        refs
     }
 
     /// 去重后的 file_id 列表，供**按文件**做的操作使用（所有权绑定守卫、引用计数）。
@@ -371,6 +586,9 @@ impl MessageMetadata {
             Link => serde_json::from_value(value.clone())
                 .ok()
                 .map(MessageMetadata::Link),
+            Interactive => serde_json::from_value(value.clone())
+                .ok()
+                .map(MessageMetadata::Interactive),
         }
     }
 
@@ -397,6 +615,9 @@ impl MessageMetadata {
                 serde_json::to_value(m).unwrap_or(serde_json::Value::Null)
             }
             MessageMetadata::Link(m) => serde_json::to_value(m).unwrap_or(serde_json::Value::Null),
+            MessageMetadata::Interactive(m) => {
+                serde_json::to_value(m).unwrap_or(serde_json::Value::Null)
+            }
         }
     }
 }
@@ -424,6 +645,89 @@ mod tests {
         assert_eq!(meta.unique_file_ids(), vec![7]);
     }
 
+    /// 合并转发包里的附件同样要被新消息认领：递归进每个 item，同 role 的序号依次递增。
+    #[test]
+    fn forward_bundle_attachments_are_collected_recursively() {
+        let item = |payload: MessagePayloadEnvelope| ForwardBundleItem {
+            original_message_id: None,
+            sender_id: 1,
+            sender_name: String::new(),
+            timestamp: 0,
+            message_type: crate::message::ContentMessageType::Image,
+            payload,
+        };
+        let image = |file_id, thumb| MessagePayloadEnvelope {
+            metadata: Some(MessageMetadata::Image(ImageMetadata {
+                file_id,
+                thumbnail_file_id: Some(thumb),
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+        let inner = MessagePayloadEnvelope {
+            metadata: Some(MessageMetadata::Forward(ForwardMetadata {
+                items: vec![item(image(3, 4))],
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+        let meta = MessageMetadata::Forward(ForwardMetadata {
+            items: vec![item(image(1, 2)), item(inner)],
+            ..Default::default()
+        });
+
+        let refs = meta.attachment_refs();
+        let got: Vec<_> = refs
+            .iter()
+            .map(|r| (r.file_id, r.role, r.ordinal))
+            .collect();
+        assert_eq!(
+            got,
+            vec![
+                (1, MediaRole::Original, 0),
+                (2, MediaRole::Thumbnail, 0),
+                (3, MediaRole::Original, 1),
+                (4, MediaRole::Thumbnail, 1),
+            ]
+        );
+        assert_eq!(meta.unique_file_ids(), vec![1, 2, 3, 4]);
+    }
+
+    /// 旧 JSON 路径：包里某条是新版本才有的类型时降级为 Text，整包不能丢；
+    /// original_message_id 走字符串编码。
+    #[test]
+    fn legacy_json_forward_tolerates_unknown_item_type() {
+        use crate::message::ContentMessageType;
+        let item = ForwardBundleItem {
+            original_message_id: Some(u64::MAX),
+            sender_id: 1,
+            sender_name: String::new(),
+            timestamp: 0,
+            message_type: ContentMessageType::Image,
+            payload: MessagePayloadEnvelope::default(),
+        };
+        let mut value = serde_json::to_value(ForwardMetadata {
+            items: vec![item.clone(), item],
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(
+            value["items"][0]["original_message_id"],
+            serde_json::json!(u64::MAX.to_string())
+        );
+        value["items"][1]["message_type"] = serde_json::json!("Poll");
+
+        let Some(MessageMetadata::Forward(meta)) =
+            MessageMetadata::from_json_value(ContentMessageType::Forward, &value)
+        else {
+            panic!("bundle dropped");
+        };
+        assert_eq!(meta.items.len(), 2);
+        assert_eq!(meta.items[0].message_type, ContentMessageType::Image);
+        assert_eq!(meta.items[0].original_message_id, Some(u64::MAX));
+        assert_eq!(meta.items[1].message_type, ContentMessageType::Text);
+    }
+
     /// 类型决定哪些字段算附件。非媒体类型即使 JSON 里塞了 file_id，
     /// 也产生不出引用——它们根本没有对应的 typed variant。
     #[test]
@@ -833,9 +1137,52 @@ fn decode_forward_ref(v: fb::ForwardMessageRef<'_>) -> ForwardMessageRef {
     }
 }
 
+fn encode_bundle_item<'a>(
+    builder: &mut FlatBufferBuilder<'a>,
+    item: &ForwardBundleItem,
+    depth: usize,
+) -> flatbuffers::WIPOffset<fb::ForwardBundleItem<'a>> {
+    let sender_name = builder.create_string(&item.sender_name);
+    let payload = encode_payload_envelope_at(builder, &item.payload, depth);
+    fb::ForwardBundleItem::create(
+        builder,
+        &fb::ForwardBundleItemArgs {
+            original_message_id: item.original_message_id.unwrap_or(0),
+            sender_id: item.sender_id,
+            sender_name: Some(sender_name),
+            timestamp: item.timestamp,
+            message_type: item.message_type.as_u32(),
+            payload: Some(payload),
+        },
+    )
+}
+fn decode_bundle_item(v: fb::ForwardBundleItem<'_>, depth: usize) -> ForwardBundleItem {
+    let original_message_id = match v.original_message_id() {
+        0 => None,
+        n => Some(n),
+    };
+    ForwardBundleItem {
+        original_message_id,
+        sender_id: v.sender_id(),
+        sender_name: v.sender_name().unwrap_or("").to_string(),
+        timestamp: v.timestamp(),
+        // Unknown (newer) types degrade to Text; the nested envelope still
+        // carries the display content.
+        message_type: ContentMessageType::from_u32(v.message_type())
+            .unwrap_or(ContentMessageType::Text),
+        payload: v
+            .payload()
+            .map(|p| decode_payload_envelope_at(p, depth))
+            .unwrap_or_default(),
+    }
+}
+
+/// `depth` is the nesting level of the envelope owning this metadata; bundle
+/// items are only written while their payload stays within the depth cap.
 fn encode_forward<'a>(
     builder: &mut FlatBufferBuilder<'a>,
     m: &ForwardMetadata,
+    depth: usize,
 ) -> flatbuffers::WIPOffset<fb::ForwardMetadata<'a>> {
     let refs: Vec<_> = m
         .messages
@@ -843,19 +1190,39 @@ fn encode_forward<'a>(
         .map(|r| encode_forward_ref(builder, r))
         .collect();
     let messages = builder.create_vector(&refs);
+    let items = if m.items.is_empty() || depth >= FORWARD_BUNDLE_MAX_DEPTH {
+        None
+    } else {
+        let items: Vec<_> = m
+            .items
+            .iter()
+            .map(|item| encode_bundle_item(builder, item, depth + 1))
+            .collect();
+        Some(builder.create_vector(&items))
+    };
     fb::ForwardMetadata::create(
         builder,
         &fb::ForwardMetadataArgs {
             messages: Some(messages),
+            items,
         },
     )
 }
-fn decode_forward(v: fb::ForwardMetadata<'_>) -> ForwardMetadata {
+fn decode_forward(v: fb::ForwardMetadata<'_>, depth: usize) -> ForwardMetadata {
     let messages = v
         .messages()
         .map(|vec| vec.iter().map(decode_forward_ref).collect())
         .unwrap_or_default();
-    ForwardMetadata { messages }
+    // Bundles nested past the cap are dropped rather than failing the whole
+    // message: a hostile sender must not be able to blow the decoder's stack.
+    let items = match v.items() {
+        Some(vec) if depth < FORWARD_BUNDLE_MAX_DEPTH => vec
+            .iter()
+            .map(|item| decode_bundle_item(item, depth + 1))
+            .collect(),
+        _ => Vec::new(),
+    };
+    ForwardMetadata { messages, items }
 }
 
 fn encode_link<'a>(
@@ -888,6 +1255,94 @@ fn decode_link(v: fb::LinkMetadata<'_>) -> LinkMetadata {
     }
 }
 
+fn encode_inline_button<'a>(
+    builder: &mut FlatBufferBuilder<'a>,
+    b: &InlineButton,
+) -> flatbuffers::WIPOffset<fb::InlineButton<'a>> {
+    let text = builder.create_string(&b.text);
+    let mut args = fb::InlineButtonArgs {
+        text: Some(text),
+        ..Default::default()
+    };
+    match &b.action {
+        InlineButtonAction::Callback { data } => {
+            args.action = fb::InlineButtonAction::Callback;
+            args.callback_data = Some(builder.create_string(data));
+        }
+        InlineButtonAction::Url { url } => {
+            args.action = fb::InlineButtonAction::Url;
+            args.url = Some(builder.create_string(url));
+        }
+        InlineButtonAction::SwitchToChat { query, same_chat } => {
+            args.action = fb::InlineButtonAction::SwitchToChat;
+            args.switch_query = Some(builder.create_string(query));
+            args.same_chat = *same_chat;
+        }
+    }
+    fb::InlineButton::create(builder, &args)
+}
+/// Buttons with an unknown (newer) action are dropped: an older client has
+/// no way to act on them.
+fn decode_inline_button(v: fb::InlineButton<'_>) -> Option<InlineButton> {
+    let action = match v.action() {
+        fb::InlineButtonAction::Callback => InlineButtonAction::Callback {
+            data: v.callback_data().unwrap_or("").to_string(),
+        },
+        fb::InlineButtonAction::Url => InlineButtonAction::Url {
+            url: v.url().unwrap_or("").to_string(),
+        },
+        fb::InlineButtonAction::SwitchToChat => InlineButtonAction::SwitchToChat {
+            query: v.switch_query().unwrap_or("").to_string(),
+            same_chat: v.same_chat(),
+        },
+        _ => return None,
+    };
+    Some(InlineButton {
+        text: v.text().unwrap_or("").to_string(),
+        action,
+    })
+}
+
+fn encode_interactive<'a>(
+    builder: &mut FlatBufferBuilder<'a>,
+    m: &InteractiveMetadata,
+) -> flatbuffers::WIPOffset<fb::InteractiveMetadata<'a>> {
+    let rows: Vec<_> = m
+        .rows
+        .iter()
+        .map(|row| {
+            let buttons: Vec<_> = row
+                .iter()
+                .map(|b| encode_inline_button(builder, b))
+                .collect();
+            let buttons = builder.create_vector(&buttons);
+            fb::InlineButtonRow::create(
+                builder,
+                &fb::InlineButtonRowArgs {
+                    buttons: Some(buttons),
+                },
+            )
+        })
+        .collect();
+    let rows = builder.create_vector(&rows);
+    fb::InteractiveMetadata::create(builder, &fb::InteractiveMetadataArgs { rows: Some(rows) })
+}
+fn decode_interactive(v: fb::InteractiveMetadata<'_>) -> InteractiveMetadata {
+    let rows = v
+        .rows()
+        .map(|rows| {
+            rows.iter()
+                .map(|row| {
+                    row.buttons()
+                        .map(|b| b.iter().filter_map(decode_inline_button).collect())
+                        .unwrap_or_default()
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    InteractiveMetadata { rows }
+}
+
 // ------------------------------------------------------------------
 // FlatBuffers codec — message_source + envelope
 // ------------------------------------------------------------------
@@ -915,6 +1370,7 @@ fn decode_source(v: fb::MessageSource<'_>) -> MessageSource {
 
 impl FlatBufferMessage for MessagePayloadEnvelope {
     fn encode_fb_into(&self, builder: &mut FlatBufferBuilder<'_>) -> Result<(), ProtocolError> {
+        self.validate()?;
         let offset = encode_payload_envelope(builder, self);
         builder.finish(offset, None);
         Ok(())
@@ -929,6 +1385,14 @@ impl FlatBufferMessage for MessagePayloadEnvelope {
 pub(crate) fn encode_payload_envelope<'a>(
     builder: &mut FlatBufferBuilder<'a>,
     envelope: &MessagePayloadEnvelope,
+) -> flatbuffers::WIPOffset<fb::MessagePayloadEnvelope<'a>> {
+    encode_payload_envelope_at(builder, envelope, 0)
+}
+
+fn encode_payload_envelope_at<'a>(
+    builder: &mut FlatBufferBuilder<'a>,
+    envelope: &MessagePayloadEnvelope,
+    depth: usize,
 ) -> flatbuffers::WIPOffset<fb::MessagePayloadEnvelope<'a>> {
     let content = builder.create_string(&envelope.content);
 
@@ -965,12 +1429,16 @@ pub(crate) fn encode_payload_envelope<'a>(
         ),
         Some(MessageMetadata::Forward(m)) => (
             fb::MessageMetadata::ForwardMetadata,
-            Some(encode_forward(builder, m).as_union_value()),
+            Some(encode_forward(builder, m, depth).as_union_value()),
         ),
         Some(MessageMetadata::Link(m)) => (
             fb::MessageMetadata::LinkMetadata,
             Some(encode_link(builder, m).as_union_value()),
         ),
+        Some(MessageMetadata::Interactive(m)) => (
+            fb::MessageMetadata::InteractiveMetadata,
+            Some(encode_interactive(builder, m).as_union_value()),
+        ),
     };
 
     let mentioned_user_ids = builder.create_vector(&envelope.mentioned_user_ids);
@@ -992,6 +1460,13 @@ pub(crate) fn encode_payload_envelope<'a>(
 
 pub(crate) fn decode_payload_envelope(
     view: fb::MessagePayloadEnvelope<'_>,
+) -> MessagePayloadEnvelope {
+    decode_payload_envelope_at(view, 0)
+}
+
+fn decode_payload_envelope_at(
+    view: fb::MessagePayloadEnvelope<'_>,
+    depth: usize,
 ) -> MessagePayloadEnvelope {
     let metadata = match view.metadata_type() {
         fb::MessageMetadata::ImageMetadata => view
@@ -1017,10 +1492,13 @@ pub(crate) fn decode_payload_envelope(
             .map(|m| MessageMetadata::Sticker(decode_sticker(m))),
         fb::MessageMetadata::ForwardMetadata => view
             .metadata_as_forward_metadata()
-            .map(|m| MessageMetadata::Forward(decode_forward(m))),
+            .map(|m| MessageMetadata::Forward(decode_forward(m, depth))),
         fb::MessageMetadata::LinkMetadata => view
             .metadata_as_link_metadata()
             .map(|m| MessageMetadata::Link(decode_link(m))),
+        fb::MessageMetadata::InteractiveMetadata => view
+            .metadata_as_interactive_metadata()
+            .map(|m| MessageMetadata::Interactive(decode_interactive(m))),
         _ => None, // NONE (or future unknown) → no metadata
     };
 
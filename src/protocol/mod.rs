@@ -14,6 +14,7 @@ mod content;
 mod disconnect;
 mod entity_sync;
 mod ping;
+mod presence;
 mod publish;
 mod push;
 mod rpc;
@@ -27,6 +28,7 @@ pub use content::*;
 pub use disconnect::*;
 pub use entity_sync::*;
 pub use ping::*;
+pub use presence::*;
 pub use publish::*;
 pub use push::*;
 pub use rpc::*;
@@ -122,6 +124,23 @@ impl MessageSetting {
     }
 }
 
+/// Anchor for the `expire` TTL (seconds) carried on Send / Push.
+///
+/// `expire == 0` means the message never expires and the mode is ignored.
+/// Otherwise the deletion deadline is `anchor + expire`, where the anchor is
+/// the server send timestamp (`AfterSend`) or the first read by anyone other
+/// than the sender (`AfterRead`). See `crate::ephemeral` for the deadline
+/// helper shared by server and clients.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExpireMode {
+    /// TTL starts at send. Legacy senders (`Unspecified` on the wire) land here.
+    #[default]
+    AfterSend,
+    /// TTL starts at the first read by a non-sender.
+    AfterRead,
+}
+
 /// Programming-side wrapper grouping a message body with its type tag.
 /// Not on the wire — `msgtrans.biz_type` is the canonical type carrier.
 #[derive(Debug, Clone)]
@@ -153,6 +172,22 @@ pub(crate) fn encode_setting<'a>(
     )
 }
 
+pub(crate) fn encode_expire_mode(mode: ExpireMode) -> fb::ExpireMode {
+    match mode {
+        ExpireMode::AfterSend => fb::ExpireMode::AfterSend,
+        ExpireMode::AfterRead => fb::ExpireMode::AfterRead,
+    }
+}
+
+/// Unknown future modes fall back to `AfterSend`: deleting too early is the
+/// safe failure for a disappearing message, keeping it forever is not.
+pub(crate) fn decode_expire_mode(mode: fb::ExpireMode) -> ExpireMode {
+    match mode {
+        fb::ExpireMode::AfterRead => ExpireMode::AfterRead,
+        _ => ExpireMode::AfterSend,
+    }
+}
+
 pub(crate) fn decode_setting(view: Option<fb::MessageSetting<'_>>) -> MessageSetting {
     match view {
         Some(s) => MessageSetting {
@@ -0,0 +1,300 @@
+//! Presence and typing push payloads (`presence.fbs`).
+//!
+//! The owned types live in [`crate::presence`]; this module only provides
+//! their FlatBuffers codec, the push topics and the batch form.
+
+use crate::codec::FlatBufferMessage;
+use crate::error::ProtocolError;
+use crate::fb;
+use crate::presence::{
+    PresenceChangedNotification, PresenceSnapshot, TypingActionType, TypingStatusNotification,
+};
+use flatbuffers::{FlatBufferBuilder, WIPOffset};
+
+pub const PRESENCE_SCHEMA_V1: u32 = 1;
+pub const PRESENCE_CHANGED_PUSH_TOPIC_V1: &str = "presence.changed.v1";
+pub const PRESENCE_BATCH_PUSH_TOPIC_V1: &str = "presence.batch.v1";
+pub const TYPING_STATUS_PUSH_TOPIC_V1: &str = "typing.status.v1";
+pub const PRESENCE_BATCH_MAX_ITEMS_V1: usize = 512;
+
+/// Several presence snapshots in one push — group online stats, or the
+/// initial presence of a freshly opened contact list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PresenceBatch {
+    pub schema_version: u32,
+    /// Group the batch describes; `None` when not group-scoped.
+    pub group_id: Option<u64>,
+    pub total_members: u32,
+    pub online_count: u32,
+    pub items: Vec<PresenceSnapshot>,
+}
+
+impl PresenceBatch {
+    pub fn new_v1(
+        group_id: Option<u64>,
+        total_members: u32,
+        items: Vec<PresenceSnapshot>,
+    ) -> Result<Self, ProtocolError> {
+        check_batch_len(items.len())?;
+        let online_count = items.iter().filter(|item| item.is_online).count() as u32;
+        Ok(Self {
+            schema_version: PRESENCE_SCHEMA_V1,
+            group_id,
+            total_members,
+            online_count,
+            items,
+        })
+    }
+}
+
+fn check_batch_len(len: usize) -> Result<(), ProtocolError> {
+    if len > PRESENCE_BATCH_MAX_ITEMS_V1 {
+        return Err(ProtocolError::InvalidValue(format!(
+            "presence batch item count must be <= {PRESENCE_BATCH_MAX_ITEMS_V1}"
+        )));
+    }
+    Ok(())
+}
+
+fn build_snapshot<'a>(
+    builder: &mut FlatBufferBuilder<'a>,
+    snapshot: &PresenceSnapshot,
+) -> WIPOffset<fb::PresenceSnapshot<'a>> {
+    fb::PresenceSnapshot::create(
+        builder,
+        &fb::PresenceSnapshotArgs {
+            user_id: snapshot.user_id,
+            is_online: snapshot.is_online,
+            last_seen_at: snapshot.last_seen_at,
+            device_count: snapshot.device_count,
+            version: snapshot.version,
+        },
+    )
+}
+
+fn read_snapshot(view: fb::PresenceSnapshot<'_>) -> PresenceSnapshot {
+    PresenceSnapshot {
+        user_id: view.user_id(),
+        is_online: view.is_online(),
+        last_seen_at: view.last_seen_at(),
+        device_count: view.device_count(),
+        version: view.version(),
+    }
+}
+
+impl FlatBufferMessage for PresenceSnapshot {
+    fn encode_fb_into(&self, builder: &mut FlatBufferBuilder<'_>) -> Result<(), ProtocolError> {
+        let root = build_snapshot(builder, self);
+        builder.finish(root, None);
+        Ok(())
+    }
+
+    fn decode_fb(bytes: &[u8]) -> Result<Self, ProtocolError> {
+        Ok(read_snapshot(flatbuffers::root::<fb::PresenceSnapshot>(
+            bytes,
+        )?))
+    }
+}
+
+impl FlatBufferMessage for PresenceChangedNotification {
+    fn encode_fb_into(&self, builder: &mut FlatBufferBuilder<'_>) -> Result<(), ProtocolError> {
+        if self.user_id != self.snapshot.user_id || self.version != self.snapshot.version {
+            return Err(ProtocolError::InvalidValue(
+                "presence notification user_id/version must match its snapshot".to_string(),
+            ));
+        }
+        let snapshot = build_snapshot(builder, &self.snapshot);
+        let root = fb::PresenceChangedNotification::create(
+            builder,
+            &fb::PresenceChangedNotificationArgs {
+                user_id: self.user_id,
+                version: self.version,
+                snapshot: Some(snapshot),
+            },
+        );
+        builder.finish(root, None);
+        Ok(())
+    }
+
+    fn decode_fb(bytes: &[u8]) -> Result<Self, ProtocolError> {
+        let view = flatbuffers::root::<fb::PresenceChangedNotification>(bytes)?;
+        let snapshot = read_snapshot(view.snapshot());
+        if view.user_id() != snapshot.user_id || view.version() != snapshot.version {
+            return Err(ProtocolError::InvalidValue(
+                "presence notification user_id/version must match its snapshot".to_string(),
+            ));
+        }
+        Ok(Self {
+            user_id: view.user_id(),
+            version: view.version(),
+            snapshot,
+        })
+    }
+}
+
+impl FlatBufferMessage for PresenceBatch {
+    fn encode_fb_into(&self, builder: &mut FlatBufferBuilder<'_>) -> Result<(), ProtocolError> {
+        check_batch_len(self.items.len())?;
+        let offsets: Vec<_> = self
+            .items
+            .iter()
+            .map(|item| build_snapshot(builder, item))
+            .collect();
+        let items = builder.create_vector(&offsets);
+        let root = fb::PresenceBatch::create(
+            builder,
+            &fb::PresenceBatchArgs {
+                schema_version: self.schema_version,
+                group_id: self.group_id.unwrap_or(0),
+                total_members: self.total_members,
+                online_count: self.online_count,
+                items: Some(items),
+            },
+        );
+        builder.finish(root, None);
+        Ok(())
+    }
+
+    fn decode_fb(bytes: &[u8]) -> Result<Self, ProtocolError> {
+        let view = flatbuffers::root::<fb::PresenceBatch>(bytes)?;
+        let items_view = view.items();
+        check_batch_len(items_view.len())?;
+        Ok(Self {
+            schema_version: view.schema_version(),
+            group_id: Some(view.group_id()).filter(|id| *id != 0),
+            total_members: view.total_members(),
+            online_count: view.online_count(),
+            items: items_view.iter().map(read_snapshot).collect(),
+        })
+    }
+}
+
+impl FlatBufferMessage for TypingStatusNotification {
+    fn encode_fb_into(&self, builder: &mut FlatBufferBuilder<'_>) -> Result<(), ProtocolError> {
+        let username = self
+            .username
+            .as_deref()
+            .map(|value| builder.create_string(value));
+        let action = match self.action_type {
+            TypingActionType::Typing => fb::TypingAction::Typing,
+            TypingActionType::Recording => fb::TypingAction::Recording,
+            TypingActionType::UploadingPhoto => fb::TypingAction::UploadingPhoto,
+            TypingActionType::UploadingVideo => fb::TypingAction::UploadingVideo,
+            TypingActionType::UploadingFile => fb::TypingAction::UploadingFile,
+            TypingActionType::ChoosingSticker => fb::TypingAction::ChoosingSticker,
+        };
+        let root = fb::TypingStatusNotification::create(
+            builder,
+            &fb::TypingStatusNotificationArgs {
+                user_id: self.user_id,
+                username,
+                channel_id: self.channel_id,
+                channel_type: self.channel_type,
+                is_typing: self.is_typing,
+                action,
+                timestamp: self.timestamp,
+            },
+        );
+        builder.finish(root, None);
+        Ok(())
+    }
+
+    fn decode_fb(bytes: &[u8]) -> Result<Self, ProtocolError> {
+        let view = flatbuffers::root::<fb::TypingStatusNotification>(bytes)?;
+        // Unknown (or newer) actions degrade to plain "typing".
+        let action_type = match view.action() {
+            fb::TypingAction::Recording => TypingActionType::Recording,
+            fb::TypingAction::UploadingPhoto => TypingActionType::UploadingPhoto,
+            fb::TypingAction::UploadingVideo => TypingActionType::UploadingVideo,
+            fb::TypingAction::UploadingFile => TypingActionType::UploadingFile,
+            fb::TypingAction::ChoosingSticker => TypingActionType::ChoosingSticker,
+            _ => TypingActionType::Typing,
+        };
+        Ok(Self {
+            user_id: view.user_id(),
+            username: view.username().map(str::to_string),
+            channel_id: view.channel_id(),
+            channel_type: view.channel_type(),
+            is_typing: view.is_typing(),
+            action_type,
+            timestamp: view.timestamp(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(user_id: u64, is_online: bool, version: u64) -> PresenceSnapshot {
+        PresenceSnapshot {
+            user_id,
+            is_online,
+            last_seen_at: 1_780_000_000,
+            device_count: u32::from(is_online),
+            version,
+        }
+    }
+
+    #[test]
+    fn changed_notification_roundtrip_and_consistency() {
+        let notification = PresenceChangedNotification {
+            user_id: 9_007_199_254_740_993,
+            version: 42,
+            snapshot: snapshot(9_007_199_254_740_993, true, 42),
+        };
+        let bytes = notification.encode_fb().expect("encode presence");
+        assert_eq!(
+            PresenceChangedNotification::decode_fb(&bytes).expect("decode presence"),
+            notification
+        );
+
+        let mut mismatched = notification;
+        mismatched.version = 41;
+        assert!(mismatched.encode_fb().is_err());
+    }
+
+    #[test]
+    fn batch_roundtrip_counts_online_members() {
+        let batch = PresenceBatch::new_v1(
+            Some(77),
+            10,
+            vec![snapshot(1, true, 3), snapshot(2, false, 5)],
+        )
+        .expect("valid batch");
+        assert_eq!(batch.online_count, 1);
+        let bytes = batch.encode_fb().expect("encode batch");
+        assert_eq!(
+            PresenceBatch::decode_fb(&bytes).expect("decode batch"),
+            batch
+        );
+
+        let empty = PresenceBatch::new_v1(None, 0, vec![]).expect("empty batch");
+        let decoded = PresenceBatch::decode_fb(&empty.encode_fb().unwrap()).unwrap();
+        assert_eq!(decoded.group_id, None);
+        assert!(PresenceBatch::new_v1(
+            None,
+            0,
+            vec![snapshot(1, true, 1); PRESENCE_BATCH_MAX_ITEMS_V1 + 1]
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn typing_roundtrip() {
+        let typing = TypingStatusNotification {
+            user_id: 5,
+            username: Some("Alice".to_string()),
+            channel_id: 9_007_199_254_740_995,
+            channel_type: 2,
+            is_typing: true,
+            action_type: TypingActionType::ChoosingSticker,
+            timestamp: 1_780_000_000_123,
+        };
+        let bytes = typing.encode_fb().expect("encode typing");
+        assert_eq!(
+            TypingStatusNotification::decode_fb(&bytes).expect("decode typing"),
+            typing
+        );
+    }
+}
@@ -1,6 +1,9 @@
 //! Server-to-client message push (single + batch).
 
-use super::{decode_setting, encode_setting, Message, MessageSetting, MessageType, Packet};
+use super::{
+    decode_expire_mode, decode_setting, encode_expire_mode, encode_setting, ExpireMode, Message,
+    MessageSetting, MessageType, Packet,
+};
 use crate::codec::FlatBufferMessage;
 use crate::error::ProtocolError;
 use crate::fb;
@@ -21,6 +24,7 @@ pub struct PushMessageRequest {
     pub channel_id: u64,
     pub channel_type: u8,
     pub message_type: u32,
+    /// TTL in seconds; 0 = never expires. Anchored by `expire_mode`.
     pub expire: u32,
     pub topic: String,
     pub from_uid: u64,
@@ -28,6 +32,8 @@ pub struct PushMessageRequest {
     /// True when this push notifies a recall of an existing server_message_id.
     /// SDK marks the matching local message as revoked.
     pub deleted: bool,
+    #[serde(default)]
+    pub expire_mode: ExpireMode,
 }
 
 impl PushMessageRequest {
@@ -175,6 +181,7 @@ fn encode_push<'a>(
             from_uid: msg.from_uid,
             payload: Some(payload),
             deleted: msg.deleted,
+            expire_mode: encode_expire_mode(msg.expire_mode),
         },
     )
 }
@@ -201,6 +208,7 @@ fn decode_push(view: fb::PushMessageRequest<'_>) -> PushMessageRequest {
             .map(|v| v.bytes().to_vec())
             .unwrap_or_default(),
         deleted: view.deleted(),
+        expire_mode: decode_expire_mode(view.expire_mode()),
     }
 }
 
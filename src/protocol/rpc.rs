@@ -1,11 +1,16 @@
 //! Generic RPC envelope. `body` and `data` are opaque application bytes —
 //! per-route encoding is decided by the RPC layer.
+//!
+//! Error responses may carry a structured [`ErrorDetail`]; callers turn a
+//! response into `Result<T, RpcError>` with [`RpcResponse::into_result`].
 
 use super::{Message, MessageType, Packet};
 use crate::codec::FlatBufferMessage;
 use crate::error::ProtocolError;
 use crate::fb;
+use crate::{ErrorCode, ErrorDetail, RpcError};
 use flatbuffers::FlatBufferBuilder;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -29,6 +34,10 @@ pub struct RpcResponse {
     pub code: i32,
     pub message: String,
     pub data: Option<Vec<u8>>,
+    /// Structured detail for error responses; `None` on success and from
+    /// servers that predate the field.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<ErrorDetail>,
 }
 
 impl RpcResponse {
@@ -37,6 +46,7 @@ impl RpcResponse {
             code: 0,
             message: "OK".to_string(),
             data: None,
+            detail: None,
         }
     }
 
@@ -49,6 +59,7 @@ impl RpcResponse {
             code: 0,
             message: "OK".to_string(),
             data: Some(data),
+            detail: None,
         }
     }
 
@@ -57,6 +68,7 @@ impl RpcResponse {
             code: 0,
             message: "OK".to_string(),
             data: None,
+            detail: None,
         }
     }
 
@@ -65,6 +77,58 @@ impl RpcResponse {
             code,
             message,
             data: None,
+            detail: None,
+        }
+    }
+
+    /// Error response for a known code, with its canonical message and a
+    /// structured detail.
+    pub fn from_error(code: ErrorCode, detail: ErrorDetail) -> Self {
+        Self {
+            code: code.code() as i32,
+            message: code.message().to_string(),
+            data: None,
+            detail: Some(detail),
+        }
+    }
+
+    pub fn with_detail(mut self, detail: ErrorDetail) -> Self {
+        self.detail = Some(detail);
+        self
+    }
+
+    /// The error half of this response, or `None` on success.
+    pub fn to_error(&self) -> Option<RpcError> {
+        self.is_err().then(|| RpcError {
+            code: self.code,
+            message: self.message.clone(),
+            detail: self.detail.clone(),
+        })
+    }
+
+    /// Decode a JSON response body into `T`, or return the error the server
+    /// sent. A missing body decodes as JSON `null` (so `Option<T>` and `()`
+    /// work); a body that doesn't match `T` becomes `DecodingError`.
+    pub fn into_result<T: DeserializeOwned>(self) -> Result<T, RpcError> {
+        if let Some(err) = self.to_error() {
+            return Err(err);
+        }
+        let parsed = match &self.data {
+            Some(data) => serde_json::from_slice(data),
+            None => serde_json::from_value(serde_json::Value::Null),
+        };
+        parsed.map_err(|e| RpcError {
+            code: ErrorCode::DecodingError.code() as i32,
+            message: format!("rpc response body: {e}"),
+            detail: None,
+        })
+    }
+
+    /// Like [`RpcResponse::into_result`] for routes that return no body.
+    pub fn into_empty_result(self) -> Result<(), RpcError> {
+        match self.to_error() {
+            Some(err) => Err(err),
+            None => Ok(()),
         }
     }
 
@@ -120,10 +184,19 @@ impl FlatBufferMessage for RpcResponse {
             Some(d) => builder.create_vector(d),
             None => builder.create_vector::<u8>(&[]),
         };
+        let detail = match &self.detail {
+            Some(detail) => {
+                let json = serde_json::to_vec(detail)
+                    .map_err(|e| ProtocolError::InvalidValue(format!("rpc error detail: {e}")))?;
+                Some(builder.create_vector(&json))
+            }
+            None => None,
+        };
         let args = fb::RpcResponseArgs {
             code: self.code,
             message: Some(message),
             data: Some(data),
+            detail,
         };
         let offset = fb::RpcResponse::create(builder, &args);
         builder.finish(offset, None);
@@ -139,10 +212,16 @@ impl FlatBufferMessage for RpcResponse {
                 Some(v)
             }
         });
+        // The detail is advisory: a malformed one must not hide the code.
+        let detail = view
+            .detail()
+            .filter(|v| !v.is_empty())
+            .and_then(|v| serde_json::from_slice(v.bytes()).ok());
         Ok(Self {
             code: view.code(),
             message: view.message().unwrap_or("").to_string(),
             data,
+            detail,
         })
     }
 }
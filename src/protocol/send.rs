@@ -1,8 +1,12 @@
 //! Client-to-server send-message messages.
 
-use super::{decode_setting, encode_setting, Message, MessageSetting, MessageType, Packet};
+use super::{
+    decode_expire_mode, decode_setting, encode_expire_mode, encode_setting, ExpireMode, Message,
+    MessageSetting, MessageType, Packet,
+};
 use crate::codec::FlatBufferMessage;
 use crate::error::ProtocolError;
+use crate::error_code::ErrorCode;
 use crate::fb;
 use flatbuffers::FlatBufferBuilder;
 use serde::{Deserialize, Serialize};
@@ -16,10 +20,13 @@ pub struct SendMessageRequest {
     pub channel_id: u64,
     /// Application content type (NOT the wire `MessageType`).
     pub message_type: u32,
+    /// TTL in seconds; 0 = never expires. Anchored by `expire_mode`.
     pub expire: u32,
     pub from_uid: u64,
     pub topic: String,
     pub payload: Vec<u8>,
+    #[serde(default)]
+    pub expire_mode: ExpireMode,
 }
 
 impl SendMessageRequest {
@@ -52,6 +59,15 @@ impl SendMessageResponse {
         Self::default()
     }
 
+    /// `reason_code` as an [`ErrorCode`]: `None` on success (0), and codes
+    /// this build doesn't know map to `MessageSendFailed`.
+    pub fn error_code(&self) -> Option<ErrorCode> {
+        match self.reason_code {
+            0 => None,
+            code => Some(ErrorCode::from_code(code).unwrap_or(ErrorCode::MessageSendFailed)),
+        }
+    }
+
     pub fn create_packet(self) -> Packet<Self> {
         Packet::new(MessageType::SendMessageResponse, self)
     }
@@ -86,6 +102,7 @@ impl FlatBufferMessage for SendMessageRequest {
             from_uid: self.from_uid,
             topic: Some(topic),
             payload: Some(payload),
+            expire_mode: encode_expire_mode(self.expire_mode),
         };
         let offset = fb::SendMessageRequest::create(builder, &args);
         builder.finish(offset, None);
@@ -108,6 +125,7 @@ impl FlatBufferMessage for SendMessageRequest {
                 .payload()
                 .map(|v| v.bytes().to_vec())
                 .unwrap_or_default(),
+            expire_mode: decode_expire_mode(view.expire_mode()),
         })
     }
 }
@@ -22,6 +22,7 @@ pub enum CanonicalTimelineEvent {
     NewMessage(NewMessageEvent),
     Revoke(RevokeEvent),
     ReactionChange(ReactionChangeEvent),
+    Expire(ExpireEvent),
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -51,6 +52,14 @@ pub struct ReactionChangeEvent {
     pub operation: ReactionOperation,
 }
 
+/// Deletion deadline of an expiring message. Every replica removes the
+/// target at `expire_at` (ms); see `crate::ephemeral` for how it is derived.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpireEvent {
+    pub target_server_message_id: u64,
+    pub expire_at: i64,
+}
+
 impl CanonicalTimelineEvent {
     /// Produce the additive legacy `message_type + content` projection used by
     /// old clients. IDs are strings so JSON consumers cannot lose u64 bits.
@@ -77,6 +86,13 @@ impl CanonicalTimelineEvent {
             emoji: &'a str,
             deleted: bool,
         }
+        #[derive(Serialize)]
+        struct LegacyExpire {
+            message_id: String,
+            channel_id: String,
+            channel_type: u8,
+            expire_at: i64,
+        }
 
         let (message_type, value) = match self {
             Self::NewMessage(event) => (
@@ -105,6 +121,15 @@ impl CanonicalTimelineEvent {
                     deleted: matches!(event.operation, ReactionOperation::Remove),
                 }),
             ),
+            Self::Expire(event) => (
+                "message.expire".to_string(),
+                serde_json::to_value(LegacyExpire {
+                    message_id: event.target_server_message_id.to_string(),
+                    channel_id: channel_id.to_string(),
+                    channel_type,
+                    expire_at: event.expire_at,
+                }),
+            ),
         };
         value
             .map(|value| (message_type, value))
@@ -167,6 +192,26 @@ impl CanonicalTimelineEvent {
                     operation,
                 })))
             }
+            "message.expire" => {
+                let target = json_u64(content, "message_id").ok_or_else(|| {
+                    ProtocolError::InvalidValue(
+                        "expire legacy payload is missing message_id".to_string(),
+                    )
+                })?;
+                // A guessed expiry would delete the message on the spot.
+                let expire_at = content
+                    .get("expire_at")
+                    .and_then(serde_json::Value::as_i64)
+                    .ok_or_else(|| {
+                        ProtocolError::InvalidValue(
+                            "expire legacy payload is missing expire_at".to_string(),
+                        )
+                    })?;
+                Ok(Some(Self::Expire(ExpireEvent {
+                    target_server_message_id: target,
+                    expire_at,
+                })))
+            }
             value => {
                 let Some(content_type) = ContentMessageType::from_str(value) else {
                     return Ok(None);
@@ -256,6 +301,7 @@ impl FlatBufferMessage for CanonicalTimelineEvent {
     fn encode_fb_into(&self, builder: &mut FlatBufferBuilder<'_>) -> Result<(), ProtocolError> {
         let (payload_type, payload) = match self {
             Self::NewMessage(event) => {
+                event.payload.validate()?;
                 let payload = encode_payload_envelope(builder, &event.payload);
                 let offset = fb::NewMessageEvent::create(
                     builder,
@@ -303,6 +349,19 @@ impl FlatBufferMessage for CanonicalTimelineEvent {
                     offset.as_union_value(),
                 )
             }
+            Self::Expire(event) => {
+                let offset = fb::ExpireEvent::create(
+                    builder,
+                    &fb::ExpireEventArgs {
+                        target_server_message_id: event.target_server_message_id,
+                        expire_at: event.expire_at,
+                    },
+                );
+                (
+                    fb::TimelineEventPayload::ExpireEvent,
+                    offset.as_union_value(),
+                )
+            }
         };
         let offset = fb::CanonicalTimelineEvent::create(
             builder,
@@ -367,6 +426,15 @@ impl FlatBufferMessage for CanonicalTimelineEvent {
                     operation,
                 }))
             }
+            fb::TimelineEventPayload::ExpireEvent => {
+                let event = view
+                    .payload_as_expire_event()
+                    .ok_or(ProtocolError::MissingField("timeline.expire"))?;
+                Ok(Self::Expire(ExpireEvent {
+                    target_server_message_id: event.target_server_message_id(),
+                    expire_at: event.expire_at(),
+                }))
+            }
             _ => Err(ProtocolError::InvalidValue(
                 "unknown canonical timeline event payload".to_string(),
             )),
@@ -399,6 +467,10 @@ mod tests {
                 emoji: "thumbs-up".to_string(),
                 operation: ReactionOperation::Remove,
             }),
+            CanonicalTimelineEvent::Expire(ExpireEvent {
+                target_server_message_id: 9_007_199_254_740_997,
+                expire_at: 1_700_000_030_000,
+            }),
         ];
         for event in events {
             let bytes = event.encode_fb().expect("encode canonical event");
@@ -487,4 +559,31 @@ mod tests {
         assert_eq!(value["uid"], "9007199254740995");
         assert_eq!(value["channel_id"], "9007199254740997");
     }
+
+    #[test]
+    fn expire_event_survives_legacy_projection() {
+        let event = CanonicalTimelineEvent::Expire(ExpireEvent {
+            target_server_message_id: 9_007_199_254_740_993,
+            expire_at: 1_700_000_030_000,
+        });
+        let (message_type, value) = event
+            .to_legacy_commit(9_007_199_254_740_997, 1)
+            .expect("legacy projection");
+        assert_eq!(message_type, "message.expire");
+        assert_eq!(value["message_id"], "9007199254740993");
+        let back = CanonicalTimelineEvent::from_legacy(&message_type, &value, 1, 2, 3)
+            .expect("map legacy")
+            .expect("known event");
+        assert_eq!(back, event);
+
+        for broken in [
+            serde_json::json!({"message_id": "1"}),
+            serde_json::json!({"message_id": "1", "expire_at": "soon"}),
+        ] {
+            assert!(
+                CanonicalTimelineEvent::from_legacy("message.expire", &broken, 1, 2, 3).is_err(),
+                "{broken}"
+            );
+        }
+    }
 }
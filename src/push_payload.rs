@@ -0,0 +1,539 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! 离线推送负载 —— 把一条 `PushMessageRequest` 变成各厂商通道的 JSON。
+//!
+//! 设备通过 `DeviceInfo.push_channel` / `DevicePushUpdateRequest.vendor` 声明
+//! 通道，[`PushVendor::parse`] 把这些字符串收敛成 typed 枚举。[`build`] 按
+//! 厂商产出请求体：
+//!
+//! - **长度限制**：厂商对标题 / 正文字数和整个负载字节数都有上限（取各家
+//!   公开文档的保守值，见 [`VendorLimits`]）。超限时先按字数截断，再逐步
+//!   缩短正文（必要时标题）直到负载装得下，截断处补 `…`；
+//! - **折叠 / 分组**：同一频道的通知共用一个 collapse id（后到的替换先到的）
+//!   和 thread id（通知中心按会话分组），均由频道派生；
+//! - **角标**：支持角标的通道写入 `badge`，其余忽略；
+//! - **隐藏内容**：[`PushPayloadOptions::hide_content`] 打开时，标题 / 正文替换为
+//!   占位文案，负载里不出现任何消息内容，只保留用于跳转的 ID。
+//!
+//! 所有通道的自定义数据都带 `channel_id` / `channel_type` / `server_message_id`，
+//! u64 一律编码为字符串。
+
+use serde_json::{json, Value};
+
+use crate::{FlatBufferMessage, MessagePayloadEnvelope, ProtocolError, PushMessageRequest};
+
+/// 推送通道。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PushVendor {
+    Apns,
+    /// APNs VoIP（PushKit），仅用于来电
+    ApnsVoip,
+    Fcm,
+    /// 华为 Push Kit
+    Hms,
+    Xiaomi,
+    Oppo,
+    Vivo,
+    Honor,
+}
+
+/// 厂商限制。`None` = 该项只受整体字节上限约束。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VendorLimits {
+    /// 整个请求体的字节上限
+    pub max_payload_bytes: usize,
+    /// 标题字数上限（字符）
+    pub max_title_chars: Option<usize>,
+    /// 正文字数上限（字符）
+    pub max_body_chars: Option<usize>,
+    /// 通道是否支持角标
+    pub supports_badge: bool,
+}
+
+impl PushVendor {
+    /// 识别设备上报的通道字符串（不区分大小写）。未知通道返回 `None`。
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "apns" | "ios" => Some(Self::Apns),
+            "apns_voip" | "apns-voip" | "voip" => Some(Self::ApnsVoip),
+            "fcm" | "gcm" => Some(Self::Fcm),
+            "hms" | "huawei" => Some(Self::Hms),
+            "xiaomi" | "mi" | "mipush" => Some(Self::Xiaomi),
+            "oppo" => Some(Self::Oppo),
+            "vivo" => Some(Self::Vivo),
+            "honor" => Some(Self::Honor),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Apns => "apns",
+            Self::ApnsVoip => "apns_voip",
+            Self::Fcm => "fcm",
+            Self::Hms => "hms",
+            Self::Xiaomi => "xiaomi",
+            Self::Oppo => "oppo",
+            Self::Vivo => "vivo",
+            Self::Honor => "honor",
+        }
+    }
+
+    pub fn limits(self) -> VendorLimits {
+        let limits =
+            |max_payload_bytes, max_title_chars, max_body_chars, supports_badge| VendorLimits {
+                max_payload_bytes,
+                max_title_chars,
+                max_body_chars,
+                supports_badge,
+            };
+        match self {
+            Self::Apns => limits(4096, None, None, true),
+            Self::ApnsVoip => limits(5120, None, None, false),
+            Self::Fcm => limits(4096, None, None, true),
+            Self::Hms => limits(4096, None, None, true),
+            Self::Xiaomi => limits(4096, Some(50), Some(128), false),
+            Self::Oppo => limits(4096, Some(50), Some(200), false),
+            Self::Vivo => limits(4096, Some(40), Some(100), false),
+            Self::Honor => limits(4096, None, None, true),
+        }
+    }
+}
+
+/// 一条待推送的通知内容（渲染前）。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PushContent {
+    /// 通知标题，通常是发送者或群名
+    pub title: String,
+    /// 通知正文，通常是消息预览
+    pub body: String,
+    pub channel_id: u64,
+    pub channel_type: u8,
+    pub server_message_id: u64,
+    /// 角标数（一般为接收者的总未读数）；`None` = 不改角标
+    pub badge: Option<u32>,
+}
+
+impl PushContent {
+    /// 从推送消息构造，正文取 payload envelope 的 `content`。
+    pub fn from_push(
+        push: &PushMessageRequest,
+        title: impl Into<String>,
+    ) -> Result<Self, ProtocolError> {
+        let envelope = MessagePayloadEnvelope::decode_fb(&push.payload)?;
+        Ok(Self {
+            title: title.into(),
+            body: envelope.content,
+            channel_id: push.channel_id,
+            channel_type: push.channel_type,
+            server_message_id: push.server_message_id,
+            badge: None,
+        })
+    }
+
+    /// 同频道通知共用的折叠 ID（APNs `apns-collapse-id` / FCM `collapse_key` 等）。
+    pub fn collapse_id(&self) -> String {
+        format!("ch-{}-{}", self.channel_type, self.channel_id)
+    }
+
+    /// 通知中心按会话分组用的 thread ID。
+    pub fn thread_id(&self) -> String {
+        format!("{}:{}", self.channel_type, self.channel_id)
+    }
+}
+
+/// 渲染选项。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PushPayloadOptions {
+    /// 隐私模式：不在通知里展示消息内容
+    pub hide_content: bool,
+    /// 隐私模式下的标题
+    pub hidden_title: String,
+    /// 隐私模式下的正文
+    pub hidden_body: String,
+}
+
+impl Default for PushPayloadOptions {
+    fn default() -> Self {
+        Self {
+            hide_content: false,
+            hidden_title: "PrivChat".to_string(),
+            hidden_body: "You have a new message".to_string(),
+        }
+    }
+}
+
+/// 某个厂商通道的推送请求。
+#[derive(Debug, Clone, PartialEq)]
+pub struct VendorPushPayload {
+    pub vendor: PushVendor,
+    /// 折叠 ID；APNs 放 `apns-collapse-id` 请求头，其余通道已写进 `body`
+    pub collapse_id: String,
+    /// APNs `apns-push-type` 请求头；非 APNs 为 `None`
+    pub apns_push_type: Option<&'static str>,
+    /// 厂商请求体
+    pub body: Value,
+}
+
+impl VendorPushPayload {
+    pub fn to_json_bytes(&self) -> Vec<u8> {
+        // Value 序列化不会失败（键都是字符串）。
+        serde_json::to_vec(&self.body).unwrap_or_default()
+    }
+}
+
+const ELLIPSIS: char = '…';
+
+/// 按字符数截断，截断时末尾补 `…`（计入上限）。
+fn truncate_chars(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let mut out: String = text.chars().take(max_chars.saturating_sub(1)).collect();
+    if max_chars > 0 {
+        out.push(ELLIPSIS);
+    }
+    out
+}
+
+/// 去掉末尾 `drop_bytes` 字节（向前对齐到字符边界）后补 `…`；不够删时返回空串。
+fn shrink_bytes(text: &str, drop_bytes: usize) -> String {
+    let stem = text.strip_suffix(ELLIPSIS).unwrap_or(text);
+    let keep = stem.len().saturating_sub(drop_bytes + ELLIPSIS.len_utf8());
+    if keep == 0 {
+        return String::new();
+    }
+    let mut end = keep;
+    while !stem.is_char_boundary(end) {
+        end -= 1;
+    }
+    let mut out = stem[..end].to_string();
+    out.push(ELLIPSIS);
+    out
+}
+
+/// 为 `token` 指向的设备渲染厂商推送请求。
+///
+/// 负载无论如何截断都装不下（例如自定义数据本身就超限）时返回
+/// `ProtocolError::InvalidValue`。
+pub fn build(
+    vendor: PushVendor,
+    token: &str,
+    content: &PushContent,
+    options: &PushPayloadOptions,
+) -> Result<VendorPushPayload, ProtocolError> {
+    let limits = vendor.limits();
+    let (title, body) = if options.hide_content {
+        (&options.hidden_title, &options.hidden_body)
+    } else {
+        (&content.title, &content.body)
+    };
+    let mut title = match limits.max_title_chars {
+        Some(max) => truncate_chars(title, max),
+        None => title.clone(),
+    };
+    let mut body = match limits.max_body_chars {
+        Some(max) => truncate_chars(body, max),
+        None => body.clone(),
+    };
+    let badge = content.badge.filter(|_| limits.supports_badge);
+
+    loop {
+        let rendered = render(vendor, token, content, &title, &body, badge);
+        let size = serde_json::to_vec(&rendered).map(|v| v.len()).unwrap_or(0);
+        if size <= limits.max_payload_bytes {
+            return Ok(VendorPushPayload {
+                vendor,
+                collapse_id: content.collapse_id(),
+                apns_push_type: match vendor {
+                    PushVendor::Apns => Some("alert"),
+                    PushVendor::ApnsVoip => Some("voip"),
+                    _ => None,
+                },
+                body: rendered,
+            });
+        }
+        let overflow = size - limits.max_payload_bytes;
+        // JSON 转义会让一个字符占多个字节，按溢出量删仍可能不够，循环直到装下。
+        if !body.is_empty() {
+            body = shrink_bytes(&body, overflow);
+        } else if !title.is_empty() {
+            title = shrink_bytes(&title, overflow);
+        } else {
+            return Err(ProtocolError::InvalidValue(format!(
+                "{} push payload exceeds {} bytes even without title and body",
+                vendor.as_str(),
+                limits.max_payload_bytes
+            )));
+        }
+    }
+}
+
+fn render(
+    vendor: PushVendor,
+    token: &str,
+    content: &PushContent,
+    title: &str,
+    body: &str,
+    badge: Option<u32>,
+) -> Value {
+    let data = json!({
+        "channel_id": content.channel_id.to_string(),
+        "channel_type": content.channel_type.to_string(),
+        "server_message_id": content.server_message_id.to_string(),
+    });
+    let collapse_id = content.collapse_id();
+    let thread_id = content.thread_id();
+    match vendor {
+        PushVendor::Apns => {
+            let mut aps = json!({
+                "alert": { "title": title, "body": body },
+                "sound": "default",
+                "thread-id": thread_id,
+                "mutable-content": 1,
+            });
+            if let Some(badge) = badge {
+                aps["badge"] = json!(badge);
+            }
+            let mut payload = data;
+            payload["aps"] = aps;
+            payload
+        }
+        PushVendor::ApnsVoip => {
+            let mut payload = data;
+            payload["title"] = json!(title);
+            payload["body"] = json!(body);
+            payload
+        }
+        PushVendor::Fcm => {
+            let mut notification = json!({ "tag": thread_id });
+            if let Some(badge) = badge {
+                notification["notification_count"] = json!(badge);
+            }
+            json!({
+                "message": {
+                    "token": token,
+                    "notification": { "title": title, "body": body },
+                    "android": {
+                        "collapse_key": collapse_id,
+                        "notification": notification,
+                    },
+                    "data": data,
+                }
+            })
+        }
+        PushVendor::Hms | PushVendor::Honor => {
+            let mut notification = json!({
+                "title": title,
+                "body": body,
+                "tag": thread_id,
+                "click_action": { "type": 3 },
+            });
+            if let Some(badge) = badge {
+                notification["badge"] = json!({ "set_num": badge });
+            }
+            json!({
+                "message": {
+                    "token": [token],
+                    "data": data.to_string(),
+                    "android": { "notification": notification },
+                }
+            })
+        }
+        PushVendor::Xiaomi => json!({
+            "registration_id": token,
+            "title": title,
+            "description": body,
+            "payload": data.to_string(),
+            "pass_through": 0,
+            "notify_type": -1,
+            // 同一 notify_id 的通知相互覆盖：按频道折叠
+            "notify_id": content.channel_id % i32::MAX as u64,
+        }),
+        PushVendor::Oppo => json!({
+            "target_type": 2,
+            "target_value": token,
+            "notification": {
+                "title": title,
+                "content": body,
+                "click_action_type": 0,
+                "action_parameters": data.to_string(),
+            },
+        }),
+        PushVendor::Vivo => json!({
+            "regId": token,
+            "notifyType": 4,
+            "title": title,
+            "content": body,
+            "skipType": 1,
+            "classification": 1,
+            "requestId": content.server_message_id.to_string(),
+            "clientCustomMap": data,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: [PushVendor; 8] = [
+        PushVendor::Apns,
+        PushVendor::ApnsVoip,
+        PushVendor::Fcm,
+        PushVendor::Hms,
+        PushVendor::Xiaomi,
+        PushVendor::Oppo,
+        PushVendor::Vivo,
+        PushVendor::Honor,
+    ];
+
+    fn content(body: &str) -> PushContent {
+        PushContent {
+            title: "Alice".to_string(),
+            body: body.to_string(),
+            channel_id: 608_993_815_990_284_288,
+            channel_type: 1,
+            server_message_id: 608_993_815_990_284_289,
+            badge: Some(5),
+        }
+    }
+
+    #[test]
+    fn vendor_strings_round_trip() {
+        for vendor in ALL {
+            assert_eq!(PushVendor::parse(vendor.as_str()), Some(vendor));
+        }
+        assert_eq!(PushVendor::parse(" HUAWEI "), Some(PushVendor::Hms));
+        assert_eq!(PushVendor::parse("lenovo"), None);
+    }
+
+    #[test]
+    fn apns_shape_has_thread_badge_and_string_ids() {
+        let payload = build(
+            PushVendor::Apns,
+            "tok",
+            &content("hi"),
+            &PushPayloadOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(payload.apns_push_type, Some("alert"));
+        assert_eq!(payload.collapse_id, "ch-1-608993815990284288");
+        let aps = &payload.body["aps"];
+        assert_eq!(aps["alert"]["body"], "hi");
+        assert_eq!(aps["badge"], 5);
+        assert_eq!(aps["thread-id"], "1:608993815990284288");
+        assert_eq!(payload.body["server_message_id"], "608993815990284289");
+    }
+
+    #[test]
+    fn badge_is_dropped_where_unsupported() {
+        let payload = build(
+            PushVendor::Xiaomi,
+            "tok",
+            &content("hi"),
+            &PushPayloadOptions::default(),
+        )
+        .unwrap();
+        assert!(!payload.to_json_bytes().windows(5).any(|w| w == b"badge"));
+        let fcm = build(
+            PushVendor::Fcm,
+            "tok",
+            &content("hi"),
+            &PushPayloadOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            fcm.body["message"]["android"]["notification"]["notification_count"],
+            5
+        );
+        assert_eq!(
+            fcm.body["message"]["android"]["collapse_key"],
+            fcm.collapse_id
+        );
+    }
+
+    #[test]
+    fn every_vendor_fits_its_limits() {
+        let long = "消息".repeat(5000);
+        for vendor in ALL {
+            let limits = vendor.limits();
+            let payload = build(
+                vendor,
+                "tok",
+                &content(&long),
+                &PushPayloadOptions::default(),
+            )
+            .unwrap();
+            assert!(
+                payload.to_json_bytes().len() <= limits.max_payload_bytes,
+                "{vendor:?}"
+            );
+            let text = payload.to_json_bytes();
+            let text = String::from_utf8(text).unwrap();
+            assert!(text.contains('…'), "{vendor:?} should mark truncation");
+        }
+    }
+
+    #[test]
+    fn vendor_char_limits_apply_before_byte_limits() {
+        let payload = build(
+            PushVendor::Vivo,
+            "tok",
+            &content(&"a".repeat(150)),
+            &PushPayloadOptions::default(),
+        )
+        .unwrap();
+        let body = payload.body["content"].as_str().unwrap();
+        assert_eq!(body.chars().count(), 100);
+        assert!(body.ends_with('…'));
+    }
+
+    #[test]
+    fn hide_content_leaks_nothing() {
+        let options = PushPayloadOptions {
+            hide_content: true,
+            ..Default::default()
+        };
+        for vendor in ALL {
+            let payload = build(vendor, "tok", &content("secret plans"), &options).unwrap();
+            let text = String::from_utf8(payload.to_json_bytes()).unwrap();
+            assert!(!text.contains("secret"), "{vendor:?}: {text}");
+            assert!(!text.contains("Alice"), "{vendor:?}: {text}");
+            assert!(text.contains("608993815990284289"), "{vendor:?} keeps ids");
+        }
+    }
+
+    #[test]
+    fn from_push_reads_envelope_content() {
+        let envelope = MessagePayloadEnvelope {
+            content: "hello".to_string(),
+            ..Default::default()
+        };
+        let push = PushMessageRequest {
+            channel_id: 7,
+            channel_type: 2,
+            server_message_id: 9,
+            payload: crate::encode_message(&envelope).unwrap(),
+            ..Default::default()
+        };
+        let content = PushContent::from_push(&push, "Team").unwrap();
+        assert_eq!(content.body, "hello");
+        assert_eq!(content.thread_id(), "2:7");
+    }
+}
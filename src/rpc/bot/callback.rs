@@ -0,0 +1,109 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/// Bot 内联键盘回调 RPC
+///
+/// 一次点击的完整链路：
+/// 1. 客户端调用 `bot/callback/query`，携带被点击消息与按钮 callback data；
+/// 2. server 校验 data 确实在该消息的 `InteractiveMetadata` 上，以
+///    `bot.callback_query` 推送 [`CallbackQueryPayload`] 给 Bot；
+/// 3. Bot 调用 `bot/callback/answer` 应答，server 把应答作为第 1 步的响应返回。
+///
+/// [`CallbackQueryPayload`]: crate::inbox_event::payloads::CallbackQueryPayload
+use serde::{Deserialize, Serialize};
+
+/// 点击回调按钮请求
+///
+/// RPC路由: `bot/callback/query`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BotCallbackQueryRequest {
+    /// 点击者ID（服务器端填充，客户端不可设置）
+    #[serde(skip_deserializing, default)]
+    pub user_id: u64,
+    /// 频道ID
+    pub channel_id: u64,
+    /// 频道类型
+    pub channel_type: u8,
+    /// 携带键盘的消息ID
+    pub server_message_id: u64,
+    /// 按钮的 callback data
+    pub data: String,
+}
+
+/// 点击回调按钮响应（即 Bot 的应答）
+///
+/// RPC路由: `bot/callback/query`
+///
+/// Bot 超时未应答时 server 返回全空的应答，客户端只需结束 loading 态。
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BotCallbackQueryResponse {
+    /// 提示文本
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    /// true = 弹窗，false = toast
+    #[serde(default)]
+    pub show_alert: bool,
+    /// 需要客户端打开的链接
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+/// Bot 应答回调请求
+///
+/// RPC路由: `bot/callback/answer`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BotCallbackAnswerRequest {
+    /// Bot 用户ID（服务器端填充，客户端不可设置）
+    #[serde(skip_deserializing, default)]
+    pub bot_user_id: u64,
+    /// `bot.callback_query` 推送里的 query_id
+    pub query_id: u64,
+    /// 返回给点击者的应答
+    #[serde(flatten)]
+    pub answer: BotCallbackQueryResponse,
+}
+
+/// Bot 应答回调响应
+///
+/// RPC路由: `bot/callback/answer`
+/// 简单操作，返回 true（成功/失败由协议层 code 处理）
+pub type BotCallbackAnswerResponse = bool;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::routes;
+
+    #[test]
+    fn route_constants_are_stable() {
+        assert_eq!(routes::bot::CALLBACK_QUERY, "bot/callback/query");
+        assert_eq!(routes::bot::CALLBACK_ANSWER, "bot/callback/answer");
+    }
+
+    #[test]
+    fn answer_request_is_flat_and_ignores_client_bot_id() {
+        let req: BotCallbackAnswerRequest = serde_json::from_str(
+            r#"{"bot_user_id":1,"query_id":42,"text":"Saved","show_alert":true}"#,
+        )
+        .expect("decode");
+        assert_eq!(req.bot_user_id, 0);
+        assert_eq!(req.query_id, 42);
+        assert_eq!(req.answer.text.as_deref(), Some("Saved"));
+        assert!(req.answer.show_alert);
+        assert_eq!(req.answer.url, None);
+    }
+}
@@ -0,0 +1,393 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/// Bot 命令菜单 RPC 与斜杠命令解析
+///
+/// Bot 通过 `bot/commands/set` 登记命令菜单，客户端用 `bot/commands/get`
+/// 拉取后在输入框展示补全。消息正文里的 `/command@botname args` 由
+/// [`parse_bot_command`] 统一识别，server 与各端 SDK 对「这是不是一次命令
+/// 调用」的判定完全一致。
+use crate::{ProtocolError, TextEntity, TextEntityKind};
+use serde::{Deserialize, Serialize};
+
+/// 命令名最大长度（字节，`[a-z0-9_]`）
+pub const BOT_COMMAND_MAX_LEN: usize = 32;
+/// 命令描述最大长度（字符）
+pub const BOT_COMMAND_DESCRIPTION_MAX_LEN: usize = 256;
+/// 单个 Bot 最多登记的命令数
+pub const BOT_COMMANDS_MAX: usize = 100;
+
+/// 命令生效范围（按频道类型）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BotCommandScope {
+    /// 所有频道
+    #[default]
+    All,
+    /// 仅私聊（channel_type = 1）
+    Direct,
+    /// 仅群聊（channel_type = 2）
+    Group,
+}
+
+impl BotCommandScope {
+    /// 该范围是否覆盖给定频道类型
+    pub fn covers(self, channel_type: u8) -> bool {
+        match self {
+            BotCommandScope::All => true,
+            BotCommandScope::Direct => channel_type == 1,
+            BotCommandScope::Group => channel_type == 2,
+        }
+    }
+
+    /// 两个范围是否有共同覆盖的频道类型（`All` 与任何范围都重叠）
+    pub fn overlaps(self, other: BotCommandScope) -> bool {
+        self == BotCommandScope::All || other == BotCommandScope::All || self == other
+    }
+}
+
+/// 一条菜单命令
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BotCommand {
+    /// 命令名，不含前导 `/`，1~32 个 `[a-z0-9_]`
+    pub command: String,
+    /// 菜单里展示的说明
+    pub description: String,
+    /// 生效范围，缺省为所有频道
+    #[serde(default)]
+    pub scope: BotCommandScope,
+}
+
+impl BotCommand {
+    pub fn validate(&self) -> Result<(), ProtocolError> {
+        let valid_name = !self.command.is_empty()
+            && self.command.len() <= BOT_COMMAND_MAX_LEN
+            && self
+                .command
+                .bytes()
+                .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'_');
+        if !valid_name {
+            return Err(ProtocolError::InvalidValue(format!(
+                "bot command name {:?}",
+                self.command
+            )));
+        }
+        let description_len = self.description.chars().count();
+        if description_len == 0 || description_len > BOT_COMMAND_DESCRIPTION_MAX_LEN {
+            return Err(ProtocolError::InvalidValue(format!(
+                "bot command /{} description is {description_len} chars",
+                self.command
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// 设置 Bot 命令菜单请求（整表替换）
+///
+/// RPC路由: `bot/commands/set`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BotCommandsSetRequest {
+    /// Bot 用户ID（服务器端填充，客户端不可设置）
+    #[serde(skip_deserializing, default)]
+    pub bot_user_id: u64,
+    /// 完整命令列表；空列表 = 清空菜单
+    pub commands: Vec<BotCommand>,
+}
+
+impl BotCommandsSetRequest {
+    /// 校验每条命令，并拒绝范围重叠的重名命令（如 `All` 与 `Group` 下各有一条
+    /// `/ban`，群里无法确定该用哪条）
+    pub fn validate(&self) -> Result<(), ProtocolError> {
+        if self.commands.len() > BOT_COMMANDS_MAX {
+            return Err(ProtocolError::InvalidValue(format!(
+                "{} bot commands (max {BOT_COMMANDS_MAX})",
+                self.commands.len()
+            )));
+        }
+        for (i, command) in self.commands.iter().enumerate() {
+            command.validate()?;
+            let duplicate = self.commands[..i]
+                .iter()
+                .any(|c| c.command == command.command && c.scope.overlaps(command.scope));
+            if duplicate {
+                return Err(ProtocolError::InvalidValue(format!(
+                    "duplicate bot command /{}",
+                    command.command
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 设置 Bot 命令菜单响应
+///
+/// RPC路由: `bot/commands/set`
+/// 简单操作，返回 true（成功/失败由协议层 code 处理）
+pub type BotCommandsSetResponse = bool;
+
+/// 获取 Bot 命令菜单请求
+///
+/// RPC路由: `bot/commands/get`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BotCommandsGetRequest {
+    pub bot_user_id: u64,
+    /// 只返回覆盖该频道类型的命令；缺省返回全部
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel_type: Option<u8>,
+}
+
+/// 获取 Bot 命令菜单响应
+///
+/// RPC路由: `bot/commands/get`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BotCommandsGetResponse {
+    pub commands: Vec<BotCommand>,
+}
+
+/// 正文中识别出的一次命令调用，字段均借用自原文
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BotCommandInvocation<'a> {
+    /// 命令名，不含 `/`
+    pub command: &'a str,
+    /// `@botname` 里的 Bot 用户名；未指定时为 `None`
+    pub bot_username: Option<&'a str>,
+    /// 命令后的参数（已去掉前导空白）
+    pub args: &'a str,
+}
+
+impl BotCommandInvocation<'_> {
+    /// 该调用是否发给 `bot_username`。未带 `@botname` 的命令发给频道内所有
+    /// Bot；用户名比较不区分大小写。
+    pub fn is_addressed_to(&self, bot_username: &str) -> bool {
+        self.bot_username
+            .is_none_or(|name| name.eq_ignore_ascii_case(bot_username))
+    }
+}
+
+/// 识别 `content` 开头的 `/command[@botname] [args]`。
+///
+/// 规则（server 与客户端共用）：
+/// - 命令必须位于正文第 0 个字符，不允许前导空白；
+/// - 若 `entities` 里有覆盖 offset 0 的 `Code` / `Pre`，正文是字面文本，不是命令；
+/// - 若有 offset 0 的 `BotCommand` 实体，以实体长度为命令 token 的边界，
+///   token 内容仍须合法；
+/// - 否则按词法识别：命令名 1~32 个 `[a-z0-9_]`（与 [`BotCommand::validate`]
+///   一致，大写不是命令），`@` 后的用户名为 `[A-Za-z0-9_]+`，token 之后必须
+///   是正文结尾或空白。
+///
+/// 命令 token 只含 ASCII，UTF-16 偏移与字节偏移在 token 范围内一致。
+pub fn parse_bot_command<'a>(
+    content: &'a str,
+    entities: &[TextEntity],
+) -> Option<BotCommandInvocation<'a>> {
+    let literal = entities.iter().any(|e| {
+        e.offset == 0
+            && e.length > 0
+            && matches!(e.kind, TextEntityKind::Code | TextEntityKind::Pre)
+    });
+    if literal {
+        return None;
+    }
+    let rest = content.strip_prefix('/')?;
+    let is_word = |b: u8| b.is_ascii_alphanumeric() || b == b'_';
+
+    let token_end = match entities
+        .iter()
+        .find(|e| e.offset == 0 && e.kind == TextEntityKind::BotCommand)
+    {
+        Some(entity) => {
+            let end = usize::try_from(entity.length).ok()?;
+            if end == 0 || end > content.len() || !content.is_char_boundary(end) {
+                return None;
+            }
+            end
+        }
+        None => {
+            1 + rest
+                .bytes()
+                .take_while(|&b| is_word(b) || b == b'@')
+                .count()
+        }
+    };
+    let token = &content[1..token_end];
+    let after = &content[token_end..];
+    if !after.is_empty() && !after.starts_with(char::is_whitespace) {
+        return None;
+    }
+
+    let (command, bot_username) = match token.split_once('@') {
+        Some((command, bot)) => (command, Some(bot)),
+        None => (token, None),
+    };
+    let valid = |s: &str| !s.is_empty() && s.bytes().all(is_word);
+    let valid_command = valid(command)
+        && command.len() <= BOT_COMMAND_MAX_LEN
+        && !command.bytes().any(|b| b.is_ascii_uppercase());
+    if !valid_command || !bot_username.is_none_or(valid) {
+        return None;
+    }
+    Some(BotCommandInvocation {
+        command,
+        bot_username,
+        args: after.trim_start(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::routes;
+
+    fn entity(offset: u32, length: u32, kind: TextEntityKind) -> TextEntity {
+        TextEntity {
+            offset,
+            length,
+            kind,
+        }
+    }
+
+    #[test]
+    fn route_constants_are_stable() {
+        assert_eq!(routes::bot::COMMANDS_SET, "bot/commands/set");
+        assert_eq!(routes::bot::COMMANDS_GET, "bot/commands/get");
+    }
+
+    #[test]
+    fn parses_command_bot_and_args() {
+        let got = parse_bot_command("/start@PrivBot  ref 42", &[]).unwrap();
+        assert_eq!(got.command, "start");
+        assert_eq!(got.bot_username, Some("PrivBot"));
+        assert_eq!(got.args, "ref 42");
+        assert!(got.is_addressed_to("privbot"));
+        assert!(!got.is_addressed_to("otherbot"));
+
+        let bare = parse_bot_command("/help", &[]).unwrap();
+        assert_eq!(
+            (bare.command, bare.bot_username, bare.args),
+            ("help", None, "")
+        );
+        assert!(bare.is_addressed_to("anybot"));
+    }
+
+    #[test]
+    fn rejects_non_commands() {
+        for text in [
+            " /start",
+            "start",
+            "/",
+            "/@bot",
+            "/start@",
+            "/start!",
+            "/a/b",
+            "/start@bot!",
+            "/Start",
+            "/HELP@PrivBot",
+        ] {
+            assert_eq!(parse_bot_command(text, &[]), None, "{text:?}");
+        }
+        assert_eq!(
+            parse_bot_command(&format!("/{}", "a".repeat(33)), &[]),
+            None
+        );
+    }
+
+    #[test]
+    fn honours_entity_spans() {
+        // 代码块里的 `/start` 是字面文本
+        assert_eq!(
+            parse_bot_command("/start", &[entity(0, 6, TextEntityKind::Code)]),
+            None
+        );
+        // 实体划定 token 边界：`/start` 之后紧跟非空白 → 不是命令
+        assert_eq!(
+            parse_bot_command("/startnow", &[entity(0, 6, TextEntityKind::BotCommand)]),
+            None
+        );
+        let got = parse_bot_command(
+            "/start@bot go",
+            &[entity(0, 10, TextEntityKind::BotCommand)],
+        )
+        .unwrap();
+        assert_eq!(
+            (got.command, got.bot_username, got.args),
+            ("start", Some("bot"), "go")
+        );
+        // 越界 / 空的实体不会让解析 panic
+        for length in [0, 1, 99] {
+            assert_eq!(
+                parse_bot_command("/start", &[entity(0, length, TextEntityKind::BotCommand)]),
+                None
+            );
+        }
+        // 其他位置的格式实体不影响识别
+        assert!(parse_bot_command("/start go", &[entity(7, 2, TextEntityKind::Bold)]).is_some());
+    }
+
+    #[test]
+    fn set_request_validates_commands() {
+        let command = |name: &str, scope| BotCommand {
+            command: name.to_string(),
+            description: "desc".to_string(),
+            scope,
+        };
+        let ok = BotCommandsSetRequest {
+            bot_user_id: 0,
+            commands: vec![
+                command("start", BotCommandScope::All),
+                command("ban", BotCommandScope::Group),
+                command("ban", BotCommandScope::Direct),
+            ],
+        };
+        assert!(ok.validate().is_ok());
+
+        for bad in [
+            vec![command("Start", BotCommandScope::All)],
+            vec![command("", BotCommandScope::All)],
+            vec![
+                command("a", BotCommandScope::All),
+                command("a", BotCommandScope::All),
+            ],
+            vec![
+                command("ban", BotCommandScope::All),
+                command("ban", BotCommandScope::Group),
+            ],
+        ] {
+            let req = BotCommandsSetRequest {
+                bot_user_id: 0,
+                commands: bad,
+            };
+            assert!(req.validate().is_err());
+        }
+    }
+
+    #[test]
+    fn scope_covers_channel_types_and_defaults_to_all() {
+        let parsed: BotCommand =
+            serde_json::from_str(r#"{"command":"help","description":"Show help"}"#).unwrap();
+        assert_eq!(parsed.scope, BotCommandScope::All);
+        assert!(BotCommandScope::All.covers(1) && BotCommandScope::All.covers(2));
+        assert!(BotCommandScope::Group.covers(2) && !BotCommandScope::Group.covers(1));
+        assert!(BotCommandScope::Direct.overlaps(BotCommandScope::All));
+        assert!(!BotCommandScope::Direct.overlaps(BotCommandScope::Group));
+        assert_eq!(
+            serde_json::to_string(&BotCommandScope::Direct).unwrap(),
+            r#""direct""#
+        );
+    }
+}
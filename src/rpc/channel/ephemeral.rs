@@ -0,0 +1,48 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/// 频道阅后即焚 RPC
+use crate::ExpireMode;
+use serde::{Deserialize, Serialize};
+
+/// 设置频道阅后即焚请求
+///
+/// RPC路由: `channel/ephemeral/set`
+///
+/// 频道级设置，对频道内所有成员生效（区别于 mute / pin 这类个人偏好）。
+/// 设置成功后 server 以 `channel` 实体同步下发 `ephemeral_ttl` /
+/// `ephemeral_mode`；之后发出的消息由 server 按该设置盖章 `expire` /
+/// `expire_mode`，已发出的消息不受影响。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelEphemeralSetRequest {
+    /// 操作者ID（服务器端填充，客户端不可设置）
+    #[serde(skip_deserializing, default)]
+    pub user_id: u64,
+    /// 频道ID
+    pub channel_id: u64,
+    /// 消息存活时长（秒），0 = 关闭阅后即焚
+    pub ttl_seconds: u32,
+    /// 计时起点，缺省为发送即计时
+    #[serde(default)]
+    pub mode: ExpireMode,
+}
+
+/// 设置频道阅后即焚响应
+///
+/// RPC路由: `channel/ephemeral/set`
+/// 简单操作，返回 true（成功/失败由协议层 code 处理）
+pub type ChannelEphemeralSetResponse = bool;
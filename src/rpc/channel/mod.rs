@@ -17,11 +17,13 @@
 
 /// 频道相关 RPC 类型定义（私聊、群聊等会话功能）
 pub mod direct;
+pub mod ephemeral;
 pub mod hide;
 pub mod mute;
 pub mod pin;
 
 pub use direct::*;
+pub use ephemeral::*;
 pub use hide::*;
 pub use mute::*;
 pub use pin::*;
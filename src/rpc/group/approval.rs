@@ -16,7 +16,14 @@
 // limitations under the License.
 
 /// 群组审批相关 RPC
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
+
+/// 入群问题最多几条
+pub const GROUP_JOIN_QUESTIONS_MAX: usize = 3;
+/// 单条入群问题最大长度（字符数）
+pub const GROUP_JOIN_QUESTION_MAX_CHARS: usize = 64;
+/// 单条回答最大长度（字符数）
+pub const GROUP_JOIN_ANSWER_MAX_CHARS: usize = 128;
 
 /// 获取群组审批列表请求
 ///
@@ -29,6 +36,55 @@ pub struct GroupApprovalListRequest {
     pub operator_id: u64,
 }
 
+/// 审批操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalAction {
+    /// 同意入群
+    Approve,
+    /// 拒绝，可附带 `reject_reason`
+    Reject,
+    /// 忽略：从待审列表移除，不通知申请人，申请人视角下保持待审直至过期
+    Ignore,
+}
+
+impl ApprovalAction {
+    /// 执行后申请的状态。
+    pub fn resulting_status(self) -> GroupJoinRequestStatus {
+        match self {
+            Self::Approve => GroupJoinRequestStatus::Approved,
+            Self::Reject => GroupJoinRequestStatus::Rejected,
+            Self::Ignore => GroupJoinRequestStatus::Ignored,
+        }
+    }
+
+    /// 是否需要给申请人推送状态变化事件。
+    pub fn notifies_applicant(self) -> bool {
+        self != Self::Ignore
+    }
+}
+
+/// 入群申请状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GroupJoinRequestStatus {
+    Pending,
+    Approved,
+    Rejected,
+    Ignored,
+    Expired,
+    /// 新版本服务端引入、本端不认识的状态
+    #[serde(other)]
+    Unknown,
+}
+
+impl GroupJoinRequestStatus {
+    /// 是否已是终态（不会再变化）。
+    pub fn is_final(self) -> bool {
+        !matches!(self, Self::Pending | Self::Unknown)
+    }
+}
+
 /// 处理群组审批请求
 ///
 /// RPC路由: `group/approval/handle`
@@ -38,16 +94,47 @@ pub struct GroupApprovalHandleRequest {
     pub request_id: String,
     /// 操作者ID
     pub operator_id: u64,
-    /// 操作（approve/reject）
-    pub action: String,
-    /// 拒绝原因（可选）
+    /// 操作（线上仍是 `"approve"` / `"reject"` / `"ignore"` 字符串）
+    pub action: ApprovalAction,
+    /// 拒绝原因（可选，仅 `reject` 时有意义）
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reject_reason: Option<String>,
 }
 
-/// 获取群组审批列表响应
+/// 申请人是怎么找到这个群的。
 ///
-/// RPC路由: `group/approval/list`
+/// 线上格式 `{"type":"invite","inviter_id":"1"}`；老服务端的
+/// `{"MemberInvite":{"inviter_id":"1"}}` / `{"QRCode":{"qr_code_id":"…"}}`
+/// 形态在反序列化时自动转换。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ApprovalMethod {
+    /// 群成员邀请
+    Invite {
+        #[serde(with = "crate::serde_u64")]
+        inviter_id: u64,
+    },
+    /// 扫群二维码
+    #[serde(rename = "qrcode")]
+    QrCode { qr_key: String },
+    /// 群搜索
+    Search,
+    /// 邀请链接
+    Link { link_id: String },
+    /// 新版本服务端引入、本端不认识的方式
+    #[serde(other)]
+    Unknown,
+}
+
+/// 老服务端的审批方式格式（两个互斥的可选字段）。只用于兼容反序列化。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupApprovalMethod {
+    #[serde(rename = "MemberInvite", skip_serializing_if = "Option::is_none")]
+    pub member_invite: Option<GroupApprovalMethodMemberInvite>,
+    #[serde(rename = "QRCode", skip_serializing_if = "Option::is_none")]
+    pub qr_code: Option<GroupApprovalMethodQrCode>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GroupApprovalMethodMemberInvite {
     pub inviter_id: String,
@@ -58,21 +145,58 @@ pub struct GroupApprovalMethodQrCode {
     pub qr_code_id: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct GroupApprovalMethod {
-    #[serde(rename = "MemberInvite", skip_serializing_if = "Option::is_none")]
-    pub member_invite: Option<GroupApprovalMethodMemberInvite>,
-    #[serde(rename = "QRCode", skip_serializing_if = "Option::is_none")]
-    pub qr_code: Option<GroupApprovalMethodQrCode>,
+impl From<GroupApprovalMethod> for ApprovalMethod {
+    fn from(legacy: GroupApprovalMethod) -> Self {
+        match (legacy.member_invite, legacy.qr_code) {
+            (Some(invite), _) => match invite.inviter_id.trim().parse() {
+                Ok(inviter_id) => Self::Invite { inviter_id },
+                Err(_) => Self::Unknown,
+            },
+            (None, Some(qr)) => Self::QrCode {
+                qr_key: qr.qr_code_id,
+            },
+            (None, None) => Self::Unknown,
+        }
+    }
 }
 
+fn deserialize_approval_method<'de, D>(deserializer: D) -> Result<ApprovalMethod, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Wire {
+        Typed(ApprovalMethod),
+        Legacy(GroupApprovalMethod),
+    }
+    Ok(match Wire::deserialize(deserializer)? {
+        Wire::Typed(method) => method,
+        Wire::Legacy(legacy) => legacy.into(),
+    })
+}
+
+/// 一条入群问题及申请人的回答
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GroupJoinAnswer {
+    pub question: String,
+    pub answer: String,
+}
+
+/// 获取群组审批列表响应
+///
+/// RPC路由: `group/approval/list`
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GroupApprovalItem {
     pub request_id: String,
     pub user_id: u64,
-    pub method: GroupApprovalMethod,
+    #[serde(deserialize_with = "deserialize_approval_method")]
+    pub method: ApprovalMethod,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub message: Option<String>,
+    /// 入群问题与回答（群未设置问题时为空）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub answers: Vec<GroupJoinAnswer>,
     pub created_at: u64,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub expires_at: Option<u64>,
@@ -93,7 +217,7 @@ pub struct GroupApprovalListResponse {
 pub struct GroupApprovalHandleResponse {
     pub success: bool,
     pub request_id: String,
-    pub action: String,
+    pub action: ApprovalAction,
     pub group_id: u64,
     pub user_id: u64,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -101,3 +225,236 @@ pub struct GroupApprovalHandleResponse {
     pub message: String,
     pub handled_at: u64,
 }
+
+/// 设置入群问题请求（需要审批权限）
+///
+/// RPC路由: `group/approval/questions/set`
+///
+/// 整体覆盖；空列表 = 清除问题。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupJoinQuestionsSetRequest {
+    /// 群组ID
+    pub group_id: u64,
+    /// 问题列表，最多 [`GROUP_JOIN_QUESTIONS_MAX`] 条
+    #[serde(default)]
+    pub questions: Vec<String>,
+
+    /// 操作者ID（服务器端填充，客户端不可设置）
+    #[serde(skip_deserializing, default)]
+    pub operator_id: u64,
+}
+
+impl GroupJoinQuestionsSetRequest {
+    /// 客户端提交前的本地校验；服务端会再做一次。
+    pub fn validate(&self) -> Result<(), crate::ErrorCode> {
+        let invalid = self.questions.len() > GROUP_JOIN_QUESTIONS_MAX
+            || self
+                .questions
+                .iter()
+                .any(|q| q.trim().is_empty() || q.chars().count() > GROUP_JOIN_QUESTION_MAX_CHARS);
+        if invalid {
+            Err(crate::ErrorCode::InvalidParams)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// 设置入群问题响应
+///
+/// RPC路由: `group/approval/questions/set`
+/// 简单操作，返回 true（成功/失败由协议层 code 处理）
+pub type GroupJoinQuestionsSetResponse = bool;
+
+/// 获取入群问题请求
+///
+/// RPC路由: `group/approval/questions/get`
+///
+/// 申请人还不是成员，扫码入群时只有 `qr_key`，其他途径用 `group_id`，二选一。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GroupJoinQuestionsGetRequest {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group_id: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub qr_key: Option<String>,
+}
+
+impl GroupJoinQuestionsGetRequest {
+    /// `group_id` 与 `qr_key` 必须恰好给一个。
+    pub fn validate(&self) -> Result<(), crate::ErrorCode> {
+        if self.group_id.is_some() == self.qr_key.is_some() {
+            Err(crate::ErrorCode::InvalidParams)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// 获取入群问题响应
+///
+/// RPC路由: `group/approval/questions/get`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupJoinQuestionsGetResponse {
+    pub group_id: u64,
+    /// 空 = 无需回答
+    #[serde(default)]
+    pub questions: Vec<String>,
+}
+
+/// 校验申请人对入群问题的回答：条数一致、每条非空且不超长。
+///
+/// 缺答 → `GroupJoinAnswerRequired`；超长 → `InvalidParams`。
+pub fn validate_join_answers(
+    questions: &[String],
+    answers: &[String],
+) -> Result<(), crate::ErrorCode> {
+    if answers.len() != questions.len() || answers.iter().any(|a| a.trim().is_empty()) {
+        return Err(crate::ErrorCode::GroupJoinAnswerRequired);
+    }
+    if answers
+        .iter()
+        .any(|a| a.chars().count() > GROUP_JOIN_ANSWER_MAX_CHARS)
+    {
+        return Err(crate::ErrorCode::InvalidParams);
+    }
+    Ok(())
+}
+
+/// 把问题与回答配对，供 [`GroupApprovalItem::answers`] 使用。
+pub fn pair_join_answers(questions: &[String], answers: &[String]) -> Vec<GroupJoinAnswer> {
+    questions
+        .iter()
+        .zip(answers)
+        .map(|(question, answer)| GroupJoinAnswer {
+            question: question.clone(),
+            answer: answer.trim().to_string(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ErrorCode;
+
+    #[test]
+    fn action_strings_are_wire_compatible() {
+        let request: GroupApprovalHandleRequest = serde_json::from_value(serde_json::json!({
+            "request_id": "r1",
+            "operator_id": 1,
+            "action": "reject",
+            "reject_reason": "spam"
+        }))
+        .expect("legacy handle request");
+        assert_eq!(request.action, ApprovalAction::Reject);
+        assert_eq!(
+            serde_json::to_value(ApprovalAction::Ignore).unwrap(),
+            serde_json::json!("ignore")
+        );
+        assert!(serde_json::from_value::<ApprovalAction>(serde_json::json!("maybe")).is_err());
+        assert!(!ApprovalAction::Ignore.notifies_applicant());
+        assert!(ApprovalAction::Approve.resulting_status().is_final());
+    }
+
+    #[test]
+    fn approval_method_reads_legacy_and_typed_shapes() {
+        let item = |method: serde_json::Value| -> GroupApprovalItem {
+            serde_json::from_value(serde_json::json!({
+                "request_id": "r1",
+                "user_id": 2,
+                "method": method,
+                "created_at": 0
+            }))
+            .expect("approval item")
+        };
+
+        assert_eq!(
+            item(serde_json::json!({"MemberInvite": {"inviter_id": "42"}})).method,
+            ApprovalMethod::Invite { inviter_id: 42 }
+        );
+        assert_eq!(
+            item(serde_json::json!({"MemberInvite": {"inviter_id": "bob"}})).method,
+            ApprovalMethod::Unknown
+        );
+        assert_eq!(
+            item(serde_json::json!({"type": "invite", "inviter_id": 42})).method,
+            ApprovalMethod::Invite { inviter_id: 42 }
+        );
+        assert_eq!(
+            serde_json::to_value(ApprovalMethod::Invite {
+                inviter_id: 9_007_199_254_740_993
+            })
+            .unwrap(),
+            serde_json::json!({"type": "invite", "inviter_id": "9007199254740993"})
+        );
+        assert_eq!(
+            item(serde_json::json!({"QRCode": {"qr_code_id": "abc"}})).method,
+            ApprovalMethod::QrCode {
+                qr_key: "abc".into()
+            }
+        );
+        assert_eq!(
+            item(serde_json::json!({"type": "search"})).method,
+            ApprovalMethod::Search
+        );
+        assert_eq!(
+            item(serde_json::json!({"type": "carrier_pigeon"})).method,
+            ApprovalMethod::Unknown
+        );
+
+        let typed = item(serde_json::json!({"type": "link", "link_id": "L1"}));
+        let json = serde_json::to_value(&typed).unwrap();
+        assert_eq!(
+            json["method"],
+            serde_json::json!({"type": "link", "link_id": "L1"})
+        );
+        assert_eq!(
+            serde_json::to_value(ApprovalMethod::QrCode { qr_key: "k".into() }).unwrap()["type"],
+            "qrcode"
+        );
+    }
+
+    #[test]
+    fn join_questions_and_answers() {
+        let questions = vec!["你从哪里知道本群？".to_string(), "你的职业？".to_string()];
+        let set = GroupJoinQuestionsSetRequest {
+            group_id: 1,
+            questions: questions.clone(),
+            operator_id: 0,
+        };
+        assert!(set.validate().is_ok());
+        let too_many = GroupJoinQuestionsSetRequest {
+            questions: vec!["q".into(); GROUP_JOIN_QUESTIONS_MAX + 1],
+            ..set.clone()
+        };
+        assert_eq!(too_many.validate(), Err(ErrorCode::InvalidParams));
+
+        let answers = vec!["朋友推荐".to_string(), " 工程师 ".to_string()];
+        assert!(validate_join_answers(&questions, &answers).is_ok());
+        assert!(validate_join_answers(&[], &[]).is_ok());
+        assert_eq!(
+            validate_join_answers(&questions, &answers[..1]),
+            Err(ErrorCode::GroupJoinAnswerRequired)
+        );
+        assert_eq!(
+            validate_join_answers(&questions, &["朋友推荐".into(), "  ".into()]),
+            Err(ErrorCode::GroupJoinAnswerRequired)
+        );
+        assert_eq!(
+            validate_join_answers(
+                &questions[..1],
+                &["x".repeat(GROUP_JOIN_ANSWER_MAX_CHARS + 1)]
+            ),
+            Err(ErrorCode::InvalidParams)
+        );
+        assert_eq!(pair_join_answers(&questions, &answers)[1].answer, "工程师");
+
+        assert!(GroupJoinQuestionsGetRequest::default().validate().is_err());
+        assert!(GroupJoinQuestionsGetRequest {
+            qr_key: Some("k".into()),
+            ..Default::default()
+        }
+        .validate()
+        .is_ok());
+    }
+}
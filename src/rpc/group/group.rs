@@ -89,8 +89,24 @@ pub struct GroupInfoResponse {
     #[serde(default)]
     pub my_role: String,
 
-    /// 管理员 uid 列表（群主见 `owner_id`）。有界——一个群的管理员只有个位数，
-    /// 让气泡上的【群主】/【管理】标签不必依赖花名册。
+    /// 管理员 uid 列表（群主见 `owner_id`）。有界——最多
+    /// [`GROUP_INFO_ADMIN_IDS_MAX`] 个，让气泡上的【群主】/【管理】标签不必依赖花名册。
+    /// 超过上限时以 `admin_count` 为准，完整列表走 `group/member/list`
+    /// （`filter = admins`）。
     #[serde(default)]
     pub admin_user_ids: Vec<u64>,
+
+    /// 管理员总数；老服务端缺省为 0，此时以 `admin_user_ids.len()` 为准。
+    #[serde(default)]
+    pub admin_count: u32,
+}
+
+/// `GroupInfoResponse.admin_user_ids` 最多携带的管理员数
+pub const GROUP_INFO_ADMIN_IDS_MAX: usize = 50;
+
+impl GroupInfoResponse {
+    /// `admin_user_ids` 是否被截断（需要分页拉取完整管理员列表）。
+    pub fn admin_ids_truncated(&self) -> bool {
+        self.admin_count as usize > self.admin_user_ids.len()
+    }
 }
@@ -18,15 +18,97 @@
 /// 群组成员管理相关 RPC
 use serde::{Deserialize, Serialize};
 
+/// 禁言请求的唯一定义在 [`super::member_mute`]，这里保留旧路径。
+pub use super::member_mute::{GroupMemberMuteRequest, GroupMemberUnmuteRequest};
+
+/// 单页成员数上限；`limit` 超过时服务端截断。
+pub const GROUP_MEMBER_LIST_MAX_LIMIT: u32 = 200;
+/// 成员搜索关键字最大长度（字符数）
+pub const GROUP_MEMBER_QUERY_MAX_CHARS: usize = 32;
+/// [`GroupMemberListFilter::Recent`] 的时间窗口：7 天（毫秒）
+pub const GROUP_MEMBER_RECENT_WINDOW_MS: u64 = 7 * 24 * 60 * 60 * 1000;
+
+/// 成员列表过滤条件
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GroupMemberListFilter {
+    /// 群主与管理员
+    Admins,
+    /// 禁言中（含限时限制里的禁言）
+    Muted,
+    /// 机器人（`user_type = 2`）
+    Bots,
+    /// 最近 [`GROUP_MEMBER_RECENT_WINDOW_MS`] 内加入，按加入时间倒序
+    Recent,
+    /// 全部成员（未知取值也按全部处理）
+    #[default]
+    #[serde(other)]
+    All,
+}
+
+impl GroupMemberListFilter {
+    /// 是否按加入时间倒序（其余均为升序）。
+    pub fn descending(self) -> bool {
+        self == Self::Recent
+    }
+}
+
+/// 成员列表 keyset 游标：上一页最后一个成员的 `(joined_at, user_id)`。
+///
+/// 线上格式为 `"joined_at:user_id"`，与 `message/history/search` 的游标一致；
+/// 客户端只应原样回传 `next_cursor`，不要自行拼接。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GroupMemberCursor {
+    pub joined_at: u64,
+    pub user_id: u64,
+}
+
+impl GroupMemberCursor {
+    /// 取某个成员的位置作为游标。
+    pub fn of(member: &GroupMemberInfo) -> Self {
+        Self {
+            joined_at: member.joined_at,
+            user_id: member.user_id,
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        format!("{}:{}", self.joined_at, self.user_id)
+    }
+
+    /// 解析游标，格式不对时返回 `None`。
+    pub fn parse(value: &str) -> Option<Self> {
+        let (joined_at, user_id) = value.split_once(':')?;
+        Some(Self {
+            joined_at: joined_at.parse().ok()?,
+            user_id: user_id.parse().ok()?,
+        })
+    }
+
+    /// `member` 是否排在游标之后（即属于下一页）。
+    pub fn precedes(&self, member: &GroupMemberInfo, descending: bool) -> bool {
+        let key = Self::of(member);
+        if descending {
+            key < *self
+        } else {
+            key > *self
+        }
+    }
+}
+
 /// 获取群组成员列表请求
 ///
 /// RPC路由: `group/member/list`
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// 大群用 `cursor` 翻页：首页不带游标，之后回传上一页的 `next_cursor`。
+/// `offset` 仅为老客户端保留，不能与 `cursor` 同时使用。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct GroupMemberListRequest {
     /// 群组ID
     pub group_id: u64,
 
-    /// 本页最多返回多少条；`None` = 全量（老客户端行为）。上限见服务端 `MAX_PAGE`。
+    /// 本页最多返回多少条；`None` = 全量（老客户端行为）。上限见
+    /// [`GROUP_MEMBER_LIST_MAX_LIMIT`]。
     ///
     /// 九宫格头像只需要前 9 个成员，却曾经拉走整份花名册——750 人群 126 KB。
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -36,11 +118,85 @@ pub struct GroupMemberListRequest {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub offset: Option<u32>,
 
+    /// keyset 游标（上一页响应的 `next_cursor`）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+
+    /// 按群名片 / 显示名 / 昵称 / 用户名前缀搜索（不区分大小写）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+
+    /// 过滤条件，缺省 = 全部
+    #[serde(default, skip_serializing_if = "is_all_filter")]
+    pub filter: GroupMemberListFilter,
+
     /// 请求者ID（服务器端填充，客户端不可设置）
     #[serde(skip_deserializing, default)]
     pub user_id: u64,
 }
 
+fn is_all_filter(filter: &GroupMemberListFilter) -> bool {
+    *filter == GroupMemberListFilter::All
+}
+
+impl GroupMemberListRequest {
+    /// 客户端提交前的本地校验；服务端会再做一次。
+    pub fn validate(&self) -> Result<(), crate::ErrorCode> {
+        if self.cursor.is_some() && self.offset.is_some() {
+            return Err(crate::ErrorCode::InvalidParams);
+        }
+        if self
+            .cursor
+            .as_deref()
+            .is_some_and(|c| GroupMemberCursor::parse(c).is_none())
+        {
+            return Err(crate::ErrorCode::InvalidParams);
+        }
+        if self
+            .query
+            .as_deref()
+            .is_some_and(|q| q.chars().count() > GROUP_MEMBER_QUERY_MAX_CHARS)
+        {
+            return Err(crate::ErrorCode::InvalidParams);
+        }
+        Ok(())
+    }
+
+    /// 解析后的游标；缺省或格式错误为 `None`。
+    pub fn parsed_cursor(&self) -> Option<GroupMemberCursor> {
+        self.cursor.as_deref().and_then(GroupMemberCursor::parse)
+    }
+
+    /// 成员是否满足 `query` 与 `filter`（不含游标）。server 查询和 SDK
+    /// 本地缓存过滤共用这一套规则。
+    pub fn matches(&self, member: &GroupMemberInfo, now_ms: u64) -> bool {
+        let filtered = match self.filter {
+            GroupMemberListFilter::All => true,
+            GroupMemberListFilter::Admins => {
+                super::role::GroupMemberRole::from_wire_str(&member.role).can_manage()
+            }
+            GroupMemberListFilter::Muted => {
+                member.is_muted
+                    || member
+                        .restriction
+                        .is_some_and(|r| r.mute && r.is_active(now_ms))
+            }
+            GroupMemberListFilter::Bots => member.user_type == 2,
+            GroupMemberListFilter::Recent => {
+                member
+                    .joined_at
+                    .saturating_add(GROUP_MEMBER_RECENT_WINDOW_MS)
+                    > now_ms
+            }
+        };
+        filtered
+            && self
+                .query
+                .as_deref()
+                .is_none_or(|q| member.matches_prefix(q))
+    }
+}
+
 /// 移除群组成员请求
 ///
 /// RPC路由: `group/member/remove`
@@ -87,38 +243,6 @@ pub struct GroupMemberAddRequest {
     pub inviter_id: u64,
 }
 
-/// 禁言群组成员请求
-///
-/// RPC路由: `group/member/mute`
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct GroupMemberMuteRequest {
-    /// 群组ID
-    pub group_id: u64,
-    /// 被禁言的用户ID
-    pub user_id: u64,
-    /// 禁言时长（秒），0表示永久禁言
-    pub mute_duration: u64,
-
-    /// 操作者ID（服务器端填充，客户端不可设置）
-    #[serde(skip_deserializing, default)]
-    pub operator_id: u64,
-}
-
-/// 取消禁言群组成员请求
-///
-/// RPC路由: `group/member/unmute`
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct GroupMemberUnmuteRequest {
-    /// 群组ID
-    pub group_id: u64,
-    /// 被取消禁言的用户ID
-    pub user_id: u64,
-
-    /// 操作者ID（服务器端填充，客户端不可设置）
-    #[serde(skip_deserializing, default)]
-    pub operator_id: u64,
-}
-
 /// 获取群组成员列表响应
 ///
 /// RPC路由: `group/member/list`
@@ -140,11 +264,41 @@ pub struct GroupMemberInfo {
     pub role: String,
     pub joined_at: u64,
     pub is_muted: bool,
+    /// 管理员权限位（仅管理员下发）。用
+    /// [`GroupAdminRights::effective`](super::role::GroupAdminRights::effective) 求实际权限。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub admin_rights: Option<super::role::GroupAdminRights>,
+    /// 管理员自定义头衔
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub admin_title: Option<String>,
+    /// 生效中的限制（禁言 / 禁发媒体 / 禁发链接）；`is_muted` 仅表示其中的禁言
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub restriction: Option<super::member_mute::GroupMemberRestriction>,
+}
+
+impl GroupMemberInfo {
+    /// 群名片、显示名、昵称或用户名中任一以 `query` 开头（不区分大小写）。
+    /// 空白 `query` 匹配所有人。
+    pub fn matches_prefix(&self, query: &str) -> bool {
+        let query = query.trim().to_lowercase();
+        if query.is_empty() {
+            return true;
+        }
+        [
+            self.alias.as_deref().unwrap_or_default(),
+            &self.display_name,
+            &self.nickname,
+            &self.username,
+        ]
+        .iter()
+        .any(|name| name.to_lowercase().starts_with(&query))
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::GroupMemberInfo;
+    use super::*;
+    use crate::rpc::group::GroupMemberRestriction;
 
     #[test]
     fn old_server_member_payload_defaults_new_projection_fields() {
@@ -161,6 +315,123 @@ mod tests {
         assert_eq!(member.alias, None);
         assert_eq!(member.display_name, "");
         assert_eq!(member.user_type, 0);
+        assert_eq!(member.admin_rights, None);
+        assert_eq!(member.admin_title, None);
+    }
+
+    fn member(user_id: u64, nickname: &str, role: &str, joined_at: u64) -> GroupMemberInfo {
+        GroupMemberInfo {
+            user_id,
+            alias: None,
+            username: String::new(),
+            nickname: nickname.to_string(),
+            display_name: nickname.to_string(),
+            avatar_url: None,
+            user_type: 0,
+            role: role.to_string(),
+            joined_at,
+            is_muted: false,
+            admin_rights: None,
+            admin_title: None,
+            restriction: None,
+        }
+    }
+
+    #[test]
+    fn legacy_list_request_and_response_still_parse() {
+        let request: GroupMemberListRequest =
+            serde_json::from_value(serde_json::json!({"group_id": 7, "limit": 9}))
+                .expect("legacy list request");
+        assert_eq!(request.filter, GroupMemberListFilter::All);
+        assert!(request.validate().is_ok());
+
+        let request: GroupMemberListRequest =
+            serde_json::from_value(serde_json::json!({"group_id": 7, "filter": "from_the_future"}))
+                .expect("unknown filter");
+        assert_eq!(request.filter, GroupMemberListFilter::All);
+
+        let response: GroupMemberListResponse =
+            serde_json::from_value(serde_json::json!({"members": [], "total": 3}))
+                .expect("legacy list response");
+        assert_eq!(response.next_cursor, None);
+        assert!(!response.has_more);
+    }
+
+    #[test]
+    fn cursor_round_trips_and_orders() {
+        let cursor = GroupMemberCursor {
+            joined_at: 100,
+            user_id: 9,
+        };
+        assert_eq!(GroupMemberCursor::parse(&cursor.encode()), Some(cursor));
+        assert_eq!(GroupMemberCursor::parse("100"), None);
+        assert_eq!(GroupMemberCursor::parse("a:1"), None);
+
+        // 同一时间加入的成员按 user_id 决出先后，翻页不丢不重
+        assert!(cursor.precedes(&member(10, "b", "member", 100), false));
+        assert!(!cursor.precedes(&member(9, "a", "member", 100), false));
+        assert!(cursor.precedes(&member(1, "c", "member", 99), true));
+
+        let invalid = [
+            GroupMemberListRequest {
+                cursor: Some("x".into()),
+                ..Default::default()
+            },
+            GroupMemberListRequest {
+                cursor: Some(cursor.encode()),
+                offset: Some(10),
+                ..Default::default()
+            },
+            GroupMemberListRequest {
+                query: Some("x".repeat(GROUP_MEMBER_QUERY_MAX_CHARS + 1)),
+                ..Default::default()
+            },
+        ];
+        for request in invalid {
+            assert_eq!(request.validate(), Err(crate::ErrorCode::InvalidParams));
+        }
+    }
+
+    #[test]
+    fn search_and_filters() {
+        let now = 30 * 24 * 60 * 60 * 1000;
+        let mut alice = member(1, "Alice", "owner", 0);
+        alice.alias = Some("老王".into());
+        let mut bot = member(2, "Helper", "member", now - 1000);
+        bot.user_type = 2;
+        let mut muted = member(3, "alex", "member", 0);
+        muted.restriction = Some(GroupMemberRestriction {
+            mute: true,
+            until: now + 1,
+            ..Default::default()
+        });
+
+        let request = |filter, query: Option<&str>| GroupMemberListRequest {
+            filter,
+            query: query.map(str::to_string),
+            ..Default::default()
+        };
+        let pick = |request: GroupMemberListRequest| -> Vec<u64> {
+            [&alice, &bot, &muted]
+                .into_iter()
+                .filter(|m| request.matches(m, now))
+                .map(|m| m.user_id)
+                .collect()
+        };
+
+        assert_eq!(
+            pick(request(GroupMemberListFilter::All, Some("AL"))),
+            [1, 3]
+        );
+        assert_eq!(pick(request(GroupMemberListFilter::All, Some("老"))), [1]);
+        assert_eq!(
+            pick(request(GroupMemberListFilter::All, Some("lice"))),
+            Vec::<u64>::new()
+        );
+        assert_eq!(pick(request(GroupMemberListFilter::Admins, None)), [1]);
+        assert_eq!(pick(request(GroupMemberListFilter::Bots, None)), [2]);
+        assert_eq!(pick(request(GroupMemberListFilter::Muted, Some("al"))), [3]);
+        assert_eq!(pick(request(GroupMemberListFilter::Recent, None)), [2]);
     }
 }
 
@@ -170,7 +441,16 @@ mod tests {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GroupMemberListResponse {
     pub members: Vec<GroupMemberInfo>,
+    /// 满足 `query` / `filter` 的成员总数
     pub total: usize,
+    /// 群成员总数（不受过滤影响），老服务端缺省为 0
+    #[serde(default)]
+    pub member_count: u32,
+    /// 下一页游标；`None` = 已到末尾
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+    #[serde(default)]
+    pub has_more: bool,
 }
 
 /// 添加群组成员响应
@@ -15,7 +15,11 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-/// 禁言/解除禁言群组成员 RPC
+/// 禁言 / 限制群组成员 RPC
+///
+/// 成员限制只有一个模型：[`GroupMemberRestriction`]。`group/member/mute` 是它
+/// 「只禁言」的简写，server 收到后按 [`GroupMemberMuteRequest::to_restriction`]
+/// 落库；`group/member/restrict` 可同时设置禁言、禁发媒体、禁发链接。
 use serde::{Deserialize, Serialize};
 
 /// 禁言群组成员请求
@@ -25,25 +29,93 @@ use serde::{Deserialize, Serialize};
 pub struct GroupMemberMuteRequest {
     /// 群组ID
     pub group_id: u64,
-    /// 操作者ID
-    pub operator_id: u64,
     /// 被禁言的用户ID
     pub user_id: u64,
-    /// 禁言时长（秒）
+    /// 禁言时长（秒），0表示永久禁言
     pub mute_duration: u64,
+
+    /// 操作者ID（服务器端填充，客户端不可设置）
+    #[serde(skip_deserializing, default)]
+    pub operator_id: u64,
 }
 
-/// 解除禁言请求
+impl GroupMemberMuteRequest {
+    /// 换算成统一的限制模型。`now_ms` 为 server 当前时间（毫秒）。
+    pub fn to_restriction(&self, now_ms: u64) -> GroupMemberRestriction {
+        GroupMemberRestriction {
+            mute: true,
+            no_media: false,
+            no_links: false,
+            until: if self.mute_duration == 0 {
+                0
+            } else {
+                now_ms.saturating_add(self.mute_duration.saturating_mul(1000))
+            },
+        }
+    }
+}
+
+/// 取消禁言群组成员请求
 ///
 /// RPC路由: `group/member/unmute`
+///
+/// 清除该成员的全部限制（不只是禁言）。
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GroupMemberUnmuteRequest {
     /// 群组ID
     pub group_id: u64,
-    /// 操作者ID
+    /// 被取消禁言的用户ID
+    pub user_id: u64,
+
+    /// 操作者ID（服务器端填充，客户端不可设置）
+    #[serde(skip_deserializing, default)]
     pub operator_id: u64,
-    /// 被解除禁言的用户ID
+}
+
+/// 群成员限制。所有限制共用一个到期时间。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GroupMemberRestriction {
+    /// 禁止发言
+    #[serde(default)]
+    pub mute: bool,
+    /// 禁止发送图片 / 视频 / 语音 / 文件 / 贴纸
+    #[serde(default)]
+    pub no_media: bool,
+    /// 禁止发送链接
+    #[serde(default)]
+    pub no_links: bool,
+    /// 到期时间（毫秒），0 表示永久
+    #[serde(default)]
+    pub until: u64,
+}
+
+impl GroupMemberRestriction {
+    /// 在 `now_ms` 时是否仍有任一限制生效。
+    pub fn is_active(&self, now_ms: u64) -> bool {
+        (self.mute || self.no_media || self.no_links) && (self.until == 0 || self.until > now_ms)
+    }
+
+    /// 距离解除还有多少毫秒；永久或已失效时为 `None`。
+    pub fn remaining_ms(&self, now_ms: u64) -> Option<u64> {
+        (self.is_active(now_ms) && self.until != 0).then(|| self.until - now_ms)
+    }
+}
+
+/// 限制群组成员请求
+///
+/// RPC路由: `group/member/restrict`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupMemberRestrictRequest {
+    /// 群组ID
+    pub group_id: u64,
+    /// 被限制的用户ID
     pub user_id: u64,
+    #[serde(flatten)]
+    pub restriction: GroupMemberRestriction,
+
+    /// 操作者ID（服务器端填充，客户端不可设置）
+    #[serde(skip_deserializing, default)]
+    pub operator_id: u64,
 }
 
 /// 禁言操作响应
@@ -52,8 +124,54 @@ pub struct GroupMemberUnmuteRequest {
 /// 返回禁言到期时间戳（毫秒），0 表示永久禁言
 pub type GroupMemberMuteResponse = u64;
 
+/// 限制操作响应
+///
+/// RPC路由: `group/member/restrict`
+/// 返回限制到期时间戳（毫秒），0 表示永久
+pub type GroupMemberRestrictResponse = u64;
+
 /// 解除禁言响应
 ///
 /// RPC路由: `group/member/unmute`
 /// 简单操作，返回 true（成功/失败由协议层 code 处理）
 pub type GroupMemberUnmuteResponse = bool;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mute_request_maps_to_restriction() {
+        let request: GroupMemberMuteRequest = serde_json::from_value(serde_json::json!({
+            "group_id": 1, "user_id": 2, "mute_duration": 60, "operator_id": 999
+        }))
+        .unwrap();
+        // operator_id 由服务端填充，客户端伪造无效
+        assert_eq!(request.operator_id, 0);
+        let restriction = request.to_restriction(1_000);
+        assert!(restriction.mute);
+        assert_eq!(restriction.until, 61_000);
+        assert!(restriction.is_active(60_999));
+        assert!(!restriction.is_active(61_000));
+        assert_eq!(restriction.remaining_ms(1_000), Some(60_000));
+
+        let forever = GroupMemberMuteRequest {
+            mute_duration: 0,
+            ..request
+        }
+        .to_restriction(1_000);
+        assert!(forever.is_active(u64::MAX));
+        assert_eq!(forever.remaining_ms(1_000), None);
+    }
+
+    #[test]
+    fn restrict_request_is_flat() {
+        let request: GroupMemberRestrictRequest = serde_json::from_value(serde_json::json!({
+            "group_id": 1, "user_id": 2, "no_links": true, "until": 5
+        }))
+        .unwrap();
+        assert!(request.restriction.no_links);
+        assert!(!request.restriction.mute);
+        assert!(!GroupMemberRestriction::default().is_active(0));
+    }
+}
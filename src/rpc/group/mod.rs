@@ -19,6 +19,8 @@ pub mod approval;
 pub mod group;
 pub mod member;
 pub mod member_mute;
+/// 群权限判定（角色 × 群设置 × 成员状态）。
+pub mod permission;
 pub mod qrcode;
 /// 群成员角色的**唯一权威定义**（数值/字符串两种线上编码都在这里冻结）。
 pub mod role;
@@ -30,7 +32,8 @@ pub mod transfer;
 pub use approval::*;
 pub use group::*;
 pub use member::*;
-pub use member_mute::{GroupMemberMuteResponse, GroupMemberUnmuteResponse};
+pub use member_mute::*;
+pub use permission::*;
 pub use qrcode::*;
 pub use role_set::*;
 pub use settings::*;
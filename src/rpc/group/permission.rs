@@ -0,0 +1,798 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! 群权限判定 —— 角色、群设置与成员禁言状态的**唯一组合逻辑**。
+//!
+//! 以前各端各自把 `role`、`all_muted`、`allow_member_post`、
+//! `member_can_invite`、`forbid_forward`、`allow_member_add_friend` 和成员禁言
+//! 拼在一起判断，拼法不一致时就会出现「客户端放行、服务端拒绝」或反过来。
+//! server 鉴权和客户端置灰按钮都调用 [`evaluate`]，拒绝时返回的
+//! [`ErrorCode`] 即 server 应返回的错误码。
+//!
+//! 发一条具体消息还要叠加成员限制（禁发媒体 / 链接）与慢速模式，见
+//! [`evaluate_post`]，它会同时给出可以重试的时间。
+
+use super::member_mute::GroupMemberRestriction;
+use super::role::{GroupAdminRights, GroupMemberRole};
+use super::settings::GroupSettingsData;
+use crate::{ErrorCode, InlineButtonAction, MessageMetadata, MessagePayloadEnvelope};
+
+/// 需要判定的群内操作。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GroupPermission {
+    /// 发言
+    Post,
+    /// 邀请入群
+    Invite,
+    /// 移出群成员（需要目标角色）
+    Kick,
+    /// 禁言群成员（需要目标角色）
+    Mute,
+    /// 置顶消息
+    Pin,
+    /// 修改群资料 / 群设置
+    EditInfo,
+    /// 把本群消息转发出去
+    Forward,
+    /// 通过本群私自添加成员为好友
+    AddFriend,
+    /// 删除他人消息
+    DeleteMessages,
+    /// 处理入群申请
+    ManageApprovals,
+}
+
+impl GroupPermission {
+    /// 是否作用于另一名成员（判定时需要 `target_role`）。
+    pub fn targets_member(self) -> bool {
+        matches!(self, Self::Kick | Self::Mute)
+    }
+
+    /// 管理员执行此操作需要的权限位；不需要管理权限的操作为 `None`。
+    pub fn required_admin_right(self) -> Option<GroupAdminRights> {
+        match self {
+            Self::Invite => Some(GroupAdminRights::INVITE_MEMBERS),
+            Self::Kick | Self::Mute => Some(GroupAdminRights::BAN_MEMBERS),
+            Self::Pin => Some(GroupAdminRights::PIN_MESSAGES),
+            Self::EditInfo => Some(GroupAdminRights::CHANGE_INFO),
+            Self::DeleteMessages => Some(GroupAdminRights::DELETE_MESSAGES),
+            Self::ManageApprovals => Some(GroupAdminRights::MANAGE_APPROVALS),
+            Self::Post | Self::Forward | Self::AddFriend => None,
+        }
+    }
+}
+
+/// 操作者在群内的状态。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct GroupMemberState {
+    /// 是否仍是群成员
+    pub is_member: bool,
+    /// 是否处于个人禁言中
+    pub muted: bool,
+    /// 管理员权限位（`GroupMemberInfo.admin_rights`），缺省见
+    /// [`GroupAdminRights::effective`]
+    pub admin_rights: Option<GroupAdminRights>,
+}
+
+impl GroupMemberState {
+    /// 普通在群、未禁言的成员。
+    pub fn member() -> Self {
+        Self {
+            is_member: true,
+            muted: false,
+            admin_rights: None,
+        }
+    }
+
+    /// 由 `group/member/mute` 返回的禁言到期时间换算。
+    ///
+    /// `mute_until_ms`：`None` = 未禁言，`Some(0)` = 永久禁言（与
+    /// `GroupMemberMuteResponse` 一致），其余为到期时间（毫秒）。
+    pub fn from_mute(mute_until_ms: Option<u64>, now_ms: u64) -> Self {
+        Self {
+            is_member: true,
+            muted: mute_until_ms.is_some_and(|until| until == 0 || until > now_ms),
+            admin_rights: None,
+        }
+    }
+}
+
+/// 判定 `role` 身份、处于 `member_state` 的成员能否执行 `permission`。
+///
+/// `target_role` 仅对 [`GroupPermission::targets_member`] 的操作有意义，
+/// 这类操作缺少目标时返回 `InvalidParams`。规则：
+///
+/// - 不在群内：一律 `NotGroupMember`；
+/// - 发言：群主不受任何限制；其他人个人禁言中 → `MemberMuted`；普通成员遇到
+///   全员禁言或本群只读（`allow_member_post = false`）→ `GroupMuted`；
+/// - 管理类操作（邀请、移出、禁言、置顶、改资料、删消息、审批）：管理员还需
+///   持有对应的 [`GroupAdminRights`] 位，缺少时 → `PermissionDenied`；
+/// - 邀请：有邀请权限的管理员，或 `member_can_invite` 时的任何成员，否则
+///   `NotGroupAdmin`；
+/// - 移出 / 禁言：需管理员以上（`NotGroupAdmin`）；目标是群主 →
+///   `CannotRemoveOwner`；管理员之间互相操作只有群主可以 → `NotGroupOwner`；
+/// - 置顶、改资料、删消息、审批：管理员以上，否则 `NotGroupAdmin`；
+/// - 转发：`forbid_forward` 对所有人生效（内容保护不区分角色）→
+///   `GroupForwardForbidden`；
+/// - 加好友：管理员以上不受限，普通成员在 `allow_member_add_friend = false`
+///   时 → `GroupAddFriendDisabled`。
+pub fn evaluate(
+    permission: GroupPermission,
+    role: GroupMemberRole,
+    settings: &GroupSettingsData,
+    member_state: GroupMemberState,
+    target_role: Option<GroupMemberRole>,
+) -> Result<(), ErrorCode> {
+    if !member_state.is_member {
+        return Err(ErrorCode::NotGroupMember);
+    }
+    let is_owner = role == GroupMemberRole::Owner;
+    let can_manage = role.can_manage();
+    let rights = GroupAdminRights::effective(role, member_state.admin_rights);
+    // 管理员身份够、但缺少这一项权限位
+    let admin_lacks_right = |permission: GroupPermission| {
+        permission
+            .required_admin_right()
+            .is_some_and(|right| can_manage && !rights.contains(right))
+    };
+
+    match permission {
+        GroupPermission::Post => {
+            if is_owner {
+                Ok(())
+            } else if member_state.muted {
+                Err(ErrorCode::MemberMuted)
+            } else if !can_manage && (settings.all_muted || !settings.allow_member_post) {
+                Err(ErrorCode::GroupMuted)
+            } else {
+                Ok(())
+            }
+        }
+        GroupPermission::Invite => {
+            if settings.member_can_invite || (can_manage && !admin_lacks_right(permission)) {
+                Ok(())
+            } else if can_manage {
+                Err(ErrorCode::PermissionDenied)
+            } else {
+                Err(ErrorCode::NotGroupAdmin)
+            }
+        }
+        GroupPermission::Kick | GroupPermission::Mute => {
+            let target = target_role.ok_or(ErrorCode::InvalidParams)?;
+            if !can_manage {
+                Err(ErrorCode::NotGroupAdmin)
+            } else if admin_lacks_right(permission) {
+                Err(ErrorCode::PermissionDenied)
+            } else if target == GroupMemberRole::Owner {
+                Err(ErrorCode::CannotRemoveOwner)
+            } else if target == GroupMemberRole::Admin && !is_owner {
+                Err(ErrorCode::NotGroupOwner)
+            } else {
+                Ok(())
+            }
+        }
+        GroupPermission::Pin
+        | GroupPermission::EditInfo
+        | GroupPermission::DeleteMessages
+        | GroupPermission::ManageApprovals => {
+            if !can_manage {
+                Err(ErrorCode::NotGroupAdmin)
+            } else if admin_lacks_right(permission) {
+                Err(ErrorCode::PermissionDenied)
+            } else {
+                Ok(())
+            }
+        }
+        GroupPermission::Forward => {
+            if settings.forbid_forward {
+                Err(ErrorCode::GroupForwardForbidden)
+            } else {
+                Ok(())
+            }
+        }
+        GroupPermission::AddFriend => {
+            if can_manage || settings.allow_member_add_friend {
+                Ok(())
+            } else {
+                Err(ErrorCode::GroupAddFriendDisabled)
+            }
+        }
+    }
+}
+
+/// 一条待发送消息中与成员限制相关的内容特征。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PostContent {
+    /// 含图片 / 文件 / 语音 / 视频 / 贴纸（含合并转发里的）
+    pub has_media: bool,
+    /// 含链接（链接卡片、正文中的 URL、URL 按钮，或旧版转发引用）
+    pub has_links: bool,
+}
+
+impl PostContent {
+    /// 从消息载荷提取；合并转发会递归检查每条被转发的消息。旧版转发引用
+    /// （`ForwardMetadata.messages`）的 `extra` 不透明、无法检查，保守地按链接算。
+    pub fn of(envelope: &MessagePayloadEnvelope) -> Self {
+        let mut content = Self {
+            has_media: false,
+            has_links: contains_url(&envelope.content),
+        };
+        match &envelope.metadata {
+            Some(
+                MessageMetadata::Image(_)
+                | MessageMetadata::File(_)
+                | MessageMetadata::Voice(_)
+                | MessageMetadata::Video(_)
+                | MessageMetadata::Sticker(_),
+            ) => content.has_media = true,
+            Some(MessageMetadata::Link(_)) => content.has_links = true,
+            Some(MessageMetadata::Interactive(m)) => {
+                content.has_links |= m
+                    .rows
+                    .iter()
+                    .flatten()
+                    .any(|button| matches!(button.action, InlineButtonAction::Url { .. }));
+            }
+            Some(MessageMetadata::Forward(m)) => {
+                content.has_links |= !m.messages.is_empty();
+                for item in &m.items {
+                    let inner = Self::of(&item.payload);
+                    content.has_media |= inner.has_media;
+                    content.has_links |= inner.has_links;
+                }
+            }
+            _ => {}
+        }
+        content
+    }
+}
+
+fn contains_url(text: &str) -> bool {
+    let lower = text.to_ascii_lowercase();
+    lower.contains("http://") || lower.contains("https://") || lower.contains("www.")
+}
+
+/// [`evaluate_post`] 的拒绝结果。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PostDenied {
+    /// server 应返回的错误码
+    pub code: ErrorCode,
+    /// 多少毫秒后可以重试；`None` = 不会自动解除（永久限制、全员禁言等）
+    pub retry_after_ms: Option<u64>,
+}
+
+impl std::fmt::Display for PostDenied {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.retry_after_ms {
+            Some(ms) => write!(f, "{} ({}ms 后重试)", self.code.message(), ms),
+            None => f.write_str(self.code.message()),
+        }
+    }
+}
+
+impl From<PostDenied> for crate::RpcError {
+    /// 带上 `retry_after` 详情，客户端据此显示倒计时。
+    fn from(denied: PostDenied) -> Self {
+        Self::new(
+            denied.code,
+            denied.retry_after_ms.map(crate::ErrorDetail::retry_after),
+        )
+    }
+}
+
+/// 判定能否在群内发出一条具体消息。
+///
+/// 依次检查：
+///
+/// 1. [`evaluate`]`(Post)`，其中 `restriction.mute` 生效等同于个人禁言；
+///    有到期时间的禁言带上剩余时间；
+/// 2. 非管理员的 `no_media` / `no_links` 限制 → `MemberMediaRestricted` /
+///    `MemberLinksRestricted`（群主、管理员的限制只有禁言生效）；
+/// 3. 非管理员的慢速模式：距上次发言（`last_post_at_ms`）不足
+///    `slow_mode_secs` → `GroupSlowMode`，并带上还需等待的时间。
+pub fn evaluate_post(
+    role: GroupMemberRole,
+    settings: &GroupSettingsData,
+    member_state: GroupMemberState,
+    restriction: Option<&GroupMemberRestriction>,
+    last_post_at_ms: Option<u64>,
+    content: PostContent,
+    now_ms: u64,
+) -> Result<(), PostDenied> {
+    let restriction = restriction.filter(|r| r.is_active(now_ms));
+    let restricted_mute = restriction.is_some_and(|r| r.mute);
+    let state = GroupMemberState {
+        muted: member_state.muted || restricted_mute,
+        ..member_state
+    };
+    evaluate(GroupPermission::Post, role, settings, state, None).map_err(|code| PostDenied {
+        code,
+        retry_after_ms: if code == ErrorCode::MemberMuted && !member_state.muted {
+            restriction.and_then(|r| r.remaining_ms(now_ms))
+        } else {
+            None
+        },
+    })?;
+
+    if role.can_manage() {
+        return Ok(());
+    }
+    if let Some(r) = restriction {
+        let code = if r.no_media && content.has_media {
+            Some(ErrorCode::MemberMediaRestricted)
+        } else if r.no_links && content.has_links {
+            Some(ErrorCode::MemberLinksRestricted)
+        } else {
+            None
+        };
+        if let Some(code) = code {
+            return Err(PostDenied {
+                code,
+                retry_after_ms: r.remaining_ms(now_ms),
+            });
+        }
+    }
+    if let Some(last) = last_post_at_ms.filter(|_| settings.slow_mode_secs > 0) {
+        let next = last.saturating_add(u64::from(settings.slow_mode_secs) * 1000);
+        if next > now_ms {
+            return Err(PostDenied {
+                code: ErrorCode::GroupSlowMode,
+                retry_after_ms: Some(next - now_ms),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// 最早可以发出这条消息的时间（毫秒）：现在可发则为 `now_ms`；存在不会
+/// 自动解除的限制时为 `None`。客户端用它显示倒计时。
+pub fn next_post_at(
+    role: GroupMemberRole,
+    settings: &GroupSettingsData,
+    member_state: GroupMemberState,
+    restriction: Option<&GroupMemberRestriction>,
+    last_post_at_ms: Option<u64>,
+    content: PostContent,
+    now_ms: u64,
+) -> Option<u64> {
+    // 每轮至少解除一项限制（禁言 / 内容限制 → 慢速模式），几轮即可收敛
+    let mut at = now_ms;
+    for _ in 0..4 {
+        match evaluate_post(
+            role,
+            settings,
+            member_state,
+            restriction,
+            last_post_at_ms,
+            content,
+            at,
+        ) {
+            Ok(()) => return Some(at),
+            Err(denied) => at = at.saturating_add(denied.retry_after_ms?),
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use GroupMemberRole::{Admin, Member, Owner};
+    use GroupPermission::*;
+
+    fn open_settings() -> GroupSettingsData {
+        GroupSettingsData {
+            join_need_approval: false,
+            member_can_invite: true,
+            allow_member_add_friend: true,
+            allow_search: true,
+            join_policy: 1,
+            all_muted: false,
+            allow_member_post: true,
+            forbid_forward: false,
+            slow_mode_secs: 0,
+            max_members: 500,
+            announcement: None,
+            description: None,
+            created_at: 0,
+            updated_at: 0,
+        }
+    }
+
+    fn locked_settings() -> GroupSettingsData {
+        GroupSettingsData {
+            member_can_invite: false,
+            allow_member_add_friend: false,
+            all_muted: true,
+            allow_member_post: false,
+            forbid_forward: true,
+            ..open_settings()
+        }
+    }
+
+    /// (操作, 角色, 群设置, 成员状态, 目标角色, 期望结果)
+    type Case<'a> = (
+        GroupPermission,
+        GroupMemberRole,
+        &'a GroupSettingsData,
+        GroupMemberState,
+        Option<GroupMemberRole>,
+        Result<(), ErrorCode>,
+    );
+
+    const OK: Result<(), ErrorCode> = Ok(());
+    const MUTED: GroupMemberState = GroupMemberState {
+        is_member: true,
+        muted: true,
+        admin_rights: None,
+    };
+
+    #[test]
+    fn permission_matrix() {
+        let open = open_settings();
+        let locked = locked_settings();
+        let read_only = GroupSettingsData {
+            allow_member_post: false,
+            ..open_settings()
+        };
+        let member = GroupMemberState::member();
+        let gone = GroupMemberState::default();
+
+        #[rustfmt::skip]
+        let cases: &[Case] = &[
+            // 发言
+            (Post, Member, &open, member, None, OK),
+            (Post, Member, &locked, member, None, Err(ErrorCode::GroupMuted)),
+            (Post, Member, &read_only, member, None, Err(ErrorCode::GroupMuted)),
+            (Post, Member, &open, MUTED, None, Err(ErrorCode::MemberMuted)),
+            (Post, Admin, &locked, member, None, OK),
+            (Post, Admin, &open, MUTED, None, Err(ErrorCode::MemberMuted)),
+            (Post, Owner, &locked, MUTED, None, OK),
+            (Post, Owner, &open, gone, None, Err(ErrorCode::NotGroupMember)),
+            // 邀请
+            (Invite, Member, &open, member, None, OK),
+            (Invite, Member, &locked, member, None, Err(ErrorCode::NotGroupAdmin)),
+            (Invite, Admin, &locked, member, None, OK),
+            (Invite, Owner, &locked, member, None, OK),
+            // 移出
+            (Kick, Member, &open, member, Some(Member), Err(ErrorCode::NotGroupAdmin)),
+            (Kick, Admin, &open, member, Some(Member), OK),
+            (Kick, Admin, &open, member, Some(Admin), Err(ErrorCode::NotGroupOwner)),
+            (Kick, Admin, &open, member, Some(Owner), Err(ErrorCode::CannotRemoveOwner)),
+            (Kick, Owner, &open, member, Some(Admin), OK),
+            (Kick, Owner, &open, member, Some(Owner), Err(ErrorCode::CannotRemoveOwner)),
+            (Kick, Owner, &open, member, None, Err(ErrorCode::InvalidParams)),
+            // 禁言
+            (Mute, Member, &open, member, Some(Member), Err(ErrorCode::NotGroupAdmin)),
+            (Mute, Admin, &open, MUTED, Some(Member), OK),
+            (Mute, Admin, &open, member, Some(Admin), Err(ErrorCode::NotGroupOwner)),
+            (Mute, Owner, &open, member, Some(Admin), OK),
+            (Mute, Owner, &open, member, Some(Owner), Err(ErrorCode::CannotRemoveOwner)),
+            // 置顶 / 改资料
+            (Pin, Member, &open, member, None, Err(ErrorCode::NotGroupAdmin)),
+            (Pin, Admin, &locked, member, None, OK),
+            (EditInfo, Member, &open, member, None, Err(ErrorCode::NotGroupAdmin)),
+            (EditInfo, Owner, &locked, member, None, OK),
+            // 转发
+            (Forward, Member, &open, MUTED, None, OK),
+            (Forward, Member, &locked, member, None, Err(ErrorCode::GroupForwardForbidden)),
+            (Forward, Owner, &locked, member, None, Err(ErrorCode::GroupForwardForbidden)),
+            // 加好友
+            (AddFriend, Member, &open, member, None, OK),
+            (AddFriend, Member, &locked, member, None, Err(ErrorCode::GroupAddFriendDisabled)),
+            (AddFriend, Admin, &locked, member, None, OK),
+            (AddFriend, Member, &open, gone, None, Err(ErrorCode::NotGroupMember)),
+        ];
+
+        for (permission, role, settings, state, target, expected) in cases {
+            assert_eq!(
+                evaluate(*permission, *role, settings, *state, *target),
+                *expected,
+                "{permission:?} role={role:?} state={state:?} target={target:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn non_members_are_always_rejected() {
+        let settings = open_settings();
+        for permission in [
+            Post,
+            Invite,
+            Kick,
+            Mute,
+            Pin,
+            EditInfo,
+            Forward,
+            AddFriend,
+            DeleteMessages,
+            ManageApprovals,
+        ] {
+            for role in [Owner, Admin, Member] {
+                assert_eq!(
+                    evaluate(
+                        permission,
+                        role,
+                        &settings,
+                        GroupMemberState::default(),
+                        Some(Member)
+                    ),
+                    Err(ErrorCode::NotGroupMember)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn admin_rights_gate_management_actions() {
+        let open = open_settings();
+        let locked = locked_settings();
+        let pin_only = GroupMemberState {
+            admin_rights: Some(GroupAdminRights::PIN_MESSAGES),
+            ..GroupMemberState::member()
+        };
+        let member = GroupMemberState::member();
+
+        #[rustfmt::skip]
+        let cases: &[Case] = &[
+            (Pin, Admin, &open, pin_only, None, OK),
+            (EditInfo, Admin, &open, pin_only, None, Err(ErrorCode::PermissionDenied)),
+            (Kick, Admin, &open, pin_only, Some(Member), Err(ErrorCode::PermissionDenied)),
+            (Mute, Admin, &open, pin_only, Some(Member), Err(ErrorCode::PermissionDenied)),
+            (DeleteMessages, Admin, &open, pin_only, None, Err(ErrorCode::PermissionDenied)),
+            (ManageApprovals, Admin, &open, pin_only, None, Err(ErrorCode::PermissionDenied)),
+            // 缺邀请权限的管理员仍可以像普通成员一样邀请
+            (Invite, Admin, &open, pin_only, None, OK),
+            (Invite, Admin, &locked, pin_only, None, Err(ErrorCode::PermissionDenied)),
+            // 未下发权限位的管理员 = 全部权限
+            (DeleteMessages, Admin, &open, member, None, OK),
+            (ManageApprovals, Admin, &open, member, None, OK),
+            (DeleteMessages, Member, &open, member, None, Err(ErrorCode::NotGroupAdmin)),
+            // 群主不受权限位影响；普通成员带权限位也不生效
+            (Kick, Owner, &open, pin_only, Some(Admin), OK),
+            (Pin, Member, &open, pin_only, None, Err(ErrorCode::NotGroupAdmin)),
+        ];
+
+        for (permission, role, settings, state, target, expected) in cases {
+            assert_eq!(
+                evaluate(*permission, *role, settings, *state, *target),
+                *expected,
+                "{permission:?} role={role:?} state={state:?} target={target:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn mute_state_from_expiry() {
+        assert!(!GroupMemberState::from_mute(None, 1_000).muted);
+        assert!(GroupMemberState::from_mute(Some(0), 1_000).muted);
+        assert!(GroupMemberState::from_mute(Some(1_001), 1_000).muted);
+        assert!(!GroupMemberState::from_mute(Some(1_000), 1_000).muted);
+    }
+
+    fn post(
+        role: GroupMemberRole,
+        settings: &GroupSettingsData,
+        restriction: Option<&GroupMemberRestriction>,
+        last_post_at_ms: Option<u64>,
+        content: PostContent,
+        now_ms: u64,
+    ) -> Result<(), PostDenied> {
+        evaluate_post(
+            role,
+            settings,
+            GroupMemberState::member(),
+            restriction,
+            last_post_at_ms,
+            content,
+            now_ms,
+        )
+    }
+
+    const TEXT: PostContent = PostContent {
+        has_media: false,
+        has_links: false,
+    };
+
+    #[test]
+    fn timed_mute_reports_remaining_time() {
+        let open = open_settings();
+        let muted = GroupMemberRestriction {
+            mute: true,
+            until: 10_000,
+            ..Default::default()
+        };
+        assert_eq!(
+            post(Member, &open, Some(&muted), None, TEXT, 4_000),
+            Err(PostDenied {
+                code: ErrorCode::MemberMuted,
+                retry_after_ms: Some(6_000)
+            })
+        );
+        assert_eq!(
+            post(Member, &open, Some(&muted), None, TEXT, 10_000),
+            Ok(())
+        );
+        assert_eq!(post(Owner, &open, Some(&muted), None, TEXT, 4_000), Ok(()));
+
+        let forever = GroupMemberRestriction { until: 0, ..muted };
+        assert_eq!(
+            post(Member, &open, Some(&forever), None, TEXT, 4_000)
+                .unwrap_err()
+                .retry_after_ms,
+            None
+        );
+    }
+
+    #[test]
+    fn content_restrictions_apply_to_members_only() {
+        let open = open_settings();
+        let no_media = GroupMemberRestriction {
+            no_media: true,
+            no_links: true,
+            until: 5_000,
+            ..Default::default()
+        };
+        let image = PostContent {
+            has_media: true,
+            has_links: false,
+        };
+        let link = PostContent {
+            has_media: false,
+            has_links: true,
+        };
+
+        assert_eq!(post(Member, &open, Some(&no_media), None, TEXT, 0), Ok(()));
+        assert_eq!(
+            post(Member, &open, Some(&no_media), None, image, 0),
+            Err(PostDenied {
+                code: ErrorCode::MemberMediaRestricted,
+                retry_after_ms: Some(5_000)
+            })
+        );
+        assert_eq!(
+            post(Member, &open, Some(&no_media), None, link, 0)
+                .unwrap_err()
+                .code,
+            ErrorCode::MemberLinksRestricted
+        );
+        assert_eq!(post(Admin, &open, Some(&no_media), None, image, 0), Ok(()));
+    }
+
+    #[test]
+    fn post_denied_becomes_rpc_error_with_retry_after() {
+        let denied = PostDenied {
+            code: ErrorCode::GroupSlowMode,
+            retry_after_ms: Some(2_000),
+        };
+        let err = crate::RpcError::from(denied);
+        assert_eq!(err.error_code(), Some(ErrorCode::GroupSlowMode));
+        assert_eq!(err.retry_after_ms(), Some(2_000));
+    }
+
+    #[test]
+    fn slow_mode_spaces_member_posts() {
+        let slow = GroupSettingsData {
+            slow_mode_secs: 30,
+            ..open_settings()
+        };
+        assert_eq!(post(Member, &slow, None, None, TEXT, 1_000), Ok(()));
+        assert_eq!(
+            post(Member, &slow, None, Some(1_000), TEXT, 11_000),
+            Err(PostDenied {
+                code: ErrorCode::GroupSlowMode,
+                retry_after_ms: Some(20_000)
+            })
+        );
+        assert_eq!(post(Member, &slow, None, Some(1_000), TEXT, 31_000), Ok(()));
+        assert_eq!(post(Admin, &slow, None, Some(1_000), TEXT, 11_000), Ok(()));
+    }
+
+    #[test]
+    fn next_post_at_chains_restrictions() {
+        let slow = GroupSettingsData {
+            slow_mode_secs: 60,
+            ..open_settings()
+        };
+        let muted = GroupMemberRestriction {
+            mute: true,
+            until: 10_000,
+            ..Default::default()
+        };
+        let state = GroupMemberState::member();
+        // 禁言到 10s，再等慢速模式到 0 + 60s
+        assert_eq!(
+            next_post_at(Member, &slow, state, Some(&muted), Some(0), TEXT, 1_000),
+            Some(60_000)
+        );
+        assert_eq!(
+            next_post_at(Member, &slow, state, None, None, TEXT, 1_000),
+            Some(1_000)
+        );
+        assert_eq!(
+            next_post_at(Member, &locked_settings(), state, None, None, TEXT, 1_000),
+            None
+        );
+    }
+
+    #[test]
+    fn post_content_detects_media_and_links() {
+        use crate::{
+            ForwardBundleItem, ForwardMessageRef, ForwardMetadata, ImageMetadata, InlineButton,
+            InteractiveMetadata,
+        };
+
+        let text = |s: &str| MessagePayloadEnvelope {
+            content: s.to_string(),
+            ..Default::default()
+        };
+        assert_eq!(PostContent::of(&text("hello")), TEXT);
+        assert!(PostContent::of(&text("see https://privchat.dev")).has_links);
+        assert!(PostContent::of(&text("WWW.example.com")).has_links);
+
+        let image = MessagePayloadEnvelope {
+            metadata: Some(MessageMetadata::Image(ImageMetadata::default())),
+            ..Default::default()
+        };
+        let bundle = MessagePayloadEnvelope {
+            metadata: Some(MessageMetadata::Forward(ForwardMetadata {
+                messages: Vec::new(),
+                items: vec![ForwardBundleItem {
+                    original_message_id: None,
+                    sender_id: 1,
+                    sender_name: String::new(),
+                    timestamp: 0,
+                    message_type: crate::message::ContentMessageType::Image,
+                    payload: image,
+                }],
+            })),
+            ..Default::default()
+        };
+        assert!(PostContent::of(&bundle).has_media);
+
+        let keyboard = |action| MessagePayloadEnvelope {
+            metadata: Some(MessageMetadata::Interactive(InteractiveMetadata {
+                rows: vec![vec![InlineButton {
+                    text: "open".to_string(),
+                    action,
+                }]],
+            })),
+            ..Default::default()
+        };
+        assert!(
+            PostContent::of(&keyboard(InlineButtonAction::Url {
+                url: "https://privchat.dev".to_string()
+            }))
+            .has_links
+        );
+        assert!(
+            !PostContent::of(&keyboard(InlineButtonAction::Callback {
+                data: "x".to_string()
+            }))
+            .has_links
+        );
+
+        let legacy = MessagePayloadEnvelope {
+            metadata: Some(MessageMetadata::Forward(ForwardMetadata {
+                messages: vec![ForwardMessageRef::default()],
+                items: Vec::new(),
+            })),
+            ..Default::default()
+        };
+        assert!(PostContent::of(&legacy).has_links);
+    }
+}
@@ -99,6 +99,10 @@ pub struct GroupQRCodeJoinRequest {
     /// 申请理由（可选）
     #[serde(skip_serializing_if = "Option::is_none")]
     pub message: Option<String>,
+    /// 入群问题的回答，按 `group/approval/questions/get` 返回的顺序一一对应；
+    /// 群未设置问题时为空。校验见 [`super::approval::validate_join_answers`]。
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub answers: Vec<String>,
     /// 用户 ID（服务器端填充，客户端不可设置）
     #[serde(skip_deserializing, default)]
     pub user_id: u64,
@@ -105,9 +105,99 @@ impl Default for GroupMemberRole {
     }
 }
 
+/// 管理员自定义头衔最大长度（字符数）。
+pub const GROUP_ADMIN_TITLE_MAX_CHARS: usize = 16;
+
+/// 管理员权限位集合，附在 `Admin` 成员上。
+///
+/// **线上编码**：`u32` 位掩码，位定义冻结，只追加不重排。
+///
+/// **未知位一律丢弃**：老客户端不认识的新权限位按「没有」处理——和
+/// [`GroupMemberRole::from_wire_i32`] 一样，看不懂就往下猜，绝不往上猜。
+/// 反序列化也走这条路径，所以任何从线上读进来的值都已经去掉了未知位。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(from = "u32", into = "u32")]
+pub struct GroupAdminRights(u32);
+
+impl GroupAdminRights {
+    /// 删除他人消息
+    pub const DELETE_MESSAGES: Self = Self(1 << 0);
+    /// 移出 / 禁言成员
+    pub const BAN_MEMBERS: Self = Self(1 << 1);
+    /// 邀请成员（不受 `member_can_invite` 限制）
+    pub const INVITE_MEMBERS: Self = Self(1 << 2);
+    /// 置顶消息
+    pub const PIN_MESSAGES: Self = Self(1 << 3);
+    /// 修改群资料 / 群设置
+    pub const CHANGE_INFO: Self = Self(1 << 4);
+    /// 处理入群申请
+    pub const MANAGE_APPROVALS: Self = Self(1 << 5);
+
+    /// 空集合
+    pub const NONE: Self = Self(0);
+    /// 当前协议版本认识的全部权限
+    pub const ALL: Self = Self(0b11_1111);
+
+    /// 从线上数值解析，丢弃未知位。
+    pub const fn from_wire_u32(value: u32) -> Self {
+        Self(value & Self::ALL.0)
+    }
+
+    /// 线上数值。
+    pub const fn to_wire_u32(self) -> u32 {
+        self.0
+    }
+
+    pub const fn contains(self, rights: Self) -> bool {
+        self.0 & rights.0 == rights.0
+    }
+
+    pub const fn union(self, rights: Self) -> Self {
+        Self(self.0 | rights.0)
+    }
+
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// 某角色实际生效的权限。
+    ///
+    /// - 群主：恒为 [`Self::ALL`]；
+    /// - 管理员：以下发的位集合为准；**缺省**（老服务端、位集合出现之前任命的
+    ///   管理员）视为 [`Self::ALL`]，保持原有「管理员 = 全部管理权限」的行为；
+    /// - 普通成员：恒为空，即使线上带了位集合也不认。
+    pub fn effective(role: GroupMemberRole, rights: Option<Self>) -> Self {
+        match role {
+            GroupMemberRole::Owner => Self::ALL,
+            GroupMemberRole::Admin => rights.unwrap_or(Self::ALL),
+            GroupMemberRole::Member => Self::NONE,
+        }
+    }
+}
+
+impl From<u32> for GroupAdminRights {
+    fn from(value: u32) -> Self {
+        Self::from_wire_u32(value)
+    }
+}
+
+impl From<GroupAdminRights> for u32 {
+    fn from(rights: GroupAdminRights) -> Self {
+        rights.to_wire_u32()
+    }
+}
+
+impl std::ops::BitOr for GroupAdminRights {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        self.union(rhs)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::GroupMemberRole;
+    use super::{GroupAdminRights, GroupMemberRole};
 
     #[test]
     fn wire_numbers_are_frozen() {
@@ -170,6 +260,45 @@ mod tests {
         }
     }
 
+    #[test]
+    fn admin_rights_drop_unknown_bits() {
+        assert_eq!(
+            GroupAdminRights::from_wire_u32(u32::MAX),
+            GroupAdminRights::ALL
+        );
+        assert_eq!(
+            GroupAdminRights::from_wire_u32(1 << 31),
+            GroupAdminRights::NONE
+        );
+        let rights: GroupAdminRights = serde_json::from_str("4294967295").unwrap();
+        assert_eq!(rights, GroupAdminRights::ALL);
+        let pin_and_invite = GroupAdminRights::PIN_MESSAGES | GroupAdminRights::INVITE_MEMBERS;
+        assert_eq!(serde_json::to_string(&pin_and_invite).unwrap(), "12");
+        assert!(pin_and_invite.contains(GroupAdminRights::PIN_MESSAGES));
+        assert!(!pin_and_invite.contains(GroupAdminRights::BAN_MEMBERS));
+    }
+
+    #[test]
+    fn effective_rights_never_promote_members() {
+        use GroupMemberRole::*;
+        assert_eq!(
+            GroupAdminRights::effective(Owner, Some(GroupAdminRights::NONE)),
+            GroupAdminRights::ALL
+        );
+        assert_eq!(
+            GroupAdminRights::effective(Admin, None),
+            GroupAdminRights::ALL
+        );
+        assert_eq!(
+            GroupAdminRights::effective(Admin, Some(GroupAdminRights::PIN_MESSAGES)),
+            GroupAdminRights::PIN_MESSAGES
+        );
+        assert_eq!(
+            GroupAdminRights::effective(Member, Some(GroupAdminRights::ALL)),
+            GroupAdminRights::NONE
+        );
+    }
+
     #[test]
     fn only_owner_and_admin_manage() {
         assert!(GroupMemberRole::Owner.can_manage());
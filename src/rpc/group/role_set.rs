@@ -20,6 +20,8 @@
 /// RPC路由: `group/role/set`
 use serde::{Deserialize, Serialize};
 
+use super::role::{GroupAdminRights, GroupMemberRole, GROUP_ADMIN_TITLE_MAX_CHARS};
+
 /// 设置群组成员角色请求
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GroupRoleSetRequest {
@@ -31,6 +33,37 @@ pub struct GroupRoleSetRequest {
     pub user_id: u64,
     /// 目标角色: "admin" | "member"
     pub role: String,
+    /// 管理员权限位（仅 `role = "admin"` 时有效）；缺省 = 全部管理权限
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub admin_rights: Option<GroupAdminRights>,
+    /// 管理员自定义头衔（仅 `role = "admin"` 时有效），最多
+    /// [`GROUP_ADMIN_TITLE_MAX_CHARS`] 个字符；空串 = 清除
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub admin_title: Option<String>,
+}
+
+impl GroupRoleSetRequest {
+    /// 校验请求：只能设为管理员或普通成员，权限位与头衔只能给管理员。
+    pub fn validate(&self) -> Result<GroupMemberRole, crate::ErrorCode> {
+        let role = GroupMemberRole::from_wire_str(&self.role);
+        let normalized = self.role.trim().to_ascii_lowercase();
+        if role == GroupMemberRole::Owner || normalized != role.as_str() {
+            return Err(crate::ErrorCode::InvalidParams);
+        }
+        if role == GroupMemberRole::Member
+            && (self.admin_rights.is_some() || self.admin_title.is_some())
+        {
+            return Err(crate::ErrorCode::InvalidParams);
+        }
+        if self
+            .admin_title
+            .as_ref()
+            .is_some_and(|title| title.chars().count() > GROUP_ADMIN_TITLE_MAX_CHARS)
+        {
+            return Err(crate::ErrorCode::InvalidParams);
+        }
+        Ok(role)
+    }
 }
 
 /// 设置群组成员角色响应
@@ -42,4 +75,58 @@ pub struct GroupRoleSetResponse {
     pub user_id: u64,
     pub role: String,
     pub updated_at: Option<u64>, // Unix 毫秒时间戳，可选
+    /// 生效的管理员权限位（普通成员缺省）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub admin_rights: Option<GroupAdminRights>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub admin_title: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(role: &str) -> GroupRoleSetRequest {
+        GroupRoleSetRequest {
+            group_id: 1,
+            operator_id: 2,
+            user_id: 3,
+            role: role.to_string(),
+            admin_rights: None,
+            admin_title: None,
+        }
+    }
+
+    #[test]
+    fn legacy_request_without_rights_still_parses() {
+        let request: GroupRoleSetRequest = serde_json::from_value(serde_json::json!({
+            "group_id": 1, "operator_id": 2, "user_id": 3, "role": "admin"
+        }))
+        .unwrap();
+        assert_eq!(request.admin_rights, None);
+        assert_eq!(request.validate(), Ok(GroupMemberRole::Admin));
+    }
+
+    #[test]
+    fn validate_rejects_owner_unknown_roles_and_member_rights() {
+        assert_eq!(
+            request("owner").validate(),
+            Err(crate::ErrorCode::InvalidParams)
+        );
+        assert_eq!(
+            request("god").validate(),
+            Err(crate::ErrorCode::InvalidParams)
+        );
+        let mut demote = request("member");
+        assert_eq!(demote.validate(), Ok(GroupMemberRole::Member));
+        demote.admin_rights = Some(GroupAdminRights::PIN_MESSAGES);
+        assert_eq!(demote.validate(), Err(crate::ErrorCode::InvalidParams));
+
+        let mut admin = request("admin");
+        admin.admin_rights = Some(GroupAdminRights::BAN_MEMBERS);
+        admin.admin_title = Some("版主".to_string());
+        assert_eq!(admin.validate(), Ok(GroupMemberRole::Admin));
+        admin.admin_title = Some("x".repeat(GROUP_ADMIN_TITLE_MAX_CHARS + 1));
+        assert_eq!(admin.validate(), Err(crate::ErrorCode::InvalidParams));
+    }
 }
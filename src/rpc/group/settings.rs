@@ -28,10 +28,20 @@ fn default_join_policy() -> u8 {
     1
 }
 
+/// 群成员上限的取值范围
+pub const GROUP_MAX_MEMBERS_MIN: usize = 2;
+pub const GROUP_MAX_MEMBERS_LIMIT: usize = 100_000;
+/// 慢速模式间隔上限（秒）
+pub const GROUP_SLOW_MODE_MAX_SECS: u32 = 86_400;
+/// 群公告最大长度（字符数）
+pub const GROUP_ANNOUNCEMENT_MAX_CHARS: usize = 2048;
+/// 群描述最大长度（字符数）
+pub const GROUP_DESCRIPTION_MAX_CHARS: usize = 512;
+
 /// 更新群组设置请求
 ///
 /// RPC路由: `group/settings/update`
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct GroupSettingsPatch {
     /// 是否开启加群审批（可选）
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -63,17 +73,184 @@ pub struct GroupSettingsPatch {
     /// 只拦截**之后**的转发；已经存在的副本是目标会话里的独立消息，不追溯。
     #[serde(skip_serializing_if = "Option::is_none")]
     pub forbid_forward: Option<bool>,
+    /// 慢速模式（可选）：普通成员两次发言的最小间隔（秒），0 = 关闭
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slow_mode_secs: Option<u32>,
     /// 最大成员数（可选）
     #[serde(skip_serializing_if = "Option::is_none")]
     pub max_members: Option<u32>,
-    /// 群公告（可选）
+    /// 群公告（可选），空串 = 清除
     #[serde(skip_serializing_if = "Option::is_none")]
     pub announcement: Option<String>,
-    /// 群描述（可选）
+    /// 群描述（可选），空串 = 清除
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
 }
 
+impl GroupSettingsPatch {
+    /// 是否没有任何更新项
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// 校验取值范围（不涉及当前成员数，见 [`GroupSettingsData::apply`]）。
+    pub fn validate(&self) -> Result<(), crate::ErrorCode> {
+        if self.join_policy.is_some_and(|policy| policy > 2) {
+            return Err(crate::ErrorCode::InvalidParams);
+        }
+        if self
+            .slow_mode_secs
+            .is_some_and(|secs| secs > GROUP_SLOW_MODE_MAX_SECS)
+        {
+            return Err(crate::ErrorCode::InvalidParams);
+        }
+        if self.max_members.is_some_and(|max| {
+            !(GROUP_MAX_MEMBERS_MIN..=GROUP_MAX_MEMBERS_LIMIT).contains(&(max as usize))
+        }) {
+            return Err(crate::ErrorCode::InvalidParams);
+        }
+        let too_long = |text: &Option<String>, max: usize| {
+            text.as_ref().is_some_and(|text| text.chars().count() > max)
+        };
+        if too_long(&self.announcement, GROUP_ANNOUNCEMENT_MAX_CHARS)
+            || too_long(&self.description, GROUP_DESCRIPTION_MAX_CHARS)
+        {
+            return Err(crate::ErrorCode::InvalidParams);
+        }
+        Ok(())
+    }
+}
+
+/// 一项群设置变更（字段级旧值 / 新值）。
+///
+/// 线上形态：`{"field": "all_muted", "old": false, "new": true}`。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "field", rename_all = "snake_case")]
+pub enum GroupSettingChange {
+    JoinNeedApproval {
+        old: bool,
+        new: bool,
+    },
+    MemberCanInvite {
+        old: bool,
+        new: bool,
+    },
+    AllowMemberAddFriend {
+        old: bool,
+        new: bool,
+    },
+    AllowSearch {
+        old: bool,
+        new: bool,
+    },
+    JoinPolicy {
+        old: u8,
+        new: u8,
+    },
+    AllMuted {
+        old: bool,
+        new: bool,
+    },
+    AllowMemberPost {
+        old: bool,
+        new: bool,
+    },
+    ForbidForward {
+        old: bool,
+        new: bool,
+    },
+    SlowModeSecs {
+        old: u32,
+        new: u32,
+    },
+    MaxMembers {
+        old: usize,
+        new: usize,
+    },
+    Announcement {
+        old: Option<String>,
+        new: Option<String>,
+    },
+    Description {
+        old: Option<String>,
+        new: Option<String>,
+    },
+    /// 新版本引入、本端不认识的字段（前向兼容）
+    #[serde(other)]
+    Unknown,
+}
+
+impl GroupSettingChange {
+    /// 字段名（与 `GroupSettingsData` 的 JSON 字段一致）
+    pub fn field(&self) -> &'static str {
+        match self {
+            Self::JoinNeedApproval { .. } => "join_need_approval",
+            Self::MemberCanInvite { .. } => "member_can_invite",
+            Self::AllowMemberAddFriend { .. } => "allow_member_add_friend",
+            Self::AllowSearch { .. } => "allow_search",
+            Self::JoinPolicy { .. } => "join_policy",
+            Self::AllMuted { .. } => "all_muted",
+            Self::AllowMemberPost { .. } => "allow_member_post",
+            Self::ForbidForward { .. } => "forbid_forward",
+            Self::SlowModeSecs { .. } => "slow_mode_secs",
+            Self::MaxMembers { .. } => "max_members",
+            Self::Announcement { .. } => "announcement",
+            Self::Description { .. } => "description",
+            Self::Unknown => "unknown",
+        }
+    }
+
+    /// 默认中文描述（不含操作者），如「开启了全员禁言」。
+    pub fn describe_cn(&self) -> String {
+        let toggle =
+            |on: bool, what: &str| format!("{}了{}", if on { "开启" } else { "关闭" }, what);
+        match self {
+            Self::JoinNeedApproval { new, .. } => toggle(*new, "入群审批"),
+            Self::MemberCanInvite { new, .. } => toggle(*new, "成员邀请"),
+            Self::AllowMemberAddFriend { new, .. } => {
+                if *new {
+                    "允许成员互加好友".to_string()
+                } else {
+                    "禁止成员互加好友".to_string()
+                }
+            }
+            Self::AllowSearch { new, .. } => toggle(*new, "群搜索"),
+            Self::JoinPolicy { new, .. } => match new {
+                0 => "关闭了入群申请".to_string(),
+                2 => "允许直接入群".to_string(),
+                _ => "设置入群需审核".to_string(),
+            },
+            Self::AllMuted { new, .. } => toggle(*new, "全员禁言"),
+            Self::AllowMemberPost { new, .. } => {
+                if *new {
+                    "允许成员发言".to_string()
+                } else {
+                    "设置仅管理员可发言".to_string()
+                }
+            }
+            Self::ForbidForward { new, .. } => {
+                if *new {
+                    "禁止转发群消息".to_string()
+                } else {
+                    "允许转发群消息".to_string()
+                }
+            }
+            Self::SlowModeSecs { new: 0, .. } => "关闭了慢速模式".to_string(),
+            Self::SlowModeSecs { new, .. } => format!("开启了慢速模式（{} 秒）", new),
+            Self::MaxMembers { new, .. } => format!("将群人数上限改为 {}", new),
+            Self::Announcement { new, .. } => match new {
+                Some(_) => "修改了群公告".to_string(),
+                None => "清除了群公告".to_string(),
+            },
+            Self::Description { new, .. } => match new {
+                Some(_) => "修改了群描述".to_string(),
+                None => "清除了群描述".to_string(),
+            },
+            Self::Unknown => "修改了群设置".to_string(),
+        }
+    }
+}
+
 /// 更新群组设置请求
 ///
 /// RPC路由: `group/settings/update`
@@ -127,7 +304,7 @@ pub struct GroupSettingsUpdateResponse {
 /// 获取群组设置响应
 ///
 /// RPC路由: `group/settings/get`
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct GroupSettingsData {
     pub join_need_approval: bool,
     pub member_can_invite: bool,
@@ -147,6 +324,9 @@ pub struct GroupSettingsData {
     /// 是否禁止转发本群消息（默认 false）
     #[serde(default)]
     pub forbid_forward: bool,
+    /// 慢速模式间隔（秒），0 = 关闭（默认 0）
+    #[serde(default)]
+    pub slow_mode_secs: u32,
     pub max_members: usize,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub announcement: Option<String>,
@@ -156,6 +336,76 @@ pub struct GroupSettingsData {
     pub updated_at: u64,
 }
 
+impl GroupSettingsData {
+    /// 应用更新，返回实际发生变化的字段（与原值相同的项不计入）。
+    ///
+    /// 校验失败时不做任何修改。`member_count` 为当前成员数，`max_members`
+    /// 不能调到它以下。`updated_at` 由调用方写入；返回列表的长度即
+    /// `GroupSettingsUpdateResponse.updated_count`。
+    pub fn apply(
+        &mut self,
+        patch: &GroupSettingsPatch,
+        member_count: usize,
+    ) -> Result<Vec<GroupSettingChange>, crate::ErrorCode> {
+        patch.validate()?;
+        if patch
+            .max_members
+            .is_some_and(|max| (max as usize) < member_count)
+        {
+            return Err(crate::ErrorCode::InvalidParams);
+        }
+
+        let mut changes = Vec::new();
+        macro_rules! apply_field {
+            ($field:ident, $variant:ident) => {
+                if let Some(new) = patch.$field {
+                    if new != self.$field {
+                        changes.push(GroupSettingChange::$variant {
+                            old: self.$field,
+                            new,
+                        });
+                        self.$field = new;
+                    }
+                }
+            };
+        }
+        apply_field!(join_need_approval, JoinNeedApproval);
+        apply_field!(member_can_invite, MemberCanInvite);
+        apply_field!(allow_member_add_friend, AllowMemberAddFriend);
+        apply_field!(allow_search, AllowSearch);
+        apply_field!(join_policy, JoinPolicy);
+        apply_field!(all_muted, AllMuted);
+        apply_field!(allow_member_post, AllowMemberPost);
+        apply_field!(forbid_forward, ForbidForward);
+        apply_field!(slow_mode_secs, SlowModeSecs);
+
+        if let Some(max) = patch.max_members.map(|max| max as usize) {
+            if max != self.max_members {
+                changes.push(GroupSettingChange::MaxMembers {
+                    old: self.max_members,
+                    new: max,
+                });
+                self.max_members = max;
+            }
+        }
+        if let Some(text) = &patch.announcement {
+            let new = Some(text.clone()).filter(|text| !text.is_empty());
+            if new != self.announcement {
+                let old = std::mem::replace(&mut self.announcement, new.clone());
+                changes.push(GroupSettingChange::Announcement { old, new });
+            }
+        }
+        if let Some(text) = &patch.description {
+            let new = Some(text.clone()).filter(|text| !text.is_empty());
+            if new != self.description {
+                let old = std::mem::replace(&mut self.description, new.clone());
+                changes.push(GroupSettingChange::Description { old, new });
+            }
+        }
+        Ok(changes)
+    }
+}
+
 /// 获取群组设置响应
 ///
 /// RPC路由: `group/settings/get`
@@ -177,3 +427,127 @@ pub struct GroupMuteAllResponse {
     pub operator_id: String,
     pub updated_at: u64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ErrorCode;
+
+    fn settings() -> GroupSettingsData {
+        serde_json::from_value(serde_json::json!({
+            "join_need_approval": false,
+            "member_can_invite": true,
+            "all_muted": false,
+            "max_members": 200,
+            "created_at": 1,
+            "updated_at": 1
+        }))
+        .expect("legacy settings payload")
+    }
+
+    #[test]
+    fn apply_reports_only_real_changes() {
+        let mut data = settings();
+        let patch = GroupSettingsPatch {
+            all_muted: Some(true),
+            member_can_invite: Some(true), // 未变化
+            max_members: Some(500),
+            announcement: Some("周五发版".to_string()),
+            ..Default::default()
+        };
+        let changes = data.apply(&patch, 10).unwrap();
+        assert_eq!(
+            changes,
+            vec![
+                GroupSettingChange::AllMuted {
+                    old: false,
+                    new: true
+                },
+                GroupSettingChange::MaxMembers { old: 200, new: 500 },
+                GroupSettingChange::Announcement {
+                    old: None,
+                    new: Some("周五发版".to_string())
+                },
+            ]
+        );
+        assert!(data.all_muted);
+        assert_eq!(data.max_members, 500);
+
+        // 再次应用同一补丁：没有变化
+        assert!(data.apply(&patch, 10).unwrap().is_empty());
+
+        // 空串清除公告
+        let clear = GroupSettingsPatch {
+            announcement: Some(String::new()),
+            ..Default::default()
+        };
+        let changes = data.apply(&clear, 10).unwrap();
+        assert_eq!(changes[0].field(), "announcement");
+        assert_eq!(data.announcement, None);
+        assert_eq!(changes[0].describe_cn(), "清除了群公告");
+    }
+
+    #[test]
+    fn invalid_patches_leave_settings_untouched() {
+        let mut data = settings();
+        let before = data.clone();
+        for patch in [
+            GroupSettingsPatch {
+                all_muted: Some(true),
+                max_members: Some(1),
+                ..Default::default()
+            },
+            GroupSettingsPatch {
+                max_members: Some(GROUP_MAX_MEMBERS_LIMIT as u32 + 1),
+                ..Default::default()
+            },
+            GroupSettingsPatch {
+                join_policy: Some(3),
+                ..Default::default()
+            },
+            GroupSettingsPatch {
+                slow_mode_secs: Some(GROUP_SLOW_MODE_MAX_SECS + 1),
+                ..Default::default()
+            },
+            GroupSettingsPatch {
+                description: Some("x".repeat(GROUP_DESCRIPTION_MAX_CHARS + 1)),
+                ..Default::default()
+            },
+        ] {
+            assert_eq!(data.apply(&patch, 10), Err(ErrorCode::InvalidParams));
+            assert_eq!(data, before);
+        }
+
+        // 不能把上限调到当前人数以下
+        let shrink = GroupSettingsPatch {
+            max_members: Some(50),
+            ..Default::default()
+        };
+        assert_eq!(data.apply(&shrink, 51), Err(ErrorCode::InvalidParams));
+        assert!(data.apply(&shrink, 50).is_ok());
+        assert!(GroupSettingsPatch::default().is_empty());
+    }
+
+    #[test]
+    fn change_wire_shape() {
+        let change = GroupSettingChange::JoinPolicy { old: 1, new: 2 };
+        assert_eq!(
+            serde_json::to_value(&change).unwrap(),
+            serde_json::json!({"field": "join_policy", "old": 1, "new": 2})
+        );
+        assert_eq!(change.describe_cn(), "允许直接入群");
+    }
+
+    #[test]
+    fn unknown_field_degrades_to_unknown() {
+        let change: GroupSettingChange = serde_json::from_value(serde_json::json!({
+            "field": "some_future_field",
+            "old": 1,
+            "new": 2
+        }))
+        .unwrap();
+        assert_eq!(change, GroupSettingChange::Unknown);
+        assert_eq!(change.field(), "unknown");
+        assert_eq!(change.describe_cn(), "修改了群设置");
+    }
+}
@@ -0,0 +1,79 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! 用户收件箱补差 RPC（inbox v2）。
+//!
+//! 与 `sync/get_difference` 同构：客户端带上已处理到的 `cursor`，服务端按
+//! cursor 升序返回之后的事件。cursor 是**用户维度**单调递增的，与频道 pts
+//! 无关；离线设备上线后靠它补齐错过的好友申请、入群审批等事件。
+
+use serde::{Deserialize, Serialize};
+
+use crate::UserInboxEventEnvelope;
+
+/// `limit` 缺省值
+pub const INBOX_GET_DIFFERENCE_DEFAULT_LIMIT: u32 = 100;
+/// `limit` 上限，超过时服务端截断
+pub const INBOX_GET_DIFFERENCE_MAX_LIMIT: u32 = 500;
+
+/// 获取收件箱差异请求
+///
+/// RPC路由: `inbox/get_difference`
+///
+/// JSON wire: `cursor` 为字符串（见 [`crate::serde_u64`]）。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InboxGetDifferenceRequest {
+    /// 客户端已处理到的 cursor；从未同步过为 0
+    #[serde(with = "crate::serde_u64")]
+    pub cursor: u64,
+
+    /// 限制数量（可选，默认 [`INBOX_GET_DIFFERENCE_DEFAULT_LIMIT`]）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+
+    /// 用户ID（服务器端填充，客户端不可设置）
+    #[serde(skip_deserializing, default)]
+    pub user_id: u64,
+}
+
+/// 补差返回的一条事件。envelope 不冗余 type，所以 topic 单独携带。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InboxEventItem {
+    /// 事件类型，与在线推送的 `PushMessageRequest.topic` 相同
+    pub topic: String,
+    /// v2 envelope，`cursor` 必有值
+    pub envelope: UserInboxEventEnvelope,
+}
+
+/// 获取收件箱差异响应
+///
+/// RPC路由: `inbox/get_difference`
+///
+/// 服务端不返回已过期（`expires_at <= now`）的事件，但 `current_cursor`
+/// 仍会越过它们，客户端不会因为过期事件而反复拉取。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InboxGetDifferenceResponse {
+    /// 事件列表（cursor 递增）
+    pub events: Vec<InboxEventItem>,
+
+    /// 本批处理完后客户端应保存的 cursor
+    #[serde(with = "crate::serde_u64")]
+    pub current_cursor: u64,
+
+    /// 是否还有更多（需要继续拉取）
+    pub has_more: bool,
+}
@@ -20,10 +20,12 @@ pub mod pin;
 /// 消息相关 RPC 类型定义
 pub mod reaction;
 pub mod revoke;
+pub mod scheduled;
 pub mod status;
 
 pub use history::*;
 pub use pin::*;
 pub use reaction::*;
 pub use revoke::*;
+pub use scheduled::*;
 pub use status::*;
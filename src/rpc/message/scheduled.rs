@@ -0,0 +1,106 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/// 定时消息 RPC
+///
+/// 定时消息通过 `sync/submit` 携带 `send_at` 创建（server 返回
+/// `ServerDecision::Scheduled`），这里只负责查询 / 取消 / 提前发送。
+/// 待发送列表同时以 `scheduled_message` 实体多端同步。
+use crate::rpc::sync::ClientSubmitResponse;
+use serde::{Deserialize, Serialize};
+
+/// 定时消息条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledMessageItem {
+    /// 定时任务ID
+    pub schedule_id: u64,
+    /// 频道ID
+    pub channel_id: u64,
+    /// 频道类型（1=私聊，2=群聊）
+    pub channel_type: u8,
+    /// 创建时提交的客户端消息号，到点发出后沿用，便于本地关联
+    pub local_message_id: u64,
+    /// 命令类型（同 `sync/submit`）
+    pub command_type: String,
+    /// 命令负载（同 `sync/submit`）
+    pub payload: serde_json::Value,
+    /// 计划发送时间（毫秒）
+    pub send_at: i64,
+    /// 创建时间（毫秒）
+    pub created_at: i64,
+}
+
+/// 获取定时消息列表请求
+///
+/// RPC路由: `message/scheduled/list`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledMessageListRequest {
+    /// 仅返回该频道的定时消息；缺省返回全部
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channel_id: Option<u64>,
+
+    /// 用户ID（服务器端填充，客户端不可设置）
+    #[serde(skip_deserializing, default)]
+    pub user_id: u64,
+}
+
+/// 获取定时消息列表响应
+///
+/// RPC路由: `message/scheduled/list`
+/// 按 `send_at` 升序
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledMessageListResponse {
+    pub items: Vec<ScheduledMessageItem>,
+}
+
+/// 取消定时消息请求
+///
+/// RPC路由: `message/scheduled/cancel`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledMessageCancelRequest {
+    /// 定时任务ID
+    pub schedule_id: u64,
+
+    /// 用户ID（服务器端填充，客户端不可设置）
+    #[serde(skip_deserializing, default)]
+    pub user_id: u64,
+}
+
+/// 取消定时消息响应
+///
+/// RPC路由: `message/scheduled/cancel`
+/// 简单操作，返回 true（成功/失败由协议层 code 处理）
+pub type ScheduledMessageCancelResponse = bool;
+
+/// 立即发送定时消息请求
+///
+/// RPC路由: `message/scheduled/send_now`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledMessageSendNowRequest {
+    /// 定时任务ID
+    pub schedule_id: u64,
+
+    /// 用户ID（服务器端填充，客户端不可设置）
+    #[serde(skip_deserializing, default)]
+    pub user_id: u64,
+}
+
+/// 立即发送定时消息响应
+///
+/// RPC路由: `message/scheduled/send_now`
+/// 与 `sync/submit` 立即发送的结果一致（decision 为 Accepted / Transformed）
+pub type ScheduledMessageSendNowResponse = ClientSubmitResponse;
@@ -17,6 +17,7 @@
 
 pub mod account;
 pub mod auth;
+pub mod bot;
 pub mod channel;
 pub mod channel_broadcast;
 pub mod contact;
@@ -42,7 +43,10 @@ pub mod file;
 ///   └── ...
 /// ```
 pub mod group;
+/// 用户收件箱补差（inbox v2）
+pub mod inbox;
 pub mod message;
+pub mod notification_policy;
 pub mod presence;
 pub mod qr_login;
 pub mod qrcode;
@@ -55,20 +59,23 @@ pub mod user_qrcode;
 // 重导出所有类型，方便使用
 pub use account::*;
 pub use auth::*;
+pub use bot::*;
 // Do not glob-reexport `channel`: both channel and message expose a `pin`
 // module, which would make `rpc::pin` ambiguous. Keep the type-level root
 // compatibility surface without exporting either module name.
 pub use channel::{
-    ChannelHideRequest, ChannelHideResponse, ChannelMuteRequest, ChannelMuteResponse,
-    ChannelPinRequest, ChannelPinResponse, GetOrCreateDirectChannelRequest,
-    GetOrCreateDirectChannelResponse,
+    ChannelEphemeralSetRequest, ChannelEphemeralSetResponse, ChannelHideRequest,
+    ChannelHideResponse, ChannelMuteRequest, ChannelMuteResponse, ChannelPinRequest,
+    ChannelPinResponse, GetOrCreateDirectChannelRequest, GetOrCreateDirectChannelResponse,
 };
 pub use channel_broadcast::*;
 pub use contact::*;
 pub use device::*;
 pub use file::*;
 pub use group::*;
+pub use inbox::*;
 pub use message::*;
+pub use notification_policy::*;
 pub use presence::*;
 pub use qr_login::*;
 pub use qrcode::*;
@@ -0,0 +1,37 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::{Deserialize, Serialize};
+
+use crate::notification_policy::NotificationPolicy;
+
+/// 整体覆盖当前用户的通知策略
+///
+/// RPC路由: `notification/policy/set`
+///
+/// 成功后 server 推送 `notification_policy` 实体失效，其他设备经
+/// `entity/sync_entities` 拉取最新策略。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationPolicySetRequest {
+    /// 用户ID（服务器端填充，客户端不可设置）
+    #[serde(skip_deserializing, default)]
+    pub user_id: u64,
+    pub policy: NotificationPolicy,
+}
+
+/// 简单操作，返回 true（成功/失败由协议层 code 处理）
+pub type NotificationPolicySetResponse = bool;
@@ -97,6 +97,9 @@ pub mod group_member {
 
     /// 取消禁言
     pub const UNMUTE: &str = "group/member/unmute";
+
+    /// 限制成员（禁言 / 禁发媒体 / 禁发链接）
+    pub const RESTRICT: &str = "group/member/restrict";
 }
 
 /// 群组角色路由
@@ -127,6 +130,12 @@ pub mod group_approval {
 
     /// 处理审批
     pub const HANDLE: &str = "group/approval/handle";
+
+    /// 设置入群问题
+    pub const QUESTIONS_SET: &str = "group/approval/questions/set";
+
+    /// 获取入群问题（申请人入群前调用）
+    pub const QUESTIONS_GET: &str = "group/approval/questions/get";
 }
 
 /// 群组二维码路由（QR_CODE_SPEC v1.3）
@@ -153,6 +162,12 @@ pub mod message {
     pub const PIN: &str = "message/pin";
     /// 获取群置顶消息列表
     pub const PIN_LIST: &str = "message/pin/list";
+    /// 获取我的定时消息列表
+    pub const SCHEDULED_LIST: &str = "message/scheduled/list";
+    /// 取消定时消息
+    pub const SCHEDULED_CANCEL: &str = "message/scheduled/cancel";
+    /// 立即发送定时消息
+    pub const SCHEDULED_SEND_NOW: &str = "message/scheduled/send_now";
 }
 
 /// 消息历史路由
@@ -210,6 +225,9 @@ pub mod channel {
 
     /// 设置频道静音
     pub const MUTE: &str = "channel/mute";
+
+    /// 设置频道阅后即焚 TTL
+    pub const EPHEMERAL_SET: &str = "channel/ephemeral/set";
 }
 
 /// 账号搜索路由
@@ -232,6 +250,21 @@ pub mod account_bot {
     pub const UNFOLLOW: &str = "account/bot/unfollow";
 }
 
+/// Bot 交互路由（内联键盘回调、命令菜单）
+pub mod bot {
+    /// 用户点击回调按钮；server 以 `bot.callback_query` 推给 Bot，等待 Bot 应答后返回
+    pub const CALLBACK_QUERY: &str = "bot/callback/query";
+
+    /// Bot 应答一次回调（toast / 弹窗 / 打开链接）
+    pub const CALLBACK_ANSWER: &str = "bot/callback/answer";
+
+    /// Bot 整表设置命令菜单
+    pub const COMMANDS_SET: &str = "bot/commands/set";
+
+    /// 获取 Bot 命令菜单（可按频道类型过滤）
+    pub const COMMANDS_GET: &str = "bot/commands/get";
+}
+
 /// 账号用户路由
 pub mod account_user {
     /// 获取用户详情
@@ -265,6 +298,12 @@ pub mod device {
     pub const PUSH_STATUS: &str = "device/push/status";
 }
 
+/// 通知策略路由
+pub mod notification_policy {
+    /// 设置通知策略（整体覆盖）
+    pub const SET: &str = "notification/policy/set";
+}
+
 /// 账号资料路由
 pub mod account_profile {
     /// 获取个人资料
@@ -398,6 +437,12 @@ pub mod sync {
     pub const SESSION_READY: &str = "sync/session_ready";
 }
 
+/// 用户收件箱路由（inbox v2）
+pub mod inbox {
+    /// 按用户 cursor 补齐收件箱事件
+    pub const GET_DIFFERENCE: &str = "inbox/get_difference";
+}
+
 /// 实体状态同步（ENTITY_SYNC_V1，与 PTS 消息流正交）
 pub mod entity {
     /// 通用实体同步 RPC
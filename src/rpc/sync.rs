@@ -15,7 +15,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::{CanonicalTimelineEvent, FlatBufferMessage, CANONICAL_TIMELINE_EVENT_SCHEMA_V1};
+use crate::{
+    CanonicalTimelineEvent, EntityInvalidation, EntityMutationHint, FlatBufferMessage, TextEntity,
+    CANONICAL_TIMELINE_EVENT_SCHEMA_V1,
+};
 /// pts-Based 同步协议
 ///
 /// 设计原则：
@@ -146,12 +149,25 @@ pub struct ClientSubmitRequest {
     /// 设备 ID（可选，用于多设备去重）
     #[serde(skip_serializing_if = "Option::is_none")]
     pub device_id: Option<String>,
+
+    /// 定时发送时间（毫秒）。缺省或不晚于服务器当前时间 = 立即发送；
+    /// 否则 server 只登记定时任务并返回 [`ServerDecision::Scheduled`]。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub send_at: Option<i64>,
+}
+
+impl ClientSubmitRequest {
+    /// 以服务器时间 `now_ms` 判断本次提交是否应登记为定时消息。
+    pub fn is_scheduled(&self, now_ms: i64) -> bool {
+        self.send_at.is_some_and(|send_at| send_at > now_ms)
+    }
 }
 
 /// 服务器提交响应
 ///
 /// RPC路由: `sync/submit`
-/// 注意：如果 decision 是 Rejected，SDK 层会返回错误，不会反序列化这个结构
+/// 注意：如果 decision 是 Rejected，SDK 层会返回错误，不会反序列化这个结构；
+/// decision 是 Scheduled 时消息尚未入 timeline，`pts` / `server_msg_id` 为空
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ClientSubmitResponse {
     /// 服务器决策
@@ -194,6 +210,13 @@ pub enum ServerDecision {
         /// 拒绝原因
         reason: String,
     },
+    /// 已登记为定时消息（到点后由 server 以同一 local_message_id 提交）
+    Scheduled {
+        /// 定时任务 ID，用于 `message/scheduled/cancel|send_now`
+        schedule_id: u64,
+        /// 计划发送时间（毫秒）
+        send_at: i64,
+    },
 }
 
 // ============================================================
@@ -496,7 +519,8 @@ pub struct ChannelPtsInfo {
 /// RPC 路由: `entity/sync_entities`（待服务端实现）
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SyncEntitiesRequest {
-    /// 实体类型：friend, group, channel, group_member, user, user_block 等（受控枚举）
+    /// 实体类型：friend, group, channel, channel_draft, group_member, user,
+    /// user_block, scheduled_message, notification_policy 等（受控枚举）
     pub entity_type: String,
     /// 客户端上次同步到的版本号，0 或空表示全量
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -654,6 +678,20 @@ pub struct ChannelSyncPayload {
     /// 客户端据此持久化 channel.peer_user_id 并用于 presence 直查。
     #[serde(skip_serializing_if = "Option::is_none")]
     pub peer_user_id: Option<u64>,
+    /// 频道阅后即焚 TTL（秒）。缺省或 0 = 未开启。新消息的 `expire` 由 server
+    /// 按此值盖章，见 [`crate::ephemeral::ChannelEphemeralSetting`]。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ephemeral_ttl: Option<u32>,
+    /// 阅后即焚计时起点（`after_send` / `after_read`）。缺省 = `after_send`。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ephemeral_mode: Option<crate::ExpireMode>,
+    /// 置顶时间（毫秒）。`top > 0` 时有效，多个置顶会话按此倒序；旧 server
+    /// 不下发时视为 0。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pinned_at: Option<i64>,
+    /// 是否被 `channel/hide` 隐藏（1 = 隐藏）。收到新消息后 server 自动取消隐藏。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hidden: Option<i32>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -676,6 +714,99 @@ pub struct ChannelExtraSyncPayload {
     pub draft_updated_at: Option<u64>,
 }
 
+/// 草稿实体类型（`entity/sync_entities` 的 `entity_type`）
+pub const CHANNEL_DRAFT_ENTITY_TYPE: &str = "channel_draft";
+
+/// `channel_draft` 实体负载：某个频道的输入框草稿，多端同步。
+///
+/// entity_id 为 channel_id 的十进制字符串。取代 [`ChannelExtraSyncPayload`]
+/// 上只有纯文本的 `draft` / `draft_updated_at`。
+///
+/// 清空草稿以 `text` 为空的 upsert 下发而**不是** tombstone：tombstone 不带
+/// `updated_at`，没法参与 last-writer-wins，旧设备上更早的草稿会把清空覆盖回来。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChannelDraftSyncPayload {
+    #[serde(with = "crate::serde_u64")]
+    pub channel_id: u64,
+    #[serde(default)]
+    pub channel_type: u8,
+    /// 草稿正文，空字符串 = 已清空
+    #[serde(default)]
+    pub text: String,
+    /// 正文上的格式 / @ 片段
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub entities: Vec<TextEntity>,
+    /// 正在回复的 server_message_id
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "option_u64_str"
+    )]
+    pub reply_to_message_id: Option<u64>,
+    /// 编辑时间（毫秒），由编辑草稿的设备写入，LWW 的唯一依据
+    pub updated_at: i64,
+}
+
+impl ChannelDraftSyncPayload {
+    /// 正文为空且没有回复目标时，本地应删除草稿行。
+    pub fn is_cleared(&self) -> bool {
+        self.text.is_empty() && self.reply_to_message_id.is_none()
+    }
+
+    /// `incoming` 是否应覆盖当前草稿。
+    ///
+    /// `updated_at` 更大者胜；相同时按全部字段比较取较大者，保证任意两台设备
+    /// 不论以什么顺序收到双方写入，最终都收敛到同一份草稿。
+    pub fn should_replace(&self, incoming: &Self) -> bool {
+        incoming.order_key() > self.order_key()
+    }
+
+    fn order_key(&self) -> (i64, &str, Option<u64>, u8, u64, &[TextEntity]) {
+        (
+            self.updated_at,
+            &self.text,
+            self.reply_to_message_id,
+            self.channel_type,
+            self.channel_id,
+            &self.entities,
+        )
+    }
+
+    /// last-writer-wins 合并；满足交换律，合并顺序不影响结果。
+    pub fn merge(self, other: Self) -> Self {
+        if self.should_replace(&other) {
+            other
+        } else {
+            self
+        }
+    }
+
+    /// 本地无草稿时直接采用远端，否则按 LWW 合并。
+    pub fn merge_into(local: Option<Self>, remote: Self) -> Self {
+        match local {
+            Some(local) => local.merge(remote),
+            None => remote,
+        }
+    }
+
+    /// 草稿写入后由 server 推送的失效通知条目。
+    pub fn invalidation(&self, target_version: u64) -> EntityInvalidation {
+        EntityInvalidation {
+            entity_type: CHANNEL_DRAFT_ENTITY_TYPE.to_string(),
+            entity_id: Some(self.channel_id.to_string()),
+            scope: None,
+            target_version,
+            mutation_hint: EntityMutationHint::Upsert,
+        }
+    }
+}
+
+/// 通知策略实体类型（`entity/sync_entities` 的 `entity_type`）。
+///
+/// 每个用户一条，entity_id 为 user_id 的十进制字符串，负载为
+/// [`crate::notification_policy::NotificationPolicy`]。
+pub const NOTIFICATION_POLICY_ENTITY_TYPE: &str = "notification_policy";
+
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ChannelUnreadSyncPayload {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -714,6 +845,16 @@ pub struct GroupMemberSyncPayload {
     pub updated_at: Option<i64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub version: Option<i64>,
+    /// 管理员权限位，未知位在反序列化时已丢弃，见
+    /// [`crate::rpc::group::role::GroupAdminRights`]。仅管理员下发。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub admin_rights: Option<crate::rpc::group::role::GroupAdminRights>,
+    /// 管理员自定义头衔
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub admin_title: Option<String>,
+    /// 成员限制，缺省 = 无限制
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub restriction: Option<crate::rpc::group::GroupMemberRestriction>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -876,6 +1017,32 @@ pub struct ChannelReadCursorSyncPayload {
     pub updated_at: Option<i64>,
 }
 
+/// `scheduled_message` 实体负载：当前用户尚未发出的定时消息，供多端同步。
+///
+/// 到点发出或被取消后，server 以 tombstone（`deleted = true`）下发，
+/// entity_id 为 schedule_id。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScheduledMessageSyncPayload {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schedule_id: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channel_id: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channel_type: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub local_message_id: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payload: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub send_at: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<i64>,
+}
+
 /// 实体同步响应
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SyncEntitiesResponse {
@@ -897,6 +1064,7 @@ pub struct SyncEntitiesResponse {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::EntityInvalidationBatch;
 
     #[derive(Serialize)]
     struct TextContent<'a> {
@@ -936,6 +1104,7 @@ mod tests {
             payload: text_content("Hello"),
             client_timestamp: 1700000000000,
             device_id: Some("device_001".to_string()),
+            send_at: None,
         };
 
         let json = serde_json::to_string(&req).unwrap();
@@ -961,6 +1130,121 @@ mod tests {
         assert!(matches!(decision_rejected, ServerDecision::Rejected { .. }));
     }
 
+    fn draft(text: &str, updated_at: i64) -> ChannelDraftSyncPayload {
+        ChannelDraftSyncPayload {
+            channel_id: 9_007_199_254_740_993,
+            channel_type: 1,
+            text: text.to_string(),
+            updated_at,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn draft_merge_is_last_writer_wins_and_order_independent() {
+        let older = draft("desktop", 100);
+        let newer = draft("mobile", 200);
+        assert_eq!(older.clone().merge(newer.clone()), newer);
+        assert_eq!(newer.clone().merge(older.clone()), newer);
+
+        // 同一毫秒的并发编辑：两端合并顺序不同也必须收敛到同一份
+        let a = draft("a", 300);
+        let b = draft("b", 300);
+        assert_eq!(a.clone().merge(b.clone()), b.clone().merge(a.clone()));
+
+        // 清空是带 updated_at 的 upsert，能压过更早的草稿
+        let cleared = draft("", 400);
+        let merged = ChannelDraftSyncPayload::merge_into(Some(newer), cleared);
+        assert!(merged.is_cleared());
+    }
+
+    #[test]
+    fn draft_merge_is_commutative_when_only_entities_or_type_differ() {
+        let plain = draft("hi @bob", 500);
+        let mut mention = plain.clone();
+        mention.entities.push(TextEntity {
+            offset: 3,
+            length: 4,
+            kind: crate::TextEntityKind::Mention { user_id: 7 },
+        });
+        assert_eq!(
+            plain.clone().merge(mention.clone()),
+            mention.clone().merge(plain.clone())
+        );
+
+        let mut other_type = plain.clone();
+        other_type.channel_type = 2;
+        assert_eq!(
+            plain.clone().merge(other_type.clone()),
+            other_type.merge(plain)
+        );
+    }
+
+    #[test]
+    fn draft_payload_wire_shape() {
+        let mut payload = draft("hi @bob", 1_700_000_000_000);
+        payload.reply_to_message_id = Some(9_007_199_254_740_995);
+        payload.entities.push(TextEntity {
+            offset: 3,
+            length: 4,
+            kind: crate::TextEntityKind::Mention {
+                user_id: 9_007_199_254_740_997,
+            },
+        });
+        let json = serde_json::to_value(&payload).unwrap();
+        assert_eq!(json["channel_id"], "9007199254740993");
+        assert_eq!(json["reply_to_message_id"], "9007199254740995");
+        assert_eq!(json["entities"][0]["type"], "mention");
+        assert_eq!(json["entities"][0]["user_id"], "9007199254740997");
+        let back: ChannelDraftSyncPayload = serde_json::from_value(json).unwrap();
+        assert_eq!(back, payload);
+
+        let invalidation = payload.invalidation(7);
+        assert_eq!(invalidation.entity_type, CHANNEL_DRAFT_ENTITY_TYPE);
+        assert_eq!(invalidation.entity_id.as_deref(), Some("9007199254740993"));
+        assert!(EntityInvalidationBatch::new_v1(1, vec![invalidation], 0).is_ok());
+    }
+
+    #[test]
+    fn unknown_text_entity_kind_does_not_break_the_draft() {
+        let back: ChannelDraftSyncPayload = serde_json::from_str(
+            r#"{"channel_id":"1","text":"x","updated_at":1,"entities":[{"offset":0,"length":1,"type":"future_kind"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(back.entities[0].kind, crate::TextEntityKind::Unknown);
+    }
+
+    #[test]
+    fn scheduled_submit_round_trips_and_old_requests_still_parse() {
+        let mut req: ClientSubmitRequest = serde_json::from_value(serde_json::json!({
+            "local_message_id": 1,
+            "channel_id": 2,
+            "channel_type": 1,
+            "last_pts": 0,
+            "command_type": "send_message",
+            "payload": {},
+            "client_timestamp": 1_700_000_000_000_i64,
+        }))
+        .unwrap();
+        assert_eq!(req.send_at, None);
+        assert!(!req.is_scheduled(1_700_000_000_000));
+
+        // 过去或当前时间视为立即发送
+        req.send_at = Some(1_700_000_000_000);
+        assert!(!req.is_scheduled(1_700_000_000_000));
+        req.send_at = Some(1_700_000_060_000);
+        assert!(req.is_scheduled(1_700_000_000_000));
+
+        let decision = ServerDecision::Scheduled {
+            schedule_id: 42,
+            send_at: 1_700_000_060_000,
+        };
+        let json = serde_json::to_value(&decision).unwrap();
+        assert_eq!(json["scheduled"]["schedule_id"], 42);
+        let back: ServerDecision = serde_json::from_value(json).unwrap();
+        assert_eq!(back, decision);
+    }
+
     #[test]
     fn old_client_new_server_and_new_client_old_server_are_compatible() {
         #[derive(Deserialize)]
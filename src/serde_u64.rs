@@ -46,6 +46,52 @@ pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D:
     }
 }
 
+/// `Option<u64>` 的同款编码：`None` 输出 `null`，缺省 / `null` 读成 `None`。
+/// 配合 `#[serde(default, with = "crate::serde_u64::option")]` 使用。
+pub mod option {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &Option<u64>, serializer: S) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(value) => super::serialize(value, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<u64>, D::Error> {
+        #[derive(Deserialize)]
+        struct Wrapped(#[serde(with = "super")] u64);
+
+        Ok(Option::<Wrapped>::deserialize(deserializer)?.map(|Wrapped(value)| value))
+    }
+}
+
+/// `Vec<u64>` 的同款编码：每个元素都是字符串。
+pub mod vec {
+    use serde::ser::SerializeSeq;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(values: &[u64], serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(values.len()))?;
+        for value in values {
+            seq.serialize_element(&value.to_string())?;
+        }
+        seq.end()
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u64>, D::Error> {
+        #[derive(Deserialize)]
+        struct Wrapped(#[serde(with = "super")] u64);
+
+        Ok(Vec::<Wrapped>::deserialize(deserializer)?
+            .into_iter()
+            .map(|Wrapped(value)| value)
+            .collect())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use serde::{Deserialize, Serialize};
@@ -77,6 +123,39 @@ mod tests {
         assert_eq!(from_number.id, 608_993_815_990_284_288);
     }
 
+    #[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+    struct Many {
+        #[serde(default, with = "super::option")]
+        reply_to: Option<u64>,
+        #[serde(default, with = "super::vec")]
+        ids: Vec<u64>,
+    }
+
+    #[test]
+    fn option_and_vec_follow_the_same_rules() {
+        let json = serde_json::to_string(&Many {
+            reply_to: Some(608_993_815_990_284_288),
+            ids: vec![1, 608_993_815_990_284_289],
+        })
+        .expect("encode");
+        assert_eq!(
+            json,
+            r#"{"reply_to":"608993815990284288","ids":["1","608993815990284289"]}"#
+        );
+        let back: Many =
+            serde_json::from_str(r#"{"reply_to":null,"ids":[1,"2"]}"#).expect("decode");
+        assert_eq!(back.reply_to, None);
+        assert_eq!(back.ids, vec![1, 2]);
+        let empty: Many = serde_json::from_str("{}").expect("decode empty");
+        assert_eq!(
+            empty,
+            Many {
+                reply_to: None,
+                ids: vec![]
+            }
+        );
+    }
+
     #[test]
     fn a_non_numeric_string_is_an_error_rather_than_a_silent_zero() {
         assert!(serde_json::from_str::<Wire>(r#"{"id":"not-an-id"}"#).is_err());
@@ -0,0 +1,440 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! 流式消息 —— `stream_no` / `stream_seq` / `stream_flag` 的统一语义与客户端组装。
+//!
+//! 一条流式消息（典型场景：bot 逐 token 推送的 AI 回复）由同一个
+//! `stream_no` 下的多帧 `PushMessageRequest` 组成：
+//!
+//! - `stream_seq` 从 1 开始逐帧 +1，[`StreamFlag::Start`] 帧为 1；
+//! - 每帧 payload 都是一个完整的 `MessagePayloadEnvelope`，其 `content` 是
+//!   **增量文本**，按 `stream_seq` 顺序拼接即得全文；
+//! - [`StreamFlag::End`] 帧的 envelope 提供最终消息的 metadata / 回复 /
+//!   @ 列表等非文本字段；
+//! - [`StreamFlag::Abort`] 表示发送方放弃，客户端保留已收到的部分文本。
+//!
+//! 推送通道不保证顺序，也可能重复投递。[`StreamAssembler`] 负责按
+//! `stream_seq` 重排、去重、给出每一步可展示的前缀文本，并清理发送方中途
+//! 失联的流。
+
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+
+use crate::{FlatBufferMessage, MessagePayloadEnvelope, ProtocolError, PushMessageRequest};
+
+/// 流式帧类型（`PushMessageRequest.stream_flag`）。0 = 非流式消息。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum StreamFlag {
+    /// 首帧
+    Start = 1,
+    /// 中间增量帧
+    Delta = 2,
+    /// 末帧，携带最终 envelope
+    End = 3,
+    /// 发送方中止
+    Abort = 4,
+}
+
+impl StreamFlag {
+    /// 未知值（含 0）返回 `None`。
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            1 => Some(Self::Start),
+            2 => Some(Self::Delta),
+            3 => Some(Self::End),
+            4 => Some(Self::Abort),
+            _ => None,
+        }
+    }
+
+    pub fn as_u8(self) -> u8 {
+        self as u8
+    }
+}
+
+impl PushMessageRequest {
+    /// 是否为流式消息的一帧。
+    pub fn is_stream_frame(&self) -> bool {
+        !self.stream_no.is_empty() && StreamFlag::from_u8(self.stream_flag).is_some()
+    }
+}
+
+/// 流式帧被吸收后的结果。
+#[derive(Debug, Clone, PartialEq)]
+pub enum StreamUpdate {
+    /// 帧已缓存，但前面还有缺口，可展示文本没有变化。
+    Buffered,
+    /// 重复帧，或已结束流的迟到帧，忽略。
+    Duplicate,
+    /// `stream_seq` 超出乱序缓冲窗口（[`MAX_PENDING_STREAM_FRAMES`]），丢弃。
+    OutOfWindow,
+    /// 可展示的前缀文本推进了。
+    Partial { text: String },
+    /// 流完整结束：最终消息（`content` 为全文）。
+    Completed(Box<MessagePayloadEnvelope>),
+    /// 发送方中止：保留已连续收到的部分文本。
+    Aborted { text: String },
+}
+
+/// 超时被丢弃的流。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AbandonedStream {
+    pub stream_no: String,
+    /// 丢弃时已连续收到的部分文本
+    pub text: String,
+}
+
+/// 默认超时：60 秒没有任何新帧即视为发送方失联。
+pub const DEFAULT_STREAM_TIMEOUT_MS: i64 = 60_000;
+
+/// 乱序缓冲窗口：只缓存 `next_seq` 之后这么多帧，单条流的 `pending` 不会
+/// 超过这个数。
+pub const MAX_PENDING_STREAM_FRAMES: u32 = 256;
+
+/// 记住最近这么多条已结束的流，其迟到帧一律判为重复。
+pub const FINISHED_STREAM_CAPACITY: usize = 1024;
+
+#[derive(Debug)]
+struct StreamState {
+    /// 下一个待拼接的 `stream_seq`
+    next_seq: u32,
+    /// 连续前缀拼出的文本
+    text: String,
+    /// 乱序到达、尚不能拼接的帧
+    pending: BTreeMap<u32, (StreamFlag, MessagePayloadEnvelope)>,
+    last_active_ms: i64,
+}
+
+/// 流式消息组装器。按 `stream_no` 维护多条并发的流。
+///
+/// 时间由调用方传入（毫秒），组装器本身不读时钟。
+#[derive(Debug)]
+pub struct StreamAssembler {
+    timeout_ms: i64,
+    streams: HashMap<String, StreamState>,
+    /// 已结束（完成 / 中止 / 超时）的流。迟到帧据此判为重复，而不是重新开
+    /// 一条流；按结束顺序淘汰，最多 [`FINISHED_STREAM_CAPACITY`] 条。
+    finished: HashSet<String>,
+    finished_order: VecDeque<String>,
+}
+
+impl Default for StreamAssembler {
+    fn default() -> Self {
+        Self::new(DEFAULT_STREAM_TIMEOUT_MS)
+    }
+}
+
+impl StreamAssembler {
+    pub fn new(timeout_ms: i64) -> Self {
+        Self {
+            timeout_ms,
+            streams: HashMap::new(),
+            finished: HashSet::new(),
+            finished_order: VecDeque::new(),
+        }
+    }
+
+    /// 吸收一帧推送。非流式消息、payload 解码失败时返回错误。
+    pub fn accept(
+        &mut self,
+        push: &PushMessageRequest,
+        now_ms: i64,
+    ) -> Result<StreamUpdate, ProtocolError> {
+        let flag = StreamFlag::from_u8(push.stream_flag).ok_or_else(|| {
+            ProtocolError::InvalidValue(format!("stream_flag {}", push.stream_flag))
+        })?;
+        if push.stream_no.is_empty() {
+            return Err(ProtocolError::MissingField("stream_no"));
+        }
+        if push.stream_seq == 0 {
+            return Err(ProtocolError::InvalidValue("stream_seq 0".to_string()));
+        }
+        let envelope = MessagePayloadEnvelope::decode_fb(&push.payload)?;
+        Ok(self.accept_frame(&push.stream_no, push.stream_seq, flag, envelope, now_ms))
+    }
+
+    /// 吸收一帧已解码的 envelope。
+    pub fn accept_frame(
+        &mut self,
+        stream_no: &str,
+        stream_seq: u32,
+        flag: StreamFlag,
+        envelope: MessagePayloadEnvelope,
+        now_ms: i64,
+    ) -> StreamUpdate {
+        if self.finished.contains(stream_no) {
+            return StreamUpdate::Duplicate;
+        }
+        let next_seq = self.streams.get(stream_no).map_or(1, |s| s.next_seq);
+        if stream_seq > next_seq.saturating_add(MAX_PENDING_STREAM_FRAMES) {
+            return StreamUpdate::OutOfWindow;
+        }
+        let state = self
+            .streams
+            .entry(stream_no.to_string())
+            .or_insert_with(|| StreamState {
+                next_seq: 1,
+                text: String::new(),
+                pending: BTreeMap::new(),
+                last_active_ms: now_ms,
+            });
+        if stream_seq < state.next_seq || state.pending.contains_key(&stream_seq) {
+            return StreamUpdate::Duplicate;
+        }
+        state.last_active_ms = now_ms;
+
+        // 中止不必等缺口补齐：发送方已经不会再发了。
+        if flag == StreamFlag::Abort {
+            let text = self.finish(stream_no);
+            return StreamUpdate::Aborted { text };
+        }
+
+        state.pending.insert(stream_seq, (flag, envelope));
+        let mut advanced = false;
+        while let Some((flag, envelope)) = state.pending.remove(&state.next_seq) {
+            state.next_seq += 1;
+            state.text.push_str(&envelope.content);
+            advanced = true;
+            if flag == StreamFlag::End {
+                let text = self.finish(stream_no);
+                return StreamUpdate::Completed(Box::new(MessagePayloadEnvelope {
+                    content: text,
+                    ..envelope
+                }));
+            }
+        }
+        if advanced {
+            StreamUpdate::Partial {
+                text: state.text.clone(),
+            }
+        } else {
+            StreamUpdate::Buffered
+        }
+    }
+
+    /// 当前可展示的前缀文本；流不存在或已结束时返回 `None`。
+    pub fn partial_text(&self, stream_no: &str) -> Option<&str> {
+        self.streams.get(stream_no).map(|s| s.text.as_str())
+    }
+
+    /// 丢弃超过 `timeout_ms` 没有新帧的流。
+    pub fn expire_idle(&mut self, now_ms: i64) -> Vec<AbandonedStream> {
+        let timeout_ms = self.timeout_ms;
+        let idle: Vec<String> = self
+            .streams
+            .iter()
+            .filter(|(_, s)| now_ms - s.last_active_ms >= timeout_ms)
+            .map(|(stream_no, _)| stream_no.clone())
+            .collect();
+        idle.into_iter()
+            .map(|stream_no| {
+                let text = self.finish(&stream_no);
+                AbandonedStream { stream_no, text }
+            })
+            .collect()
+    }
+
+    fn finish(&mut self, stream_no: &str) -> String {
+        if self.finished.insert(stream_no.to_string()) {
+            self.finished_order.push_back(stream_no.to_string());
+            if self.finished_order.len() > FINISHED_STREAM_CAPACITY {
+                if let Some(oldest) = self.finished_order.pop_front() {
+                    self.finished.remove(&oldest);
+                }
+            }
+        }
+        self.streams
+            .remove(stream_no)
+            .map(|s| s.text)
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{encode_message, MessageMetadata};
+
+    fn frame(seq: u32, flag: StreamFlag, content: &str) -> PushMessageRequest {
+        let envelope = MessagePayloadEnvelope {
+            content: content.to_string(),
+            ..Default::default()
+        };
+        PushMessageRequest {
+            stream_no: "s-1".to_string(),
+            stream_seq: seq,
+            stream_flag: flag.as_u8(),
+            payload: encode_message(&envelope).unwrap(),
+            ..Default::default()
+        }
+    }
+
+    fn partial(text: &str) -> StreamUpdate {
+        StreamUpdate::Partial {
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn out_of_order_frames_are_reordered_and_deduped() {
+        let mut asm = StreamAssembler::default();
+        let now = 1_700_000_000_000;
+
+        assert_eq!(
+            asm.accept(&frame(2, StreamFlag::Delta, "lo "), now)
+                .unwrap(),
+            StreamUpdate::Buffered
+        );
+        assert_eq!(
+            asm.accept(&frame(1, StreamFlag::Start, "Hel"), now)
+                .unwrap(),
+            partial("Hello ")
+        );
+        assert_eq!(
+            asm.accept(&frame(2, StreamFlag::Delta, "lo "), now)
+                .unwrap(),
+            StreamUpdate::Duplicate
+        );
+        assert_eq!(asm.partial_text("s-1"), Some("Hello "));
+
+        assert_eq!(
+            asm.accept(&frame(4, StreamFlag::End, "!"), now).unwrap(),
+            StreamUpdate::Buffered
+        );
+        match asm
+            .accept(&frame(3, StreamFlag::Delta, "world"), now)
+            .unwrap()
+        {
+            StreamUpdate::Completed(envelope) => assert_eq!(envelope.content, "Hello world!"),
+            other => panic!("expected Completed, got {other:?}"),
+        }
+        assert_eq!(asm.partial_text("s-1"), None);
+
+        // 结束后重投的帧不会重新开一条流
+        assert_eq!(
+            asm.accept(&frame(4, StreamFlag::End, "!"), now).unwrap(),
+            StreamUpdate::Duplicate
+        );
+    }
+
+    #[test]
+    fn end_frame_supplies_final_envelope_fields() {
+        let mut asm = StreamAssembler::default();
+        asm.accept_frame(
+            "s-2",
+            1,
+            StreamFlag::Start,
+            MessagePayloadEnvelope::default(),
+            0,
+        );
+        let end = MessagePayloadEnvelope {
+            content: "done".to_string(),
+            reply_to_message_id: Some(99),
+            metadata: Some(MessageMetadata::Link(Default::default())),
+            ..Default::default()
+        };
+        match asm.accept_frame("s-2", 2, StreamFlag::End, end, 0) {
+            StreamUpdate::Completed(envelope) => {
+                assert_eq!(envelope.content, "done");
+                assert_eq!(envelope.reply_to_message_id, Some(99));
+                assert!(envelope.metadata.is_some());
+            }
+            other => panic!("expected Completed, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn abort_keeps_contiguous_prefix() {
+        let mut asm = StreamAssembler::default();
+        asm.accept(&frame(1, StreamFlag::Start, "part"), 0).unwrap();
+        asm.accept(&frame(3, StreamFlag::Delta, "lost"), 0).unwrap();
+        assert_eq!(
+            asm.accept(&frame(4, StreamFlag::Abort, ""), 0).unwrap(),
+            StreamUpdate::Aborted {
+                text: "part".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn idle_streams_time_out() {
+        let mut asm = StreamAssembler::new(1_000);
+        asm.accept(&frame(1, StreamFlag::Start, "abc"), 0).unwrap();
+        assert!(asm.expire_idle(999).is_empty());
+        assert_eq!(
+            asm.expire_idle(1_000),
+            vec![AbandonedStream {
+                stream_no: "s-1".to_string(),
+                text: "abc".to_string(),
+            }]
+        );
+        // 超时后的迟到帧判为重复，不会重新开一条僵尸流
+        asm.expire_idle(5_000);
+        assert_eq!(
+            asm.accept(&frame(2, StreamFlag::Delta, "d"), 5_000)
+                .unwrap(),
+            StreamUpdate::Duplicate
+        );
+        assert_eq!(asm.partial_text("s-1"), None);
+    }
+
+    #[test]
+    fn far_ahead_frames_are_dropped() {
+        let mut asm = StreamAssembler::default();
+        let seq = 1 + MAX_PENDING_STREAM_FRAMES;
+        assert_eq!(
+            asm.accept_frame("s-3", seq + 1, StreamFlag::Delta, Default::default(), 0),
+            StreamUpdate::OutOfWindow
+        );
+        assert!(asm.streams.is_empty());
+        assert_eq!(
+            asm.accept_frame("s-3", seq, StreamFlag::Delta, Default::default(), 0),
+            StreamUpdate::Buffered
+        );
+        assert_eq!(
+            asm.accept_frame("s-3", u32::MAX, StreamFlag::End, Default::default(), 0),
+            StreamUpdate::OutOfWindow
+        );
+    }
+
+    #[test]
+    fn finished_tombstones_are_bounded() {
+        let mut asm = StreamAssembler::default();
+        for i in 0..=FINISHED_STREAM_CAPACITY {
+            asm.accept_frame(&i.to_string(), 1, StreamFlag::Abort, Default::default(), 0);
+        }
+        assert_eq!(asm.finished.len(), FINISHED_STREAM_CAPACITY);
+        assert!(!asm.finished.contains("0"));
+        assert_eq!(
+            asm.accept_frame("1", 2, StreamFlag::Delta, Default::default(), 0),
+            StreamUpdate::Duplicate
+        );
+    }
+
+    #[test]
+    fn non_stream_push_is_rejected() {
+        let mut asm = StreamAssembler::default();
+        let plain = PushMessageRequest::default();
+        assert!(!plain.is_stream_frame());
+        assert!(asm.accept(&plain, 0).is_err());
+        assert_eq!(StreamFlag::from_u8(0), None);
+        assert_eq!(
+            StreamFlag::from_u8(StreamFlag::Abort.as_u8()),
+            Some(StreamFlag::Abort)
+        );
+    }
+}
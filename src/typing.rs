@@ -0,0 +1,395 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! 输入状态（typing）的发送节流与接收过期。
+//!
+//! 本地：用户持续输入时每个 `(channel, action)` 最多每 `send_interval_ms`
+//! 发一次 `presence/typing`，停止或发出消息时补发一次「停止」。
+//!
+//! 远端：收到 [`TypingStatusNotification`] 后按 `(channel, user)` 记录，
+//! 超过 `ttl_ms` 没有续期即视为停止——停止事件可能丢失，不能依赖它。
+//! 发送方的续期间隔必须小于接收方 TTL，默认 5s / 6s。
+//!
+//! 群聊里多人同时输入时用 [`TypingTracker::summary`] 聚合成
+//! 「Alice and 2 others are typing」。
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::presence::{TypingActionType, TypingIndicatorRequest, TypingStatusNotification};
+
+/// 默认续发间隔（毫秒）。
+pub const DEFAULT_TYPING_SEND_INTERVAL_MS: i64 = 5_000;
+/// 默认远端状态过期时间（毫秒）。
+pub const DEFAULT_TYPING_TTL_MS: i64 = 6_000;
+
+/// 某个远端用户在某频道的输入状态。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteTyping {
+    pub user_id: u64,
+    pub username: Option<String>,
+    pub action: TypingActionType,
+    /// 本次开始输入的本地时间（毫秒），用于展示顺序
+    pub started_at_ms: i64,
+    /// 本地时间到达此值后过期
+    pub expires_at_ms: i64,
+    /// 最后一条通知的服务端时间戳，用于丢弃乱序到达的旧通知
+    last_event_ts: i64,
+}
+
+/// 聚合后的输入提示。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypingSummary {
+    /// 最早开始输入的用户
+    pub first_user_id: u64,
+    pub first_username: Option<String>,
+    /// 第二个用户（恰好两人时用于「A and B」）
+    pub second_username: Option<String>,
+    /// 除 `first` 之外还有几人
+    pub others: usize,
+    /// 所有人动作相同时为该动作，否则为 `Typing`
+    pub action: TypingActionType,
+}
+
+impl fmt::Display for TypingSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let first = self
+            .first_username
+            .clone()
+            .unwrap_or_else(|| self.first_user_id.to_string());
+        let verb = match self.action {
+            TypingActionType::Typing => "typing",
+            TypingActionType::Recording => "recording a voice message",
+            TypingActionType::UploadingPhoto => "sending a photo",
+            TypingActionType::UploadingVideo => "sending a video",
+            TypingActionType::UploadingFile => "sending a file",
+            TypingActionType::ChoosingSticker => "choosing a sticker",
+        };
+        match (self.others, &self.second_username) {
+            (0, _) => write!(f, "{first} is {verb}"),
+            (1, Some(second)) => write!(f, "{first} and {second} are {verb}"),
+            (1, None) => write!(f, "{first} and 1 other are {verb}"),
+            (n, _) => write!(f, "{first} and {n} others are {verb}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct OutgoingTyping {
+    channel_type: u8,
+    last_sent_ms: i64,
+}
+
+/// 输入状态追踪器（每个登录用户一个）。
+#[derive(Debug, Clone)]
+pub struct TypingTracker {
+    me: u64,
+    send_interval_ms: i64,
+    ttl_ms: i64,
+    outgoing: HashMap<(u64, TypingActionType), OutgoingTyping>,
+    remote: HashMap<u64, Vec<RemoteTyping>>,
+}
+
+impl TypingTracker {
+    pub fn new(me: u64) -> Self {
+        Self::with_timing(me, DEFAULT_TYPING_SEND_INTERVAL_MS, DEFAULT_TYPING_TTL_MS)
+    }
+
+    pub fn with_timing(me: u64, send_interval_ms: i64, ttl_ms: i64) -> Self {
+        Self {
+            me,
+            send_interval_ms,
+            ttl_ms,
+            outgoing: HashMap::new(),
+            remote: HashMap::new(),
+        }
+    }
+
+    // ---- 本地 ----
+
+    /// 本地有输入动作；需要发送时返回请求。
+    pub fn local_activity(
+        &mut self,
+        channel_id: u64,
+        channel_type: u8,
+        action: TypingActionType,
+        now_ms: i64,
+    ) -> Option<TypingIndicatorRequest> {
+        let key = (channel_id, action);
+        if let Some(state) = self.outgoing.get(&key) {
+            if now_ms - state.last_sent_ms < self.send_interval_ms {
+                return None;
+            }
+        }
+        self.outgoing.insert(
+            key,
+            OutgoingTyping {
+                channel_type,
+                last_sent_ms: now_ms,
+            },
+        );
+        Some(TypingIndicatorRequest {
+            channel_id,
+            channel_type,
+            is_typing: true,
+            action_type: action,
+        })
+    }
+
+    /// 本地停止某动作（清空输入框、取消录音等）；之前发过开始时返回停止请求。
+    pub fn local_stop(
+        &mut self,
+        channel_id: u64,
+        action: TypingActionType,
+    ) -> Option<TypingIndicatorRequest> {
+        let state = self.outgoing.remove(&(channel_id, action))?;
+        Some(TypingIndicatorRequest {
+            channel_id,
+            channel_type: state.channel_type,
+            is_typing: false,
+            action_type: action,
+        })
+    }
+
+    /// 本地发出了消息：清除该频道所有输入状态。接收方收到消息会自行清除，
+    /// 不必再发停止。
+    pub fn local_message_sent(&mut self, channel_id: u64) {
+        self.outgoing
+            .retain(|(channel, _), _| *channel != channel_id);
+    }
+
+    // ---- 远端 ----
+
+    /// 处理服务端推送的输入状态。自己（其他设备）的通知被忽略。
+    pub fn on_notification(&mut self, notification: &TypingStatusNotification, now_ms: i64) {
+        if notification.user_id == self.me {
+            return;
+        }
+        let entries = self.remote.entry(notification.channel_id).or_default();
+        let existing = entries
+            .iter()
+            .position(|e| e.user_id == notification.user_id);
+        if let Some(index) = existing {
+            if notification.timestamp < entries[index].last_event_ts {
+                return;
+            }
+        }
+        match (existing, notification.is_typing) {
+            (Some(index), true) => {
+                let entry = &mut entries[index];
+                entry.action = notification.action_type;
+                entry.expires_at_ms = now_ms + self.ttl_ms;
+                entry.last_event_ts = notification.timestamp;
+                if notification.username.is_some() {
+                    entry.username = notification.username.clone();
+                }
+            }
+            (None, true) => entries.push(RemoteTyping {
+                user_id: notification.user_id,
+                username: notification.username.clone(),
+                action: notification.action_type,
+                started_at_ms: now_ms,
+                expires_at_ms: now_ms + self.ttl_ms,
+                last_event_ts: notification.timestamp,
+            }),
+            (Some(index), false) => {
+                entries.remove(index);
+            }
+            (None, false) => {}
+        }
+        if entries.is_empty() {
+            self.remote.remove(&notification.channel_id);
+        }
+    }
+
+    /// 远端用户在频道里发出了消息：其输入状态随之结束。
+    pub fn on_remote_message(&mut self, channel_id: u64, from_uid: u64) {
+        if let Some(entries) = self.remote.get_mut(&channel_id) {
+            entries.retain(|e| e.user_id != from_uid);
+            if entries.is_empty() {
+                self.remote.remove(&channel_id);
+            }
+        }
+    }
+
+    /// 清理过期状态，返回状态有变化的频道。
+    pub fn expire(&mut self, now_ms: i64) -> Vec<u64> {
+        let mut changed = Vec::new();
+        self.remote.retain(|channel_id, entries| {
+            let before = entries.len();
+            entries.retain(|e| e.expires_at_ms > now_ms);
+            if entries.len() != before {
+                changed.push(*channel_id);
+            }
+            !entries.is_empty()
+        });
+        changed.sort_unstable();
+        changed
+    }
+
+    /// 频道内仍在输入的用户，按开始时间排序。
+    pub fn typing_in(&self, channel_id: u64, now_ms: i64) -> Vec<&RemoteTyping> {
+        let mut users: Vec<&RemoteTyping> = self
+            .remote
+            .get(&channel_id)
+            .into_iter()
+            .flatten()
+            .filter(|e| e.expires_at_ms > now_ms)
+            .collect();
+        users.sort_by_key(|e| (e.started_at_ms, e.user_id));
+        users
+    }
+
+    /// 聚合频道内的输入提示；无人输入时返回 `None`。
+    pub fn summary(&self, channel_id: u64, now_ms: i64) -> Option<TypingSummary> {
+        let users = self.typing_in(channel_id, now_ms);
+        let first = users.first()?;
+        let action = if users.iter().all(|e| e.action == first.action) {
+            first.action
+        } else {
+            TypingActionType::Typing
+        };
+        Some(TypingSummary {
+            first_user_id: first.user_id,
+            first_username: first.username.clone(),
+            second_username: users.get(1).and_then(|e| e.username.clone()),
+            others: users.len() - 1,
+            action,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ME: u64 = 1;
+    const GROUP: u64 = 100;
+
+    fn typing(user_id: u64, name: &str, is_typing: bool, ts: i64) -> TypingStatusNotification {
+        TypingStatusNotification {
+            user_id,
+            username: Some(name.to_string()),
+            channel_id: GROUP,
+            channel_type: 2,
+            is_typing,
+            action_type: TypingActionType::Typing,
+            timestamp: ts,
+        }
+    }
+
+    #[test]
+    fn outgoing_is_throttled_per_channel_and_action() {
+        let mut tracker = TypingTracker::with_timing(ME, 5_000, 6_000);
+        assert!(tracker
+            .local_activity(GROUP, 2, TypingActionType::Typing, 0)
+            .is_some());
+        assert!(tracker
+            .local_activity(GROUP, 2, TypingActionType::Typing, 4_999)
+            .is_none());
+        // 不同动作 / 不同频道各自节流
+        assert!(tracker
+            .local_activity(GROUP, 2, TypingActionType::Recording, 100)
+            .is_some());
+        assert!(tracker
+            .local_activity(7, 1, TypingActionType::Typing, 100)
+            .is_some());
+        let renewed = tracker
+            .local_activity(GROUP, 2, TypingActionType::Typing, 5_000)
+            .unwrap();
+        assert!(renewed.is_typing);
+
+        let stop = tracker.local_stop(GROUP, TypingActionType::Typing).unwrap();
+        assert!(!stop.is_typing);
+        assert_eq!(stop.channel_type, 2);
+        assert!(tracker
+            .local_stop(GROUP, TypingActionType::Typing)
+            .is_none());
+
+        tracker.local_message_sent(GROUP);
+        assert!(tracker
+            .local_stop(GROUP, TypingActionType::Recording)
+            .is_none());
+        assert!(tracker
+            .local_activity(GROUP, 2, TypingActionType::Recording, 5_100)
+            .is_some());
+    }
+
+    #[test]
+    fn remote_state_expires_without_stop() {
+        let mut tracker = TypingTracker::with_timing(ME, 5_000, 6_000);
+        tracker.on_notification(&typing(2, "Alice", true, 10), 0);
+        assert_eq!(tracker.typing_in(GROUP, 5_999).len(), 1);
+        assert!(tracker.typing_in(GROUP, 6_000).is_empty());
+        // 续期
+        tracker.on_notification(&typing(2, "Alice", true, 15), 5_000);
+        assert_eq!(tracker.expire(6_000), Vec::<u64>::new());
+        assert_eq!(tracker.expire(11_000), vec![GROUP]);
+        assert!(tracker.summary(GROUP, 11_000).is_none());
+    }
+
+    #[test]
+    fn stop_message_and_stale_events() {
+        let mut tracker = TypingTracker::new(ME);
+        tracker.on_notification(&typing(2, "Alice", true, 20), 0);
+        // 乱序到达的旧停止被忽略
+        tracker.on_notification(&typing(2, "Alice", false, 10), 0);
+        assert_eq!(tracker.typing_in(GROUP, 1).len(), 1);
+        tracker.on_notification(&typing(2, "Alice", false, 30), 0);
+        assert!(tracker.typing_in(GROUP, 1).is_empty());
+
+        tracker.on_notification(&typing(3, "Bob", true, 40), 0);
+        tracker.on_remote_message(GROUP, 3);
+        assert!(tracker.typing_in(GROUP, 1).is_empty());
+
+        // 自己其他设备的输入不展示
+        tracker.on_notification(&typing(ME, "me", true, 50), 0);
+        assert!(tracker.typing_in(GROUP, 1).is_empty());
+    }
+
+    #[test]
+    fn group_summary() {
+        let mut tracker = TypingTracker::new(ME);
+        tracker.on_notification(&typing(2, "Alice", true, 1), 0);
+        let summary = tracker.summary(GROUP, 1).unwrap();
+        assert_eq!(summary.to_string(), "Alice is typing");
+
+        tracker.on_notification(&typing(3, "Bob", true, 1), 10);
+        assert_eq!(
+            tracker.summary(GROUP, 11).unwrap().to_string(),
+            "Alice and Bob are typing"
+        );
+
+        let mut carol = typing(4, "Carol", true, 1);
+        carol.action_type = TypingActionType::Recording;
+        tracker.on_notification(&carol, 20);
+        let summary = tracker.summary(GROUP, 21).unwrap();
+        assert_eq!(summary.others, 2);
+        assert_eq!(summary.action, TypingActionType::Typing);
+        assert_eq!(summary.to_string(), "Alice and 2 others are typing");
+
+        let mut anonymous = TypingTracker::new(ME);
+        let mut dave = typing(5, "", true, 1);
+        dave.username = None;
+        dave.action_type = TypingActionType::Recording;
+        anonymous.on_notification(&dave, 0);
+        assert_eq!(
+            anonymous.summary(GROUP, 1).unwrap().to_string(),
+            "5 is recording a voice message"
+        );
+    }
+}
@@ -0,0 +1,490 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! 未读数 / 角标计算。
+//!
+//! 各端以前各自用 `ChannelUnreadSyncPayload.unread_count`、
+//! `MessageStatusCountResponse.unread_count` 和本地消息拼凑角标，结果不一致。
+//! [`UnreadCalculator`] 统一按「频道 pts + 已读游标」计算：
+//!
+//! - 每个频道记录最新 pts 与已读 pts（`pts <= read_pts` 的消息均已读）；
+//! - 已读游标只前进不后退，乱序到达的旧游标直接忽略；
+//! - @ 我的未读单独统计，已读游标越过即清除；
+//! - 总角标按 [`BadgePolicy`] 排除静音 / 隐藏频道；
+//! - 本地已读通过 [`UnreadCalculator::take_read_reports`] 合并成
+//!   `message/status/read_pts` 请求，每个频道在合并窗口内只上报一次最大值。
+//!
+//! 服务端快照（[`UnreadCalculator::apply_snapshot`]）只给出计数、不给出逐条
+//! pts，已读游标推进到快照 pts 之前时，快照部分的未读按
+//! `min(快照计数, 快照 pts - read_pts)` 估算（频道 pts 对消息稠密时即精确值）。
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Bound;
+
+use crate::notification_policy::NotificationPolicy;
+use crate::rpc::message::status::MessageStatusReadPtsRequest;
+
+/// 默认已读上报合并窗口（毫秒）。
+pub const DEFAULT_READ_REPORT_INTERVAL_MS: i64 = 1_000;
+
+/// 总角标统计口径。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BadgePolicy {
+    /// 静音频道的普通未读计入总角标
+    pub include_muted: bool,
+    /// 静音频道的 @ 我未读计入总角标（`include_muted` 为 true 时无意义）
+    pub include_muted_mentions: bool,
+    /// 隐藏频道计入总角标
+    pub include_hidden: bool,
+}
+
+impl Default for BadgePolicy {
+    fn default() -> Self {
+        Self {
+            include_muted: false,
+            include_muted_mentions: true,
+            include_hidden: false,
+        }
+    }
+}
+
+/// 单个频道的未读视图。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ChannelUnread {
+    pub channel_id: u64,
+    pub channel_type: u8,
+    pub latest_pts: u64,
+    pub read_pts: u64,
+    pub unread_count: u32,
+    pub mention_count: u32,
+    pub muted: bool,
+    pub hidden: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+struct ChannelState {
+    channel_type: u8,
+    latest_pts: u64,
+    read_pts: u64,
+    /// 服务端快照：`pts <= at_pts` 范围内的未读计数
+    snapshot: Option<(u64, u32)>,
+    /// 本地观察到的、`pts > read_pts` 的未读消息
+    unread: BTreeSet<u64>,
+    /// 其中 @ 我（或命中关键词）的消息
+    mentions: BTreeSet<u64>,
+    muted: bool,
+    hidden: bool,
+    /// 待上报的本地已读游标
+    pending_report: Option<u64>,
+    /// 本窗口第一次本地已读的时间
+    pending_since_ms: i64,
+    /// 已上报 / 已由服务端确认的游标
+    reported_pts: u64,
+}
+
+impl ChannelState {
+    fn unread_count(&self) -> u32 {
+        let (snapshot_pts, snapshot_count) = match self.snapshot {
+            Some((at, count)) if at > self.read_pts => {
+                let gap = u32::try_from(at - self.read_pts).unwrap_or(u32::MAX);
+                (at, count.min(gap))
+            }
+            _ => (0, 0),
+        };
+        let local = self
+            .unread
+            .range((Bound::Excluded(snapshot_pts), Bound::Unbounded))
+            .count() as u32;
+        snapshot_count.saturating_add(local)
+    }
+
+    fn advance_read(&mut self, read_pts: u64) -> bool {
+        if read_pts <= self.read_pts {
+            return false;
+        }
+        self.read_pts = read_pts;
+        self.latest_pts = self.latest_pts.max(read_pts);
+        match read_pts.checked_add(1) {
+            Some(first_unread) => {
+                self.unread = self.unread.split_off(&first_unread);
+                self.mentions = self.mentions.split_off(&first_unread);
+            }
+            None => {
+                self.unread.clear();
+                self.mentions.clear();
+            }
+        }
+        if self.snapshot.is_some_and(|(at, _)| at <= read_pts) {
+            self.snapshot = None;
+        }
+        true
+    }
+}
+
+/// 未读计算器。以 `channel_id` 为键（与 `MessageStatusReadPtsRequest` 一致）。
+#[derive(Debug, Clone)]
+pub struct UnreadCalculator {
+    channels: BTreeMap<u64, ChannelState>,
+    badge_policy: BadgePolicy,
+    report_interval_ms: i64,
+}
+
+impl Default for UnreadCalculator {
+    fn default() -> Self {
+        Self::new(BadgePolicy::default(), DEFAULT_READ_REPORT_INTERVAL_MS)
+    }
+}
+
+impl UnreadCalculator {
+    pub fn new(badge_policy: BadgePolicy, report_interval_ms: i64) -> Self {
+        Self {
+            channels: BTreeMap::new(),
+            badge_policy,
+            report_interval_ms,
+        }
+    }
+
+    fn state(&mut self, channel_id: u64, channel_type: u8) -> &mut ChannelState {
+        let state = self.channels.entry(channel_id).or_default();
+        state.channel_type = channel_type;
+        state
+    }
+
+    /// 应用服务端快照（同步 / `message/status/count` 的结果）。
+    ///
+    /// `latest_pts` 为快照时刻的频道最新 pts，`unread_count` 为该时刻的未读数。
+    /// 快照中的已读游标同样只前进不后退。
+    pub fn apply_snapshot(
+        &mut self,
+        channel_id: u64,
+        channel_type: u8,
+        read_pts: u64,
+        latest_pts: u64,
+        unread_count: u32,
+    ) {
+        let state = self.state(channel_id, channel_type);
+        state.advance_read(read_pts);
+        state.reported_pts = state.reported_pts.max(read_pts);
+        if state
+            .pending_report
+            .is_some_and(|p| p <= state.reported_pts)
+        {
+            state.pending_report = None;
+        }
+        state.latest_pts = state.latest_pts.max(latest_pts);
+        if latest_pts > state.read_pts && state.snapshot.is_none_or(|(at, _)| latest_pts >= at) {
+            state.snapshot = Some((latest_pts, unread_count));
+        }
+    }
+
+    /// 收到一条消息。`from_me` 的消息不计未读；`mentions_me` 包括命中关键词。
+    /// 重复 / 已读范围内的 pts 不会重复计数。
+    pub fn on_message(
+        &mut self,
+        channel_id: u64,
+        channel_type: u8,
+        pts: u64,
+        from_me: bool,
+        mentions_me: bool,
+    ) {
+        let state = self.state(channel_id, channel_type);
+        state.latest_pts = state.latest_pts.max(pts);
+        if from_me || pts <= state.read_pts {
+            return;
+        }
+        state.unread.insert(pts);
+        if mentions_me {
+            state.mentions.insert(pts);
+        }
+    }
+
+    /// 消息被撤回 / 删除：从未读中移除。
+    pub fn on_message_removed(&mut self, channel_id: u64, pts: u64) {
+        if let Some(state) = self.channels.get_mut(&channel_id) {
+            state.unread.remove(&pts);
+            state.mentions.remove(&pts);
+        }
+    }
+
+    /// 本设备阅读到 `read_pts`，会在合并窗口后经 [`Self::take_read_reports`] 上报。
+    pub fn mark_read(&mut self, channel_id: u64, channel_type: u8, read_pts: u64, now_ms: i64) {
+        let state = self.state(channel_id, channel_type);
+        if !state.advance_read(read_pts) || read_pts <= state.reported_pts {
+            return;
+        }
+        if state.pending_report.is_none() {
+            state.pending_since_ms = now_ms;
+        }
+        state.pending_report = Some(read_pts);
+    }
+
+    /// 读到频道最新消息。
+    pub fn mark_all_read(&mut self, channel_id: u64, channel_type: u8, now_ms: i64) {
+        let latest = self
+            .channels
+            .get(&channel_id)
+            .map(|s| s.latest_pts)
+            .unwrap_or(0);
+        self.mark_read(channel_id, channel_type, latest, now_ms);
+    }
+
+    /// 其他设备 / 服务端推来的已读游标：推进本地状态，不再上报。
+    pub fn apply_remote_read(&mut self, channel_id: u64, channel_type: u8, read_pts: u64) {
+        let state = self.state(channel_id, channel_type);
+        state.advance_read(read_pts);
+        state.reported_pts = state.reported_pts.max(read_pts);
+        if state
+            .pending_report
+            .is_some_and(|p| p <= state.reported_pts)
+        {
+            state.pending_report = None;
+        }
+    }
+
+    pub fn set_muted(&mut self, channel_id: u64, channel_type: u8, muted: bool) {
+        self.state(channel_id, channel_type).muted = muted;
+    }
+
+    pub fn set_hidden(&mut self, channel_id: u64, channel_type: u8, hidden: bool) {
+        self.state(channel_id, channel_type).hidden = hidden;
+    }
+
+    /// 按通知策略刷新所有已知频道的静音状态。
+    pub fn apply_notification_policy(&mut self, policy: &NotificationPolicy, now_ms: i64) {
+        for (channel_id, state) in &mut self.channels {
+            state.muted = policy
+                .rule_for(*channel_id, state.channel_type)
+                .is_muted(now_ms);
+        }
+    }
+
+    pub fn channel(&self, channel_id: u64) -> Option<ChannelUnread> {
+        self.channels.get(&channel_id).map(|s| ChannelUnread {
+            channel_id,
+            channel_type: s.channel_type,
+            latest_pts: s.latest_pts,
+            read_pts: s.read_pts,
+            unread_count: s.unread_count(),
+            mention_count: s.mentions.len() as u32,
+            muted: s.muted,
+            hidden: s.hidden,
+        })
+    }
+
+    pub fn unread_count(&self, channel_id: u64) -> u32 {
+        self.channels
+            .get(&channel_id)
+            .map(ChannelState::unread_count)
+            .unwrap_or(0)
+    }
+
+    pub fn mention_count(&self, channel_id: u64) -> u32 {
+        self.channels
+            .get(&channel_id)
+            .map(|s| s.mentions.len() as u32)
+            .unwrap_or(0)
+    }
+
+    /// 总角标（按 [`BadgePolicy`] 排除静音 / 隐藏频道）。
+    pub fn badge(&self) -> u32 {
+        let policy = &self.badge_policy;
+        self.channels
+            .values()
+            .filter(|s| policy.include_hidden || !s.hidden)
+            .map(|s| {
+                if !s.muted || policy.include_muted {
+                    s.unread_count()
+                } else if policy.include_muted_mentions {
+                    s.mentions.len() as u32
+                } else {
+                    0
+                }
+            })
+            .fold(0u32, u32::saturating_add)
+    }
+
+    /// 取出合并窗口已到期的已读上报，每个频道一条（取窗口内最大游标）。
+    pub fn take_read_reports(&mut self, now_ms: i64) -> Vec<MessageStatusReadPtsRequest> {
+        let interval = self.report_interval_ms;
+        self.drain_reports(|since| now_ms - since >= interval)
+    }
+
+    /// 立即取出所有待上报的已读（切后台 / 退出前调用）。
+    pub fn flush_read_reports(&mut self) -> Vec<MessageStatusReadPtsRequest> {
+        self.drain_reports(|_| true)
+    }
+
+    fn drain_reports(&mut self, due: impl Fn(i64) -> bool) -> Vec<MessageStatusReadPtsRequest> {
+        let mut reports = Vec::new();
+        for (channel_id, state) in &mut self.channels {
+            let Some(read_pts) = state.pending_report else {
+                continue;
+            };
+            if !due(state.pending_since_ms) {
+                continue;
+            }
+            state.pending_report = None;
+            state.reported_pts = state.reported_pts.max(read_pts);
+            reports.push(MessageStatusReadPtsRequest {
+                channel_id: *channel_id,
+                read_pts,
+                last_read_message_id: None,
+                client_visible_pts: Some(state.latest_pts),
+            });
+        }
+        reports
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DM: u64 = 1;
+    const GROUP: u64 = 2;
+
+    #[test]
+    fn counts_unread_and_mentions_above_read_cursor() {
+        let mut calc = UnreadCalculator::default();
+        for pts in 1..=5 {
+            calc.on_message(GROUP, 2, pts, false, pts == 4);
+        }
+        calc.on_message(GROUP, 2, 6, true, false);
+        calc.on_message(GROUP, 2, 3, false, false); // 重复
+        assert_eq!(calc.unread_count(GROUP), 5);
+        assert_eq!(calc.mention_count(GROUP), 1);
+
+        calc.mark_read(GROUP, 2, 4, 0);
+        assert_eq!(calc.unread_count(GROUP), 1);
+        assert_eq!(calc.mention_count(GROUP), 0);
+        calc.mark_all_read(GROUP, 2, 0);
+        assert_eq!(calc.unread_count(GROUP), 0);
+        assert_eq!(calc.channel(GROUP).unwrap().read_pts, 6);
+    }
+
+    #[test]
+    fn out_of_order_read_cursors_never_move_backwards() {
+        let mut calc = UnreadCalculator::default();
+        for pts in 1..=10 {
+            calc.on_message(DM, 1, pts, false, false);
+        }
+        calc.apply_remote_read(DM, 1, 8);
+        calc.apply_remote_read(DM, 1, 5);
+        assert_eq!(calc.unread_count(DM), 2);
+        // 已读范围内迟到的消息不计未读
+        calc.on_message(DM, 1, 7, false, true);
+        assert_eq!(calc.unread_count(DM), 2);
+        assert_eq!(calc.mention_count(DM), 0);
+        // 比远端游标旧的本地已读不上报
+        calc.mark_read(DM, 1, 6, 0);
+        assert!(calc.flush_read_reports().is_empty());
+    }
+
+    #[test]
+    fn read_reports_are_coalesced_per_channel() {
+        let mut calc = UnreadCalculator::new(BadgePolicy::default(), 1_000);
+        for pts in 1..=10 {
+            calc.on_message(DM, 1, pts, false, false);
+        }
+        calc.mark_read(DM, 1, 3, 100);
+        calc.mark_read(DM, 1, 7, 600);
+        assert!(calc.take_read_reports(1_000).is_empty());
+        let reports = calc.take_read_reports(1_100);
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].channel_id, DM);
+        assert_eq!(reports[0].read_pts, 7);
+        assert_eq!(reports[0].client_visible_pts, Some(10));
+        assert!(calc.take_read_reports(5_000).is_empty());
+
+        // 上报前被其他设备的更大游标覆盖 → 不再上报
+        calc.mark_read(DM, 1, 8, 5_000);
+        calc.apply_remote_read(DM, 1, 9);
+        assert!(calc.flush_read_reports().is_empty());
+    }
+
+    #[test]
+    fn snapshot_counts_are_bounded_by_read_progress() {
+        let mut calc = UnreadCalculator::default();
+        calc.apply_snapshot(GROUP, 2, 90, 100, 10);
+        assert_eq!(calc.unread_count(GROUP), 10);
+        calc.on_message(GROUP, 2, 101, false, false);
+        calc.on_message(GROUP, 2, 100, false, false); // 已含在快照中
+        assert_eq!(calc.unread_count(GROUP), 11);
+        calc.mark_read(GROUP, 2, 97, 0);
+        assert_eq!(calc.unread_count(GROUP), 4);
+        calc.mark_read(GROUP, 2, 100, 0);
+        assert_eq!(calc.unread_count(GROUP), 1);
+        // 快照带来的已读游标不触发上报
+        calc.apply_snapshot(DM, 1, 50, 50, 0);
+        let reports = calc.flush_read_reports();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].channel_id, GROUP);
+    }
+
+    #[test]
+    fn max_pts_does_not_overflow() {
+        let mut calc = UnreadCalculator::default();
+        calc.on_message(GROUP, 2, u64::MAX, false, true);
+        calc.apply_snapshot(GROUP, 2, 0, u64::MAX, 3);
+        assert_eq!(calc.unread_count(GROUP), 3);
+        calc.mark_read(GROUP, 2, u64::MAX, 0);
+        assert_eq!(calc.unread_count(GROUP), 0);
+        assert_eq!(calc.mention_count(GROUP), 0);
+    }
+
+    #[test]
+    fn badge_excludes_muted_and_hidden_channels() {
+        let mut calc = UnreadCalculator::default();
+        for pts in 1..=3 {
+            calc.on_message(DM, 1, pts, false, false);
+            calc.on_message(GROUP, 2, pts, false, pts == 2);
+            calc.on_message(3, 2, pts, false, false);
+        }
+        calc.set_hidden(3, 2, true);
+        assert_eq!(calc.badge(), 6);
+
+        let mut policy = NotificationPolicy::default();
+        policy.set_channel_muted(GROUP, 2, true);
+        calc.apply_notification_policy(&policy, 0);
+        assert!(calc.channel(GROUP).unwrap().muted);
+        assert_eq!(calc.badge(), 3 + 1);
+
+        let mut strict = UnreadCalculator::new(
+            BadgePolicy {
+                include_muted: false,
+                include_muted_mentions: false,
+                include_hidden: true,
+            },
+            0,
+        );
+        strict.on_message(GROUP, 2, 1, false, true);
+        strict.on_message(3, 2, 1, false, false);
+        strict.set_muted(GROUP, 2, true);
+        strict.set_hidden(3, 2, true);
+        assert_eq!(strict.badge(), 1);
+    }
+
+    #[test]
+    fn removed_messages_leave_the_count() {
+        let mut calc = UnreadCalculator::default();
+        calc.on_message(DM, 1, 1, false, true);
+        calc.on_message(DM, 1, 2, false, false);
+        calc.on_message_removed(DM, 1);
+        assert_eq!(calc.unread_count(DM), 1);
+        assert_eq!(calc.mention_count(DM), 0);
+    }
+}
@@ -0,0 +1,483 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Bot Webhook —— 投递到第三方 Bot HTTP 端点的 JSON 事件与签名。
+//!
+//! 第三方 Bot 开发者不走 msgtrans / FlatBuffers：server 把事件编码成
+//! [`WebhookEvent`] JSON，`POST` 到 Bot 登记的 URL。所有 u64 ID 一律编码为
+//! 十进制字符串（见 [`crate::serde_u64`]），避免 JS 端丢精度。
+//!
+//! ## 签名
+//!
+//! 每次投递带两个头：
+//!
+//! - [`TIMESTAMP_HEADER`]：投递时刻，Unix 秒；
+//! - [`SIGNATURE_HEADER`]：`v1=<hex>`，其中
+//!   `hex = lowercase_hex(HMAC-SHA256(secret, "{timestamp}.{body}"))`，`body`
+//!   是原始请求体字节（不要先解析再重新序列化）。密钥轮换期间可能出现多个
+//!   逗号分隔的 `v1=`，任一匹配即通过。
+//!
+//! 接收端必须同时校验时间戳落在容忍窗口内（默认
+//! [`DEFAULT_TOLERANCE_SECS`]），防止重放。
+//!
+//! ## 测试向量
+//!
+//! | secret (UTF-8) | timestamp | body (UTF-8) | signature |
+//! |---|---|---|---|
+//! | `whsec_test_secret` | `1770000000` | `{"schema_version":1,"event_id":"577943304432390144","type":"message"}` | `v1=587e388c6dc506cdd759ecd51104032b044c1e55ad2de6c451db3a61a6e04ac2` |
+//! | `whsec_test_secret` | `1770000000` | *(空)* | `v1=0fd92b64112cca538b3e4843c5595b74f38361506133a7bf95b16e331dc096b7` |
+//! | `机器人密钥` | `1770000123` | `{"content":"你好"}` | `v1=c2bfe71943cf2759bff1e0974dff6bfd0e33906a6c8f825d48696736be801d95` |
+
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use thiserror::Error;
+
+use crate::inbox_event::payloads::CallbackQueryPayload;
+use crate::rpc::account::{BotFollowResponse, BotUnfollowResponse};
+use crate::{
+    ContentMessageType, FlatBufferMessage, MessagePayloadEnvelope, ProtocolError,
+    PushMessageRequest,
+};
+
+/// 当前 webhook 事件 schema 版本。
+pub const WEBHOOK_SCHEMA_VERSION_V1: u32 = 1;
+
+/// 签名头：`v1=<hex>`
+pub const SIGNATURE_HEADER: &str = "X-PrivChat-Signature";
+/// 时间戳头：Unix 秒
+pub const TIMESTAMP_HEADER: &str = "X-PrivChat-Timestamp";
+/// 默认时间戳容忍窗口（秒）
+pub const DEFAULT_TOLERANCE_SECS: i64 = 300;
+
+const SIGNATURE_SCHEME: &str = "v1";
+
+/// Webhook 事件信封。
+///
+/// JSON 形如 `{"schema_version":1,"event_id":"…","bot_user_id":"…",
+/// "created_at":…,"type":"message","data":{…}}`。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebhookEvent {
+    pub schema_version: u32,
+    /// 全局唯一事件 ID；重投时不变，Bot 用它去重。
+    #[serde(with = "crate::serde_u64")]
+    pub event_id: u64,
+    /// 接收事件的 Bot
+    #[serde(with = "crate::serde_u64")]
+    pub bot_user_id: u64,
+    /// 事件发生时间，毫秒时间戳
+    pub created_at: i64,
+    #[serde(flatten)]
+    pub data: WebhookEventData,
+}
+
+/// 事件类型与数据。`type` 为事件名，`data` 为对应结构体。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum WebhookEventData {
+    /// Bot 所在频道收到新消息
+    Message(WebhookMessage),
+    /// 用户点击了 Bot 消息上的回调按钮
+    CallbackQuery(WebhookCallbackQuery),
+    /// 用户关注了 Bot
+    Follow(WebhookFollow),
+    /// 用户取消关注 Bot
+    Unfollow(WebhookUnfollow),
+}
+
+/// 新消息。v1 只投递文本层：媒体消息的 `content` 是说明文字，附件不随
+/// webhook 下发。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebhookMessage {
+    #[serde(with = "crate::serde_u64")]
+    pub server_message_id: u64,
+    #[serde(with = "crate::serde_u64")]
+    pub channel_id: u64,
+    pub channel_type: u8,
+    #[serde(with = "crate::serde_u64")]
+    pub from_uid: u64,
+    /// [`ContentMessageType::as_str`]，如 `"text"`；未知类型为 `"unknown"`
+    pub message_type: String,
+    #[serde(default)]
+    pub content: String,
+    #[serde(
+        default,
+        with = "crate::serde_u64::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub reply_to_message_id: Option<u64>,
+    #[serde(default, with = "crate::serde_u64::vec")]
+    pub mentioned_user_ids: Vec<u64>,
+    /// 发送时间，Unix 秒（同 `PushMessageRequest.timestamp`）
+    pub timestamp: u32,
+}
+
+impl WebhookMessage {
+    /// 从投递给 Bot 的推送构造；payload 解码失败时返回错误。
+    pub fn from_push(push: &PushMessageRequest) -> Result<Self, ProtocolError> {
+        let envelope = MessagePayloadEnvelope::decode_fb(&push.payload)?;
+        Ok(Self {
+            server_message_id: push.server_message_id,
+            channel_id: push.channel_id,
+            channel_type: push.channel_type,
+            from_uid: push.from_uid,
+            message_type: ContentMessageType::from_u32(push.message_type)
+                .map_or("unknown", ContentMessageType::as_str)
+                .to_string(),
+            content: envelope.content,
+            reply_to_message_id: envelope.reply_to_message_id,
+            mentioned_user_ids: envelope.mentioned_user_ids,
+            timestamp: push.timestamp,
+        })
+    }
+}
+
+/// 回调按钮点击。Bot 须调用 `bot/callback/answer` 应答 `query_id`。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebhookCallbackQuery {
+    #[serde(with = "crate::serde_u64")]
+    pub query_id: u64,
+    #[serde(with = "crate::serde_u64")]
+    pub from_user_id: u64,
+    #[serde(with = "crate::serde_u64")]
+    pub channel_id: u64,
+    pub channel_type: u8,
+    #[serde(with = "crate::serde_u64")]
+    pub server_message_id: u64,
+    pub data: String,
+}
+
+impl From<&CallbackQueryPayload> for WebhookCallbackQuery {
+    fn from(p: &CallbackQueryPayload) -> Self {
+        Self {
+            query_id: p.query_id,
+            from_user_id: p.from_user_id,
+            channel_id: p.channel_id,
+            channel_type: p.channel_type,
+            server_message_id: p.server_message_id,
+            data: p.data.clone(),
+        }
+    }
+}
+
+/// 用户关注了 Bot。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebhookFollow {
+    #[serde(with = "crate::serde_u64")]
+    pub user_id: u64,
+    /// 用户与 Bot 的私聊频道
+    #[serde(with = "crate::serde_u64")]
+    pub channel_id: u64,
+    /// true = 首次关注（或取消后重新关注）；false = 重复关注
+    pub created: bool,
+}
+
+impl WebhookFollow {
+    /// `user_id` 是发起关注的用户，`account/bot/follow` 的响应里没有它。
+    pub fn from_response(user_id: u64, resp: &BotFollowResponse) -> Self {
+        Self {
+            user_id,
+            channel_id: resp.channel_id,
+            created: resp.created,
+        }
+    }
+}
+
+/// 用户取消关注 Bot。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebhookUnfollow {
+    #[serde(with = "crate::serde_u64")]
+    pub user_id: u64,
+    /// 保留的私聊频道；从未关注过时为 0
+    #[serde(with = "crate::serde_u64")]
+    pub channel_id: u64,
+}
+
+impl WebhookUnfollow {
+    pub fn from_response(user_id: u64, resp: &BotUnfollowResponse) -> Self {
+        Self {
+            user_id,
+            channel_id: resp.channel_id,
+        }
+    }
+}
+
+impl WebhookEvent {
+    /// 构造 v1 事件（`schema_version` 自动填 1）。
+    pub fn new_v1(
+        event_id: u64,
+        bot_user_id: u64,
+        created_at: i64,
+        data: WebhookEventData,
+    ) -> Self {
+        Self {
+            schema_version: WEBHOOK_SCHEMA_VERSION_V1,
+            event_id,
+            bot_user_id,
+            created_at,
+            data,
+        }
+    }
+
+    /// 序列化成请求体字节。签名针对的就是这组字节。
+    pub fn to_json_bytes(&self) -> Result<Vec<u8>, serde_json::Error> {
+        serde_json::to_vec(self)
+    }
+}
+
+/// 签名校验失败原因。
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum WebhookSignatureError {
+    #[error("signature header has no v1 signature")]
+    Malformed,
+
+    #[error("timestamp {timestamp} is outside the {tolerance_secs}s tolerance window")]
+    TimestampOutOfTolerance { timestamp: i64, tolerance_secs: i64 },
+
+    #[error("signature mismatch")]
+    Mismatch,
+}
+
+fn mac(secret: &[u8], timestamp: i64, body: &[u8]) -> Hmac<Sha256> {
+    // HMAC 接受任意长度的 key，new_from_slice 不会失败。
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC accepts any key length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    mac
+}
+
+/// 计算签名头的值：`v1=<hex>`。
+pub fn sign(secret: &[u8], timestamp: i64, body: &[u8]) -> String {
+    let digest = mac(secret, timestamp, body).finalize().into_bytes();
+    let mut out = String::with_capacity(SIGNATURE_SCHEME.len() + 1 + digest.len() * 2);
+    out.push_str(SIGNATURE_SCHEME);
+    out.push('=');
+    for byte in digest {
+        out.push_str(&format!("{byte:02x}"));
+    }
+    out
+}
+
+/// 校验一次投递：时间戳在 `now_secs ± tolerance_secs` 内，且签名头里任一
+/// `v1=` 与 `body` 匹配（常量时间比较）。
+pub fn verify(
+    secret: &[u8],
+    timestamp: i64,
+    signature_header: &str,
+    body: &[u8],
+    now_secs: i64,
+    tolerance_secs: i64,
+) -> Result<(), WebhookSignatureError> {
+    let candidates: Vec<Vec<u8>> = signature_header
+        .split(',')
+        .filter_map(|part| part.trim().split_once('='))
+        .filter(|(scheme, _)| *scheme == SIGNATURE_SCHEME)
+        .filter_map(|(_, hex)| decode_hex(hex))
+        .collect();
+    if candidates.is_empty() {
+        return Err(WebhookSignatureError::Malformed);
+    }
+    // abs_diff：攻击者可控的 `t=` 取极值时 `now - t` 会溢出
+    if now_secs.abs_diff(timestamp) > tolerance_secs.max(0) as u64 {
+        return Err(WebhookSignatureError::TimestampOutOfTolerance {
+            timestamp,
+            tolerance_secs,
+        });
+    }
+    let expected = mac(secret, timestamp, body);
+    if candidates
+        .iter()
+        .any(|sig| expected.clone().verify_slice(sig).is_ok())
+    {
+        Ok(())
+    } else {
+        Err(WebhookSignatureError::Mismatch)
+    }
+}
+
+/// 只接受 SHA-256 长度（32 字节 / 64 个十六进制字符）的签名。
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() != 64 {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_770_000_000;
+
+    /// 与模块文档中的表格一致；各语言 Bot SDK 以此为准。
+    const VECTORS: &[(&str, i64, &str, &str)] = &[
+        (
+            "whsec_test_secret",
+            1_770_000_000,
+            r#"{"schema_version":1,"event_id":"577943304432390144","type":"message"}"#,
+            "v1=587e388c6dc506cdd759ecd51104032b044c1e55ad2de6c451db3a61a6e04ac2",
+        ),
+        (
+            "whsec_test_secret",
+            1_770_000_000,
+            "",
+            "v1=0fd92b64112cca538b3e4843c5595b74f38361506133a7bf95b16e331dc096b7",
+        ),
+        (
+            "机器人密钥",
+            1_770_000_123,
+            r#"{"content":"你好"}"#,
+            "v1=c2bfe71943cf2759bff1e0974dff6bfd0e33906a6c8f825d48696736be801d95",
+        ),
+    ];
+
+    #[test]
+    fn signature_test_vectors() {
+        for (secret, timestamp, body, signature) in VECTORS {
+            assert_eq!(
+                sign(secret.as_bytes(), *timestamp, body.as_bytes()),
+                *signature
+            );
+            assert_eq!(
+                verify(
+                    secret.as_bytes(),
+                    *timestamp,
+                    signature,
+                    body.as_bytes(),
+                    *timestamp,
+                    DEFAULT_TOLERANCE_SECS
+                ),
+                Ok(())
+            );
+        }
+    }
+
+    #[test]
+    fn verify_rejects_tampering_replay_and_junk() {
+        let (secret, body) = (b"whsec_test_secret".as_slice(), b"{}".as_slice());
+        let sig = sign(secret, NOW, body);
+
+        assert_eq!(
+            verify(secret, NOW, &sig, b"{ }", NOW, 300),
+            Err(WebhookSignatureError::Mismatch)
+        );
+        assert_eq!(
+            verify(b"other", NOW, &sig, body, NOW, 300),
+            Err(WebhookSignatureError::Mismatch)
+        );
+        assert!(verify(secret, NOW, &sig, body, NOW + 300, 300).is_ok());
+        assert_eq!(
+            verify(secret, NOW, &sig, body, NOW + 301, 300),
+            Err(WebhookSignatureError::TimestampOutOfTolerance {
+                timestamp: NOW,
+                tolerance_secs: 300
+            })
+        );
+        for junk in ["", "v1=", "v1=zz", "v0=abcd", "sha256=abcd"] {
+            assert_eq!(
+                verify(secret, NOW, junk, body, NOW, 300),
+                Err(WebhookSignatureError::Malformed),
+                "{junk:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn extreme_timestamps_are_out_of_tolerance() {
+        let (secret, body) = (b"whsec_test_secret".as_slice(), b"{}".as_slice());
+        for t in [i64::MIN, i64::MAX] {
+            let sig = sign(secret, t, body);
+            assert_eq!(
+                verify(secret, t, &sig, body, NOW, 300),
+                Err(WebhookSignatureError::TimestampOutOfTolerance {
+                    timestamp: t,
+                    tolerance_secs: 300
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn any_signature_matches_during_secret_rotation() {
+        let body = b"{}";
+        let header = format!("{}, {}", sign(b"old", NOW, body), sign(b"new", NOW, body));
+        assert!(verify(b"old", NOW, &header, body, NOW, 300).is_ok());
+        assert!(verify(b"new", NOW, &header, body, NOW, 300).is_ok());
+    }
+
+    #[test]
+    fn ids_go_out_as_strings() {
+        let event = WebhookEvent::new_v1(
+            577_943_304_432_390_144,
+            100_002_888,
+            1_770_000_000_000,
+            WebhookEventData::Follow(WebhookFollow::from_response(
+                10001,
+                &BotFollowResponse {
+                    bot_user_id: 100_002_888,
+                    channel_id: 608_993_815_990_284_288,
+                    account_user_type: 2,
+                    followed: true,
+                    created: true,
+                },
+            )),
+        );
+        let json: serde_json::Value =
+            serde_json::from_slice(&event.to_json_bytes().unwrap()).unwrap();
+        assert_eq!(json["event_id"], "577943304432390144");
+        assert_eq!(json["bot_user_id"], "100002888");
+        assert_eq!(json["type"], "follow");
+        assert_eq!(json["data"]["user_id"], "10001");
+        assert_eq!(json["data"]["channel_id"], "608993815990284288");
+
+        let back: WebhookEvent = serde_json::from_value(json).unwrap();
+        assert_eq!(back, event);
+    }
+
+    #[test]
+    fn message_event_from_push() {
+        let envelope = MessagePayloadEnvelope {
+            content: "/start".to_string(),
+            reply_to_message_id: Some(7),
+            mentioned_user_ids: vec![100_002_888],
+            ..Default::default()
+        };
+        let push = PushMessageRequest {
+            server_message_id: 608_993_815_990_284_288,
+            channel_id: 42,
+            channel_type: 1,
+            from_uid: 10001,
+            message_type: ContentMessageType::Text.as_u32(),
+            timestamp: 1_770_000_000,
+            payload: crate::encode_message(&envelope).unwrap(),
+            ..Default::default()
+        };
+        let message = WebhookMessage::from_push(&push).unwrap();
+        assert_eq!(message.message_type, "text");
+        assert_eq!(message.content, "/start");
+
+        let json = serde_json::to_value(WebhookEventData::Message(message)).unwrap();
+        assert_eq!(json["type"], "message");
+        assert_eq!(json["data"]["server_message_id"], "608993815990284288");
+        assert_eq!(json["data"]["reply_to_message_id"], "7");
+        assert_eq!(json["data"]["mentioned_user_ids"][0], "100002888");
+    }
+}
@@ -77,6 +77,7 @@ fn send_message_request_roundtrip() {
     msg.channel_id = 10001;
     msg.message_type = 99;
     msg.expire = 3600;
+    msg.expire_mode = ExpireMode::AfterRead;
     msg.from_uid = 20002;
     msg.topic = "general".to_string();
     msg.payload = b"hello world \x00\xff".to_vec();
@@ -89,6 +90,7 @@ fn send_message_request_roundtrip() {
     assert_eq!(got.channel_id, msg.channel_id);
     assert_eq!(got.message_type, msg.message_type);
     assert_eq!(got.expire, msg.expire);
+    assert_eq!(got.expire_mode, msg.expire_mode);
     assert_eq!(got.from_uid, msg.from_uid);
     assert_eq!(got.topic, msg.topic);
     assert_eq!(got.payload, msg.payload);
@@ -132,6 +134,7 @@ fn push_message_request_roundtrip() {
         from_uid: 99,
         payload: vec![1, 2, 3, 4, 5],
         deleted: true,
+        expire_mode: ExpireMode::AfterRead,
     };
     let got = roundtrip(&msg);
     assert_eq!(got.msg_key, msg.msg_key);
@@ -139,6 +142,8 @@ fn push_message_request_roundtrip() {
     assert_eq!(got.timestamp, msg.timestamp);
     assert_eq!(got.payload, msg.payload);
     assert_eq!(got.deleted, msg.deleted);
+    assert_eq!(got.expire, msg.expire);
+    assert_eq!(got.expire_mode, msg.expire_mode);
 }
 
 #[test]
@@ -180,6 +185,7 @@ fn push_batch_roundtrip() {
         from_uid: 0,
         payload: b"abc".to_vec(),
         deleted: false,
+        expire_mode: ExpireMode::AfterSend,
     };
     let msg = PushBatchRequest {
         messages: vec![inner.clone(), inner],
@@ -291,6 +297,42 @@ fn rpc_response_roundtrip() {
     assert_eq!(got.data, None);
 }
 
+#[test]
+fn rpc_response_error_detail_roundtrip() {
+    let resp = RpcResponse::from_error(
+        ErrorCode::UploadMissingRanges,
+        ErrorDetail::MissingRanges {
+            ranges: vec![ByteRange {
+                offset: 8192,
+                length: 4096,
+            }],
+        },
+    );
+    let got = roundtrip(&resp);
+    assert_eq!(got.code, 20615);
+    assert_eq!(got.detail, resp.detail);
+    let err = got.into_result::<bool>().unwrap_err();
+    assert_eq!(err.error_code(), Some(ErrorCode::UploadMissingRanges));
+    assert!(matches!(
+        err.detail,
+        Some(ErrorDetail::MissingRanges { .. })
+    ));
+
+    // Old responses have no detail
+    let got = roundtrip(&RpcResponse::error(10300, "slow down".to_string()));
+    assert_eq!(got.detail, None);
+    assert_eq!(got.into_empty_result().unwrap_err().retry_after_ms(), None);
+
+    let ok = roundtrip(&RpcResponse::success(b"true".to_vec()));
+    assert_eq!(ok.into_result::<bool>(), Ok(true));
+    let empty = roundtrip(&RpcResponse::success_empty());
+    assert_eq!(empty.into_result::<Option<u64>>(), Ok(None));
+    let bad = RpcResponse::success(b"{".to_vec())
+        .into_result::<bool>()
+        .unwrap_err();
+    assert_eq!(bad.error_code(), Some(ErrorCode::DecodingError));
+}
+
 #[test]
 fn transfer_request_roundtrip() {
     let req = TransferRequest {
@@ -780,6 +822,7 @@ fn payload_envelope_forward_roundtrip() {
                     extra: vec![],
                 },
             ],
+            items: vec![],
         })),
         reply_to_message_id: None,
         mentioned_user_ids: vec![],
@@ -799,6 +842,162 @@ fn payload_envelope_forward_roundtrip() {
     }
 }
 
+fn bundle_of(depth: usize) -> MessagePayloadEnvelope {
+    use privchat_protocol::message::ContentMessageType;
+    let mut env = MessagePayloadEnvelope {
+        content: "leaf".to_string(),
+        ..Default::default()
+    };
+    for level in 0..depth {
+        env = MessagePayloadEnvelope {
+            content: format!("bundle {level}"),
+            metadata: Some(MessageMetadata::Forward(ForwardMetadata {
+                messages: vec![],
+                items: vec![ForwardBundleItem {
+                    original_message_id: Some(900 + level as u64),
+                    sender_id: 42,
+                    sender_name: "Alice".to_string(),
+                    timestamp: 1_770_000_000_000,
+                    message_type: ContentMessageType::Forward,
+                    payload: env,
+                }],
+            })),
+            ..Default::default()
+        };
+    }
+    env
+}
+
+#[test]
+fn payload_envelope_forward_bundle_roundtrip() {
+    use privchat_protocol::message::ContentMessageType;
+    let env = MessagePayloadEnvelope {
+        content: "[Chat history]".to_string(),
+        metadata: Some(MessageMetadata::Forward(ForwardMetadata {
+            messages: vec![],
+            items: vec![
+                ForwardBundleItem {
+                    original_message_id: Some(501),
+                    sender_id: 10001,
+                    sender_name: "Alice".to_string(),
+                    timestamp: 1_770_000_000_000,
+                    message_type: ContentMessageType::Text,
+                    payload: MessagePayloadEnvelope {
+                        content: "hello".to_string(),
+                        ..Default::default()
+                    },
+                },
+                ForwardBundleItem {
+                    original_message_id: None,
+                    sender_id: 10002,
+                    sender_name: "Bob".to_string(),
+                    timestamp: 1_770_000_001_000,
+                    message_type: ContentMessageType::Image,
+                    payload: MessagePayloadEnvelope {
+                        content: String::new(),
+                        metadata: Some(MessageMetadata::Image(ImageMetadata {
+                            file_id: 77,
+                            thumbnail_file_id: Some(78),
+                            ..Default::default()
+                        })),
+                        ..Default::default()
+                    },
+                },
+            ],
+        })),
+        ..Default::default()
+    };
+    assert_eq!(env.forward_depth(), 1);
+    let got = roundtrip(&env);
+    assert_eq!(got, env);
+}
+
+#[test]
+fn payload_envelope_forward_bundle_depth_is_capped() {
+    let max = bundle_of(FORWARD_BUNDLE_MAX_DEPTH);
+    assert_eq!(roundtrip(&max), max);
+
+    let too_deep = bundle_of(FORWARD_BUNDLE_MAX_DEPTH + 1);
+    assert!(matches!(
+        encode_message(&too_deep),
+        Err(ProtocolError::InvalidValue(_))
+    ));
+}
+
+#[test]
+fn payload_envelope_interactive_roundtrip() {
+    let env = MessagePayloadEnvelope {
+        content: "Pick one".to_string(),
+        metadata: Some(MessageMetadata::Interactive(InteractiveMetadata {
+            rows: vec![
+                vec![
+                    InlineButton {
+                        text: "Yes".to_string(),
+                        action: InlineButtonAction::Callback {
+                            data: "vote:1".to_string(),
+                        },
+                    },
+                    InlineButton {
+                        text: "No".to_string(),
+                        action: InlineButtonAction::Callback {
+                            data: "vote:0".to_string(),
+                        },
+                    },
+                ],
+                vec![
+                    InlineButton {
+                        text: "Docs".to_string(),
+                        action: InlineButtonAction::Url {
+                            url: "https://privchat.dev".to_string(),
+                        },
+                    },
+                    InlineButton {
+                        text: "Share".to_string(),
+                        action: InlineButtonAction::SwitchToChat {
+                            query: "poll".to_string(),
+                            same_chat: false,
+                        },
+                    },
+                ],
+            ],
+        })),
+        ..Default::default()
+    };
+    let got = roundtrip(&env);
+    assert_eq!(got, env);
+    if let Some(MessageMetadata::Interactive(m)) = &got.metadata {
+        assert_eq!(
+            m.find_callback("vote:0").map(|b| b.text.as_str()),
+            Some("No")
+        );
+        assert!(m.find_callback("vote:2").is_none());
+    }
+}
+
+#[test]
+fn payload_envelope_interactive_callback_data_is_size_limited() {
+    let keyboard = |data: String| MessagePayloadEnvelope {
+        metadata: Some(MessageMetadata::Interactive(InteractiveMetadata {
+            rows: vec![vec![InlineButton {
+                text: "Go".to_string(),
+                action: InlineButtonAction::Callback { data },
+            }]],
+        })),
+        ..Default::default()
+    };
+    let max = keyboard("x".repeat(INLINE_CALLBACK_DATA_MAX_BYTES));
+    assert_eq!(roundtrip(&max), max);
+    for bad in [
+        String::new(),
+        "x".repeat(INLINE_CALLBACK_DATA_MAX_BYTES + 1),
+    ] {
+        assert!(matches!(
+            encode_message(&keyboard(bad)),
+            Err(ProtocolError::InvalidValue(_))
+        ));
+    }
+}
+
 #[test]
 fn payload_envelope_link_roundtrip() {
     let env = MessagePayloadEnvelope {